@@ -0,0 +1,46 @@
+//! Prints differences between two packages, one changed entry per line.
+//!
+//! Usage: `cargo run --example diff -- <old package> <new package>`
+//!
+//! Packages are read in PKG format if their file extension is `.pkg`, and in DAT format otherwise.
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use ftldat::Package;
+use ftldat::error::PackageReadError;
+
+fn read_package(path: &str) -> Result<Package, PackageReadError> {
+    match Path::new(path).extension().and_then(|extension| extension.to_str()) {
+        Some("pkg") => Package::from_path_pkg(path),
+        _ => Package::from_path_dat(path),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() != 2 {
+        eprintln!("Usage: diff <old package> <new package>");
+        return ExitCode::from(2);
+    }
+
+    let result = read_package(&args[0])
+        .and_then(|old| Ok((old, read_package(&args[1])?)))
+        .map_err(|error| error.to_string())
+        .and_then(|(old, new)| old.diff(&new).map_err(|error| error.to_string()));
+
+    match result {
+        Ok(diff) => {
+            print!("{}", diff);
+            eprintln!(
+                "{} added, {} removed, {} modified, {} unchanged",
+                diff.added().len(), diff.removed().len(), diff.modified().len(), diff.unchanged().len()
+            );
+            if diff.is_empty() { ExitCode::SUCCESS } else { ExitCode::from(1) }
+        }
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::from(2)
+        }
+    }
+}
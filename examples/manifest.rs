@@ -0,0 +1,37 @@
+//! Prints the manifest of a package: path, size and content hash of each entry.
+//!
+//! Usage: `cargo run --example manifest -- <package>`
+//!
+//! Packages are read in PKG format if their file extension is `.pkg`, and in DAT format otherwise.
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use ftldat::{Manifest, Package};
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() != 1 {
+        eprintln!("Usage: manifest <package>");
+        return ExitCode::from(2);
+    }
+
+    let path = &args[0];
+    let package = match Path::new(path).extension().and_then(|extension| extension.to_str()) {
+        Some("pkg") => Package::from_path_pkg(path),
+        _ => Package::from_path_dat(path),
+    };
+
+    let result = package
+        .map_err(|error| error.to_string())
+        .and_then(|package| Manifest::from_package(&package).map_err(|error| error.to_string()))
+        .and_then(|manifest| manifest.to_output(std::io::stdout().lock()).map_err(|error| error.to_string()));
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,3 @@
+//! Builds the C ABI of `ftldat`, declared in `include/ftldat.h`, as a dynamic and a static library.
+
+pub use ftldat::ffi::*;
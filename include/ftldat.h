@@ -0,0 +1,165 @@
+/* Generated by cbindgen from src/ffi; do not edit. */
+
+#ifndef FTLDAT_H
+#define FTLDAT_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Result codes of fallible functions.
+typedef enum FtldatResult {
+  FTLDAT_RESULT_OK = 0,
+  // A pointer was NULL, or a string was not valid UTF-8.
+  FTLDAT_RESULT_INVALID_ARGUMENT = 1,
+  // Reading a package or entry failed.
+  FTLDAT_RESULT_READ_ERROR = 2,
+  // Writing a package failed.
+  FTLDAT_RESULT_WRITE_ERROR = 3,
+  // There is no entry under the specified inner path.
+  FTLDAT_RESULT_NOT_FOUND = 4,
+  // An entry already exists under the specified inner path.
+  FTLDAT_RESULT_ALREADY_EXISTS = 5,
+  // The caller-provided buffer is too small; the required length has been reported.
+  FTLDAT_RESULT_BUFFER_TOO_SMALL = 6,
+  // An internal error occurred; the package may be left in an inconsistent state.
+  FTLDAT_RESULT_PANIC = 7,
+} FtldatResult;
+
+// Opaque handle of a [`Package`].
+typedef struct FtldatPackage FtldatPackage;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Copies the message of the last error that occurred on the calling thread into `buffer`,
+// NUL-terminated and truncated to fit, and returns the full length of the message.
+//
+// # Safety
+// `buffer` must be NULL, or valid for writes of `buffer_size` bytes.
+size_t ftldat_last_error_message(char *buffer, size_t buffer_size);
+
+// Creates a new, empty package.
+struct FtldatPackage *ftldat_package_new(void);
+
+// Releases a package handle. Does nothing if `package` is NULL.
+//
+// # Safety
+// `package` must be NULL, or a handle that has not been released yet.
+void ftldat_package_free(struct FtldatPackage *package);
+
+// Reads the DAT package at `source_path`, and stores its handle in `out_package`.
+//
+// # Safety
+// `source_path` must be a NUL-terminated string, and `out_package` must be valid for writes.
+enum FtldatResult ftldat_package_open_dat(const char *source_path,
+                                          struct FtldatPackage **out_package);
+
+// Reads the PKG package at `source_path`, and stores its handle in `out_package`.
+//
+// # Safety
+// `source_path` must be a NUL-terminated string, and `out_package` must be valid for writes.
+enum FtldatResult ftldat_package_open_pkg(const char *source_path,
+                                          struct FtldatPackage **out_package);
+
+// Writes the package in DAT format to `destination_path`, which may be the file it was read
+// from.
+//
+// If writing fails, the package is left unchanged.
+//
+// # Safety
+// `package` must be a valid handle, and `destination_path` a NUL-terminated string.
+enum FtldatResult ftldat_package_save_dat(struct FtldatPackage *package,
+                                          const char *destination_path);
+
+// Writes the package in PKG format to `destination_path`, which may be the file it was read
+// from.
+//
+// If writing fails, the package is left unchanged.
+//
+// # Safety
+// `package` must be a valid handle, and `destination_path` a NUL-terminated string.
+enum FtldatResult ftldat_package_save_pkg(struct FtldatPackage *package,
+                                          const char *destination_path);
+
+// Returns the number of entries in the package, or 0 if `package` is NULL.
+//
+// # Safety
+// `package` must be NULL, or a valid handle.
+size_t ftldat_package_entry_count(const struct FtldatPackage *package);
+
+// Copies the inner path of the entry at `index`, in package order, into `buffer` as a
+// NUL-terminated string, and stores its length (without the NUL) in `out_length`.
+//
+// # Safety
+// `package` must be a valid handle, `buffer` must be NULL or valid for writes of `buffer_size`
+// bytes, and `out_length` must be NULL or valid for writes.
+enum FtldatResult ftldat_package_entry_path(const struct FtldatPackage *package,
+                                            size_t index,
+                                            char *buffer,
+                                            size_t buffer_size,
+                                            size_t *out_length);
+
+// Returns `true` if the package has an entry under `inner_path`.
+//
+// # Safety
+// `package` must be NULL or a valid handle, and `inner_path` NULL or a NUL-terminated string.
+bool ftldat_package_entry_exists(const struct FtldatPackage *package, const char *inner_path);
+
+// Stores the content size of the entry under `inner_path` in `out_size`.
+//
+// # Safety
+// `package` must be a valid handle, `inner_path` a NUL-terminated string, and `out_size` valid
+// for writes.
+enum FtldatResult ftldat_package_content_size(const struct FtldatPackage *package,
+                                              const char *inner_path,
+                                              uint64_t *out_size);
+
+// Copies the content of the entry under `inner_path` into `buffer`, and stores its length in
+// `out_length`.
+//
+// # Safety
+// `package` must be a valid handle, `inner_path` a NUL-terminated string, `buffer` NULL or valid
+// for writes of `buffer_size` bytes, and `out_length` NULL or valid for writes.
+enum FtldatResult ftldat_package_read_content(const struct FtldatPackage *package,
+                                              const char *inner_path,
+                                              uint8_t *buffer,
+                                              size_t buffer_size,
+                                              size_t *out_length);
+
+// Puts a copy of `content` into the package under `inner_path`, replacing any existing entry.
+//
+// # Safety
+// `package` must be a valid handle, `inner_path` a NUL-terminated string, and `content` valid
+// for reads of `content_length` bytes (or NULL, if `content_length` is 0).
+enum FtldatResult ftldat_package_put_entry(struct FtldatPackage *package,
+                                           const char *inner_path,
+                                           const uint8_t *content,
+                                           size_t content_length);
+
+// Adds a copy of `content` to the package under `inner_path`, or returns
+// [`FtldatResult::AlreadyExists`] if an entry already exists there.
+//
+// # Safety
+// `package` must be a valid handle, `inner_path` a NUL-terminated string, and `content` valid
+// for reads of `content_length` bytes (or NULL, if `content_length` is 0).
+enum FtldatResult ftldat_package_add_entry(struct FtldatPackage *package,
+                                           const char *inner_path,
+                                           const uint8_t *content,
+                                           size_t content_length);
+
+// Removes the entry under `inner_path`, or returns [`FtldatResult::NotFound`] if there is none.
+//
+// # Safety
+// `package` must be a valid handle, and `inner_path` a NUL-terminated string.
+enum FtldatResult ftldat_package_remove_entry(struct FtldatPackage *package,
+                                              const char *inner_path);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* FTLDAT_H */
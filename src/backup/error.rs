@@ -0,0 +1,23 @@
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+use crate::error::PackageWriteError;
+
+#[derive(Error, Debug)]
+pub enum BackupError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    WriteError(#[from] PackageWriteError),
+    #[error("backup state uses hash algorithm '{0}', but this build uses '{}'", crate::ContentHash::ALGORITHM)]
+    HashAlgorithmMismatchError(String),
+    #[error("line {0} of backup state file is malformed")]
+    InvalidStateError(usize),
+    #[error("no backup of '{}' exists", .0.display())]
+    MissingBackupError(PathBuf),
+    #[error("backup '{}' does not match the hash recorded when it was taken", .0.display())]
+    CorruptBackupError(PathBuf),
+    #[error("'{}' was replaced since its backup was taken, likely by a game update; the backup has to be discarded", .0.display())]
+    StaleBackupError(PathBuf),
+}
@@ -0,0 +1,302 @@
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::{ContentHash, DatWriter, Package, PackageWriter, PkgWriter};
+use crate::backup::error::BackupError;
+use crate::shared::hash::ContentHasher;
+use crate::shared::writer::write_via_temporary_file;
+
+pub(crate) mod error;
+
+// Backups consist of two files next to the package:
+// - `<package>.bak` := unmodified copy of the original package
+// - `<package>.bak.state` := UTF-8 text with the following structure:
+//   - header line: `#ftldat-backup hash=<algorithm>`
+//   - `original\t<hash>\t<size>` := fingerprint of the original package
+//   - optionally `installed\t<hash>\t<size>` := fingerprint of the package as last written with a backup
+//
+// Hashes are hexadecimal content hashes of the whole file, see `ContentHash`.
+
+static BACKUP_EXTENSION: &str = ".bak";
+static STATE_EXTENSION: &str = ".bak.state";
+static HEADER_PREFIX: &str = "#ftldat-backup hash=";
+static ORIGINAL_KEY: &str = "original";
+static INSTALLED_KEY: &str = "installed";
+
+/// State of a package file relative to its [`PackageBackup`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackupStatus {
+    /// No backup of the package has been taken yet.
+    Missing,
+    /// The package is identical to the backed up original.
+    Original,
+    /// The package was last written by [`Package::into_path_with_backup`], or is missing and can be
+    /// restored.
+    Modified,
+    /// The package matches neither the backed up original nor what was last written, most likely
+    /// because a game update replaced it. The backup must be discarded before a new one is taken.
+    Stale,
+}
+
+/// Size and content hash of a whole file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Fingerprint {
+    hash: ContentHash,
+    size: u64,
+}
+
+impl Fingerprint {
+    /// Returns the fingerprint of the file at the specified path, or `None` if it does not exist.
+    ///
+    /// The file is streamed through the hasher rather than read into memory at once.
+    fn of_path(path: &Path) -> Result<Option<Fingerprint>, std::io::Error> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error),
+        };
+
+        let mut hasher = ContentHasher::new();
+        let size = std::io::copy(&mut BufReader::new(file), &mut hasher)?;
+        Ok(Some(Fingerprint { hash: hasher.finish(), size }))
+    }
+}
+
+#[derive(Debug)]
+struct BackupState {
+    original: Fingerprint,
+    installed: Option<Fingerprint>,
+}
+
+/// Pristine copy of a game package, kept next to it so that the package can be modded and later
+/// restored to its original state.
+///
+/// The backup is taken the first time the package is written by
+/// [`Package::into_path_with_backup`], or explicitly with [`PackageBackup::create`].
+#[derive(Debug, Clone)]
+pub struct PackageBackup {
+    package_path: PathBuf,
+}
+
+impl PackageBackup {
+    /// Creates a handle for the backup of the package at the specified path. No files are
+    /// accessed until one of its methods is called.
+    pub fn new<P: AsRef<Path>>(package_path: P) -> PackageBackup {
+        PackageBackup { package_path: package_path.as_ref().to_path_buf() }
+    }
+
+    /// Returns the path of the backed up package.
+    pub fn package_path(&self) -> &Path {
+        &self.package_path
+    }
+
+    /// Returns the path of the backup copy, which is the package path with `.bak` appended
+    /// (e.g. `resource.dat.bak`).
+    pub fn backup_path(&self) -> PathBuf {
+        self.sidecar_path(BACKUP_EXTENSION)
+    }
+
+    fn state_path(&self) -> PathBuf {
+        self.sidecar_path(STATE_EXTENSION)
+    }
+
+    fn sidecar_path(&self, extension: &str) -> PathBuf {
+        let mut path = OsString::from(&self.package_path);
+        path.push(extension);
+        PathBuf::from(path)
+    }
+
+    /// Determines the state of the package relative to this backup.
+    ///
+    /// This hashes the whole package file, streaming it rather than reading it into memory.
+    pub fn status(&self) -> Result<BackupStatus, BackupError> {
+        let state = match self.read_state()? {
+            Some(state) => state,
+            None => return Ok(BackupStatus::Missing),
+        };
+
+        let status = match Fingerprint::of_path(&self.package_path)? {
+            None => BackupStatus::Modified,
+            Some(current) if current == state.original => BackupStatus::Original,
+            Some(current) if Some(&current) == state.installed.as_ref() => BackupStatus::Modified,
+            Some(_) => BackupStatus::Stale,
+        };
+        Ok(status)
+    }
+
+    /// Takes a backup of the package, unless one already exists.
+    ///
+    /// Returns `true` if a new backup was taken. Returns a [BackupError::StaleBackupError] if the
+    /// existing backup is stale.
+    pub fn create(&self) -> Result<bool, BackupError> {
+        match self.status()? {
+            BackupStatus::Missing => {}
+            BackupStatus::Stale => return Err(BackupError::StaleBackupError(self.package_path.clone())),
+            BackupStatus::Original | BackupStatus::Modified => return Ok(false),
+        }
+
+        let original = Fingerprint::of_path(&self.package_path)?
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound))?;
+        let backup_path = self.backup_path();
+        std::fs::copy(&self.package_path, &backup_path)?;
+        if Fingerprint::of_path(&backup_path)?.as_ref() != Some(&original) {
+            return Err(BackupError::CorruptBackupError(backup_path));
+        }
+
+        self.write_state(&BackupState { original, installed: None })?;
+        Ok(true)
+    }
+
+    /// Checks that the backup copy still matches the hash recorded when it was taken.
+    pub fn verify(&self) -> Result<(), BackupError> {
+        let state = self.read_state()?
+            .ok_or_else(|| BackupError::MissingBackupError(self.backup_path()))?;
+        self.verify_against(&state)
+    }
+
+    fn verify_against(&self, state: &BackupState) -> Result<(), BackupError> {
+        let backup_path = self.backup_path();
+        match Fingerprint::of_path(&backup_path)? {
+            None => Err(BackupError::MissingBackupError(backup_path)),
+            Some(fingerprint) if fingerprint != state.original => Err(BackupError::CorruptBackupError(backup_path)),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Replaces the package with the verified backup copy. The backup itself is kept.
+    ///
+    /// Returns a [BackupError::StaleBackupError] instead of reverting a game update, if the
+    /// package was replaced since the backup was taken.
+    pub fn restore(&self) -> Result<(), BackupError> {
+        if self.status()? == BackupStatus::Stale {
+            return Err(BackupError::StaleBackupError(self.package_path.clone()));
+        }
+
+        let mut state = self.read_state()?
+            .ok_or_else(|| BackupError::MissingBackupError(self.backup_path()))?;
+        self.verify_against(&state)?;
+
+        write_via_temporary_file(&self.package_path, |package_path_tmp| {
+            std::fs::copy(self.backup_path(), package_path_tmp).map(|_| ())
+        })?;
+
+        state.installed = None;
+        self.write_state(&state)
+    }
+
+    /// Deletes the backup, e.g. after it went stale, so that a new one can be taken.
+    pub fn discard(&self) -> Result<(), std::io::Error> {
+        for path in [self.backup_path(), self.state_path()] {
+            match std::fs::remove_file(path) {
+                Err(error) if error.kind() != std::io::ErrorKind::NotFound => return Err(error),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records the package as it is currently on disk as the last written version.
+    fn record_installed(&self) -> Result<(), BackupError> {
+        let mut state = self.read_state()?
+            .ok_or_else(|| BackupError::MissingBackupError(self.backup_path()))?;
+        state.installed = Fingerprint::of_path(&self.package_path)?;
+        self.write_state(&state)
+    }
+
+    fn read_state(&self) -> Result<Option<BackupState>, BackupError> {
+        let file = match File::open(self.state_path()) {
+            Ok(file) => file,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+
+        let mut lines = BufReader::new(file).lines();
+        let header = lines.next()
+            .ok_or(BackupError::InvalidStateError(1))??;
+        let algorithm = header.trim_end()
+            .strip_prefix(HEADER_PREFIX)
+            .ok_or(BackupError::InvalidStateError(1))?;
+        if algorithm != ContentHash::ALGORITHM {
+            return Err(BackupError::HashAlgorithmMismatchError(algorithm.to_string()));
+        }
+
+        let mut original = None;
+        let mut installed = None;
+        for (index, line) in lines.enumerate() {
+            let line = line?;
+            let line = line.trim_end_matches('\r');
+            if line.is_empty() {
+                continue;
+            }
+
+            // Header is line 1
+            let line_number = index + 2;
+            let mut fields = line.splitn(3, '\t');
+            let (key, fingerprint) = match (fields.next(), fields.next(), fields.next()) {
+                (Some(key), Some(hash), Some(size)) => (key, Fingerprint {
+                    hash: hash.parse().map_err(|_| BackupError::InvalidStateError(line_number))?,
+                    size: size.parse().map_err(|_| BackupError::InvalidStateError(line_number))?,
+                }),
+                _ => return Err(BackupError::InvalidStateError(line_number)),
+            };
+
+            match key {
+                key if key == ORIGINAL_KEY => original = Some(fingerprint),
+                key if key == INSTALLED_KEY => installed = Some(fingerprint),
+                _ => return Err(BackupError::InvalidStateError(line_number)),
+            }
+        }
+
+        // The original fingerprint follows the header
+        let original = original.ok_or(BackupError::InvalidStateError(2))?;
+        Ok(Some(BackupState { original, installed }))
+    }
+
+    fn write_state(&self, state: &BackupState) -> Result<(), BackupError> {
+        let file = File::options()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(self.state_path())?;
+
+        let mut output = BufWriter::new(file);
+        writeln!(output, "{}{}", HEADER_PREFIX, ContentHash::ALGORITHM)?;
+        writeln!(output, "{}\t{}\t{}", ORIGINAL_KEY, state.original.hash, state.original.size)?;
+        if let Some(installed) = &state.installed {
+            writeln!(output, "{}\t{}\t{}", INSTALLED_KEY, installed.hash, installed.size)?;
+        }
+        output.flush()?;
+        Ok(())
+    }
+}
+
+impl Package {
+    /// Same as [Package::into_path_dat], but takes a [`PackageBackup`] of the file at the
+    /// destination path before overwriting it for the first time.
+    pub fn into_path_dat_with_backup<P: AsRef<Path>>(self, destination_path: P) -> Result<PackageBackup, BackupError> {
+        self.into_path_with_backup(destination_path, DatWriter())
+    }
+
+    /// Same as [Package::into_path_pkg], but takes a [`PackageBackup`] of the file at the
+    /// destination path before overwriting it for the first time.
+    pub fn into_path_pkg_with_backup<P: AsRef<Path>>(self, destination_path: P) -> Result<PackageBackup, BackupError> {
+        self.into_path_with_backup(destination_path, PkgWriter::new())
+    }
+
+    /// Same as [Package::into_path], but takes a [`PackageBackup`] of the file at the destination
+    /// path before overwriting it for the first time.
+    ///
+    /// Returns a [BackupError::StaleBackupError] without writing anything if the file at the
+    /// destination path was replaced since the backup was taken.
+    pub fn into_path_with_backup<P: AsRef<Path>, T: PackageWriter>(self, destination_path: P, writer: T) -> Result<PackageBackup, BackupError> {
+        let backup = PackageBackup::new(destination_path);
+        backup.create()?;
+
+        self.into_path(backup.package_path(), writer)?;
+        backup.record_installed()?;
+        Ok(backup)
+    }
+}
@@ -1,2 +1,2 @@
-
-pub(super) static INDEX_SIZE: usize = 4;
+/// Size of the fixed part of an entry: `data_size` and `str_len`
+pub(super) static ENTRY_HEADER_SIZE: u64 = 8;
@@ -0,0 +1,65 @@
+use std::io::{Cursor, ErrorKind, Seek, SeekFrom};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use crate::dat::reader::read_entry;
+use crate::shared::error::PackageReadError;
+use crate::shared::reader::IndexEntry;
+
+/// Read-only view of a DAT package's index, which parses entry headers only when they are needed.
+///
+/// DAT packages do not store hashes of inner paths, so looking up an entry scans the entry headers,
+/// comparing inner paths in place without allocating them.
+#[derive(Debug)]
+pub(crate) struct DatIndex {
+    entry_count: usize,
+}
+
+impl DatIndex {
+    pub(crate) fn read(bytes: &[u8]) -> Result<DatIndex, PackageReadError> {
+        let entry_count = Cursor::new(bytes).read_u32::<LittleEndian>()? as usize;
+
+        let offset_table_end = 4 + 4 * entry_count as u64;
+        if offset_table_end > bytes.len() as u64 {
+            return Err(std::io::Error::from(ErrorKind::UnexpectedEof).into());
+        }
+
+        Ok(DatIndex { entry_count })
+    }
+
+    pub(crate) fn entry_count(&self) -> usize {
+        self.entry_count
+    }
+
+    pub(crate) fn entry_at(&self, bytes: &[u8], position: usize) -> Result<IndexEntry, PackageReadError> {
+        let entry_offset = entry_offset(bytes, position)?;
+        read_entry(&mut Cursor::new(bytes), entry_offset)
+    }
+
+    pub(crate) fn position_of(&self, bytes: &[u8], inner_path: &str) -> Result<Option<usize>, PackageReadError> {
+        let mut input = Cursor::new(bytes);
+        for position in 0..self.entry_count {
+            // Skip the entry's `data_size`
+            input.seek(SeekFrom::Start(entry_offset(bytes, position)? + 4))?;
+            let inner_path_length = input.read_u32::<LittleEndian>()? as usize;
+            if inner_path_length != inner_path.len() {
+                continue;
+            }
+
+            let inner_path_start = input.position() as usize;
+            let candidate = bytes.get(inner_path_start..inner_path_start + inner_path_length)
+                .ok_or_else(|| std::io::Error::from(ErrorKind::UnexpectedEof))?;
+            if candidate == inner_path.as_bytes() {
+                return Ok(Some(position));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+fn entry_offset(bytes: &[u8], position: usize) -> Result<u64, PackageReadError> {
+    let mut input = Cursor::new(bytes);
+    input.seek(SeekFrom::Start(4 + 4 * position as u64))?;
+    Ok(input.read_u32::<LittleEndian>()? as u64)
+}
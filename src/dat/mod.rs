@@ -1,7 +1,11 @@
 pub mod reader;
 pub mod writer;
+pub(crate) mod index;
+mod salvage;
 mod error;
 mod constants;
 
 pub use crate::dat::reader::*;
 pub use crate::dat::writer::*;
+pub use crate::dat::salvage::SalvageReport;
+pub(crate) use crate::dat::salvage::salvage;
@@ -1,15 +1,12 @@
-use std::fs::File;
-use std::io::{Cursor, Read};
-use std::rc::Rc;
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
 
 use byteorder::{LittleEndian, ReadBytesExt};
-use memmap2::Mmap;
-use crate::dat::constants::INDEX_SIZE;
-use crate::PackageReader;
-use crate::shared::entry::PackageEntry;
 
+use crate::dat::constants::ENTRY_HEADER_SIZE;
+use crate::PackageReader;
+use crate::shared::binary::read_vec;
 use crate::shared::error::PackageReadError;
-use crate::shared::package::{Package};
+use crate::shared::reader::{IndexEntry, SeekableInput};
 
 // Dat packages have the following structure:
 // - `entry_count` := number of entries (1x u32)
@@ -25,80 +22,45 @@ use crate::shared::package::{Package};
 pub struct DatReader();
 
 impl PackageReader for DatReader {
-    fn read_package_from_file(&self, file: File) -> Result<Package, PackageReadError> {
-        let mut result = Package::new();
-
-        let mmap = unsafe {
-            Mmap::map(&file)
-        }?;
-
-        let mut cursor = Cursor::new(&mmap[..INDEX_SIZE]);
-        let entry_count = cursor.read_u32::<LittleEndian>()? as usize;
+    fn read_index(&self, input: &mut dyn SeekableInput) -> Result<Vec<IndexEntry>, PackageReadError> {
+        let input_length = input.seek(SeekFrom::End(0))?;
+        input.seek(SeekFrom::Start(0))?;
+        let entry_count = input.read_u32::<LittleEndian>()? as usize;
+
+        let offset_table_end = 4 + 4 * entry_count as u64;
+        if offset_table_end > input_length {
+            return Err(std::io::Error::from(ErrorKind::UnexpectedEof).into());
+        }
 
         // TODO: Skip offsets and simply read entries until EOF?
-        let entry_area_offset = INDEX_SIZE + entry_count * 4;
-        let mut cursor = Cursor::new(&mmap[INDEX_SIZE..entry_area_offset]);
         let mut entry_offsets = Vec::with_capacity(entry_count);
         for _ in 0..entry_count {
-            let entry_offset = cursor.read_u32::<LittleEndian>()?;
+            let entry_offset = input.read_u32::<LittleEndian>()?;
             entry_offsets.push(entry_offset);
         }
 
-        let entry_builders: Vec<EntryBuilder> = entry_offsets.iter()
-            .map(|entry_offset| {
-                EntryBuilder::read_entry(&mmap, *entry_offset as usize)
-                    .expect("Failed to read entry")
-            })
-            .collect();
-
-        let mmap_rc = Rc::new(mmap);
-        for entry_builder in entry_builders {
-            let entry = entry_builder.build(mmap_rc.clone());
-            result.add_entry(entry)?;
+        let mut result = Vec::with_capacity(entry_count);
+        for entry_offset in entry_offsets {
+            result.push(read_entry(input, entry_offset as u64)?);
         }
 
         Ok(result)
     }
 }
 
-struct EntryBuilder {
-    inner_path: String,
-    data_offset: usize,
-    data_size: usize
-}
-
-impl EntryBuilder {
-    fn read_entry(mmap: &Mmap, entry_offset: usize) -> Result<EntryBuilder, PackageReadError> {
-        let entry_variable_area_offset = entry_offset + 8;
-        let mut cursor = Cursor::new(&mmap[entry_offset..entry_variable_area_offset]);
-
-        let entry_content_length = cursor.read_u32::<LittleEndian>()? as usize;
-        let inner_path_length = cursor.read_u32::<LittleEndian>()? as usize;
+pub(super) fn read_entry(input: &mut (impl Read + Seek + ?Sized), entry_offset: u64) -> Result<IndexEntry, PackageReadError> {
+    input.seek(SeekFrom::Start(entry_offset))?;
 
-        let entry_end = entry_variable_area_offset + inner_path_length + entry_content_length;
+    let entry_content_length = input.read_u32::<LittleEndian>()? as u64;
+    let inner_path_length = input.read_u32::<LittleEndian>()? as usize;
 
-        let mut cursor = Cursor::new(&mmap[entry_variable_area_offset..entry_end]);
-        let inner_path = {
-            let mut buffer = vec![0u8; inner_path_length];
-            cursor.read_exact(&mut buffer)?;
-            String::from_utf8(buffer)?
-        };
+    let inner_path = String::from_utf8(read_vec(input, inner_path_length)?)?;
 
-        let entry_content_offset = entry_variable_area_offset + inner_path_length;
+    let entry_content_offset = entry_offset + ENTRY_HEADER_SIZE + inner_path_length as u64;
 
-        Ok(EntryBuilder {
-            inner_path,
-            data_offset: entry_content_offset,
-            data_size: entry_content_length
-        })
-    }
-
-    fn build(self, input: Rc<Mmap>) -> PackageEntry {
-        PackageEntry::from_memory_mapped_file(
-            self.inner_path,
-            input,
-            self.data_offset as u64,
-            self.data_size as u64
-        )
-    }
+    Ok(IndexEntry::new(
+        inner_path,
+        entry_content_offset,
+        entry_content_length,
+    ))
 }
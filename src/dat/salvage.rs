@@ -0,0 +1,178 @@
+use std::ops::Range;
+use std::rc::Rc;
+
+use crate::dat::constants::ENTRY_HEADER_SIZE;
+use crate::{Package, PackageEntry};
+
+/// Longest inner path that the scan accepts as plausible.
+const MAX_INNER_PATH_LENGTH: usize = 1024;
+
+/// Describes how a damaged DAT package was salvaged, see [`Package::salvage_bytes_dat`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SalvageReport {
+    recovered_from_index: usize,
+    recovered_by_scan: usize,
+    unaccounted_ranges: Vec<Range<u64>>,
+}
+
+impl SalvageReport {
+    /// Returns the number of entries recovered by following the package's offset table.
+    pub fn recovered_from_index(&self) -> usize {
+        self.recovered_from_index
+    }
+
+    /// Returns the number of entries recovered by scanning the package for plausible entry headers.
+    pub fn recovered_by_scan(&self) -> usize {
+        self.recovered_by_scan
+    }
+
+    /// Returns the byte ranges of the package that do not belong to its offset table or any
+    /// recovered entry, in ascending order.
+    pub fn unaccounted_ranges(&self) -> &[Range<u64>] {
+        &self.unaccounted_ranges
+    }
+
+    /// Returns the total number of bytes in [unaccounted ranges](SalvageReport::unaccounted_ranges).
+    pub fn unaccounted_size(&self) -> u64 {
+        self.unaccounted_ranges.iter()
+            .map(|range| range.end - range.start)
+            .sum()
+    }
+}
+
+/// A plausible entry found in the package.
+struct Candidate {
+    /// Range of the whole entry, including its header.
+    range: Range<usize>,
+    inner_path: String,
+    data_offset: usize,
+    from_index: bool,
+}
+
+/// Recovers as many entries as possible from a DAT package whose offset table may be damaged, or
+/// which may be truncated.
+///
+/// Entries are first looked up through the offset table, as far as it points to plausible entries.
+/// The remaining bytes are then scanned for plausible entry headers: a content size and an inner
+/// path length that fit in the package, followed by an inner path of printable ASCII characters.
+pub(crate) fn salvage(bytes: Vec<u8>) -> (Package, SalvageReport) {
+    let mut report = SalvageReport::default();
+    let mut accounted: Vec<Range<usize>> = Vec::new();
+    let mut candidates: Vec<Candidate> = Vec::new();
+
+    // Offset table
+    if let Some(entry_count) = read_u32(&bytes, 0) {
+        let offset_table_end = 4 + 4 * entry_count as usize;
+        if offset_table_end <= bytes.len() {
+            accounted.push(0..offset_table_end);
+            for position in 0..entry_count as usize {
+                let entry_offset = read_u32(&bytes, 4 + 4 * position).unwrap() as usize;
+                let candidate = read_candidate(&bytes, entry_offset, true)
+                    .filter(|candidate| !overlaps(&accounted, &candidate.range));
+
+                if let Some(candidate) = candidate {
+                    accounted.push(candidate.range.clone());
+                    candidates.push(candidate);
+                }
+            }
+        }
+    }
+
+    // Scan the gaps left by the offset table
+    accounted.sort_by_key(|range| range.start);
+    for gap in find_gaps(&accounted, bytes.len()) {
+        let mut offset = gap.start;
+        while offset < gap.end {
+            match read_candidate(&bytes[..gap.end], offset, false) {
+                Some(candidate) => {
+                    offset = candidate.range.end;
+                    accounted.push(candidate.range.clone());
+                    candidates.push(candidate);
+                }
+                None => offset += 1,
+            }
+        }
+    }
+
+    // Entries that appear earlier in the package win over later duplicates
+    candidates.sort_by_key(|candidate| candidate.range.start);
+    let bytes: Rc<[u8]> = bytes.into();
+    let mut package = Package::with_capacity(candidates.len());
+    for candidate in candidates {
+        let entry = PackageEntry::from_shared_byte_array(
+            &candidate.inner_path,
+            bytes.clone(),
+            candidate.data_offset as u64,
+            (candidate.range.end - candidate.data_offset) as u64,
+        );
+
+        if package.add_entry(entry).is_err() {
+            accounted.retain(|range| *range != candidate.range);
+        } else if candidate.from_index {
+            report.recovered_from_index += 1;
+        } else {
+            report.recovered_by_scan += 1;
+        }
+    }
+
+    accounted.sort_by_key(|range| range.start);
+    report.unaccounted_ranges = find_gaps(&accounted, bytes.len()).into_iter()
+        .map(|gap| gap.start as u64..gap.end as u64)
+        .collect();
+
+    (package, report)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let slice = bytes.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes(slice.try_into().unwrap()))
+}
+
+fn read_candidate(bytes: &[u8], entry_offset: usize, from_index: bool) -> Option<Candidate> {
+    let data_size = read_u32(bytes, entry_offset)? as usize;
+    let inner_path_length = read_u32(bytes, entry_offset + 4)? as usize;
+    if inner_path_length == 0 || inner_path_length > MAX_INNER_PATH_LENGTH {
+        return None;
+    }
+
+    let inner_path_offset = entry_offset + ENTRY_HEADER_SIZE as usize;
+    let inner_path = bytes.get(inner_path_offset..inner_path_offset + inner_path_length)?;
+    if !inner_path.iter().all(|byte| (0x20..0x7F).contains(byte)) {
+        return None;
+    }
+
+    let data_offset = inner_path_offset + inner_path_length;
+    let entry_end = data_offset.checked_add(data_size)?;
+    if entry_end > bytes.len() {
+        return None;
+    }
+
+    Some(Candidate {
+        range: entry_offset..entry_end,
+        inner_path: String::from_utf8(inner_path.to_vec()).ok()?,
+        data_offset,
+        from_index,
+    })
+}
+
+fn overlaps(ranges: &[Range<usize>], other: &Range<usize>) -> bool {
+    ranges.iter().any(|range| range.start < other.end && other.start < range.end)
+}
+
+/// Returns the ranges within `0..length` not covered by any of the sorted `ranges`.
+fn find_gaps(ranges: &[Range<usize>], length: usize) -> Vec<Range<usize>> {
+    let mut result = Vec::new();
+    let mut position = 0;
+    for range in ranges {
+        if range.start > position {
+            result.push(position..range.start);
+        }
+        position = position.max(range.end);
+    }
+
+    if position < length {
+        result.push(position..length);
+    }
+
+    result
+}
@@ -0,0 +1,432 @@
+//! C ABI for using this library from other languages, declared in the generated `include/ftldat.h`.
+//!
+//! All functions are prefixed with `ftldat_`. Packages are passed around as opaque
+//! [`FtldatPackage`] handles, which are created by `ftldat_package_new` or `ftldat_package_open_*`,
+//! and must be released with `ftldat_package_free`.
+//!
+//! Fallible functions return an [`FtldatResult`]. The message of the last error that occurred on
+//! the calling thread can be retrieved with `ftldat_last_error_message`. Panics do not unwind
+//! into the caller; they are reported as [`FtldatResult::Panic`], or as the neutral value of
+//! functions that do not return a result (NULL, 0 or `false`).
+//!
+//! Strings are NUL-terminated and UTF-8 encoded. Functions that copy data into caller-provided
+//! buffers always report the full length of the data, so that callers can retry with a buffer
+//! that is large enough if [`FtldatResult::BufferTooSmall`] is returned.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr};
+use std::fmt::Display;
+use std::io::Cursor;
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
+
+use crate::{Package, PackageEntry};
+use crate::shared::writer::write_via_temporary_file;
+
+/// Result codes of fallible functions.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FtldatResult {
+    Ok = 0,
+    /// A pointer was NULL, or a string was not valid UTF-8.
+    InvalidArgument = 1,
+    /// Reading a package or entry failed.
+    ReadError = 2,
+    /// Writing a package failed.
+    WriteError = 3,
+    /// There is no entry under the specified inner path.
+    NotFound = 4,
+    /// An entry already exists under the specified inner path.
+    AlreadyExists = 5,
+    /// The caller-provided buffer is too small; the required length has been reported.
+    BufferTooSmall = 6,
+    /// An internal error occurred; the package may be left in an inconsistent state.
+    Panic = 7,
+}
+
+/// Opaque handle of a [`Package`].
+pub struct FtldatPackage {
+    package: Package,
+    /// Canonical path of the file that the package's entries are read from, if any.
+    source_path: Option<PathBuf>,
+}
+
+impl FtldatPackage {
+    fn new(package: Package, source_path: Option<&Path>) -> *mut FtldatPackage {
+        let source_path = source_path.and_then(|path| std::fs::canonicalize(path).ok());
+        Box::into_raw(Box::new(FtldatPackage { package, source_path }))
+    }
+
+    fn is_read_from(&self, path: &Path) -> bool {
+        match (&self.source_path, std::fs::canonicalize(path)) {
+            (Some(source_path), Ok(path)) => *source_path == path,
+            _ => false,
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+fn fail<E: Display>(result: FtldatResult, error: E) -> FtldatResult {
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = error.to_string());
+    result
+}
+
+/// Converts a NUL-terminated string argument, or returns [`FtldatResult::InvalidArgument`].
+unsafe fn str_arg<'a>(value: *const c_char, name: &str) -> Result<&'a str, FtldatResult> {
+    if value.is_null() {
+        return Err(fail(FtldatResult::InvalidArgument, format!("argument '{}' is NULL", name)));
+    }
+
+    CStr::from_ptr(value).to_str()
+        .map_err(|_| fail(FtldatResult::InvalidArgument, format!("argument '{}' is not valid UTF-8", name)))
+}
+
+/// Returns the package behind a handle, or [`FtldatResult::InvalidArgument`].
+unsafe fn package_arg<'a>(package: *const FtldatPackage) -> Result<&'a Package, FtldatResult> {
+    package.as_ref()
+        .map(|package| &package.package)
+        .ok_or_else(|| fail(FtldatResult::InvalidArgument, "argument 'package' is NULL"))
+}
+
+/// Returns the package behind a handle, or [`FtldatResult::InvalidArgument`].
+unsafe fn package_arg_mut<'a>(package: *mut FtldatPackage) -> Result<&'a mut Package, FtldatResult> {
+    handle_arg_mut(package).map(|package| &mut package.package)
+}
+
+/// Returns the handle itself, or [`FtldatResult::InvalidArgument`].
+unsafe fn handle_arg_mut<'a>(package: *mut FtldatPackage) -> Result<&'a mut FtldatPackage, FtldatResult> {
+    package.as_mut()
+        .ok_or_else(|| fail(FtldatResult::InvalidArgument, "argument 'package' is NULL"))
+}
+
+/// Copies `data` into the caller-provided buffer, and reports its length via `out_length`.
+///
+/// If `nul_terminated`, a NUL byte is appended, which is not included in the reported length.
+unsafe fn copy_out(data: &[u8], nul_terminated: bool, buffer: *mut u8, buffer_size: usize, out_length: *mut usize) -> FtldatResult {
+    if let Some(out_length) = out_length.as_mut() {
+        *out_length = data.len();
+    }
+
+    // Nothing is copied for empty data, so the buffer may be NULL
+    let required_size = data.len() + nul_terminated as usize;
+    if required_size == 0 {
+        return FtldatResult::Ok;
+    }
+    if buffer.is_null() || buffer_size < required_size {
+        return fail(FtldatResult::BufferTooSmall, format!("buffer of {} bytes is too small, {} bytes are required", buffer_size, required_size));
+    }
+
+    std::ptr::copy_nonoverlapping(data.as_ptr(), buffer, data.len());
+    if nul_terminated {
+        *buffer.add(data.len()) = 0;
+    }
+    FtldatResult::Ok
+}
+
+/// Runs the body of an exported function, and returns `default` if it panics, since unwinding
+/// into foreign code is undefined behavior.
+fn catch_panic<T, F: FnOnce() -> T>(default: T, body: F) -> T {
+    std::panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        let message = payload.downcast_ref::<&str>().copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown cause");
+        fail(FtldatResult::Panic, format!("panicked: {}", message));
+        default
+    })
+}
+
+fn into_result<F: FnOnce() -> Result<(), FtldatResult>>(body: F) -> FtldatResult {
+    catch_panic(FtldatResult::Panic, || body().err().unwrap_or(FtldatResult::Ok))
+}
+
+/// Copies the message of the last error that occurred on the calling thread into `buffer`,
+/// NUL-terminated and truncated to fit, and returns the full length of the message.
+///
+/// # Safety
+/// `buffer` must be NULL, or valid for writes of `buffer_size` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ftldat_last_error_message(buffer: *mut c_char, buffer_size: usize) -> usize {
+    catch_panic(0, || LAST_ERROR.with(|last_error| {
+        let message = last_error.borrow();
+        if !buffer.is_null() && buffer_size > 0 {
+            let length = message.len().min(buffer_size - 1);
+            std::ptr::copy_nonoverlapping(message.as_ptr(), buffer as *mut u8, length);
+            *buffer.add(length) = 0;
+        }
+        message.len()
+    }))
+}
+
+// region <Lifecycle>
+/// Creates a new, empty package.
+#[no_mangle]
+pub extern "C" fn ftldat_package_new() -> *mut FtldatPackage {
+    catch_panic(std::ptr::null_mut(), || FtldatPackage::new(Package::new(), None))
+}
+
+/// Releases a package handle. Does nothing if `package` is NULL.
+///
+/// # Safety
+/// `package` must be NULL, or a handle that has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn ftldat_package_free(package: *mut FtldatPackage) {
+    catch_panic((), || {
+        if !package.is_null() {
+            drop(Box::from_raw(package));
+        }
+    })
+}
+
+unsafe fn open(source_path: *const c_char, out_package: *mut *mut FtldatPackage, read: fn(&str) -> Result<Package, crate::error::PackageReadError>) -> FtldatResult {
+    into_result(|| {
+        let source_path = str_arg(source_path, "source_path")?;
+        let out_package = out_package.as_mut()
+            .ok_or_else(|| fail(FtldatResult::InvalidArgument, "argument 'out_package' is NULL"))?;
+
+        let package = read(source_path)
+            .map_err(|error| fail(FtldatResult::ReadError, error))?;
+        *out_package = FtldatPackage::new(package, Some(Path::new(source_path)));
+        Ok(())
+    })
+}
+
+/// Reads the DAT package at `source_path`, and stores its handle in `out_package`.
+///
+/// # Safety
+/// `source_path` must be a NUL-terminated string, and `out_package` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ftldat_package_open_dat(source_path: *const c_char, out_package: *mut *mut FtldatPackage) -> FtldatResult {
+    open(source_path, out_package, |path| Package::from_path_dat(path))
+}
+
+/// Reads the PKG package at `source_path`, and stores its handle in `out_package`.
+///
+/// # Safety
+/// `source_path` must be a NUL-terminated string, and `out_package` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ftldat_package_open_pkg(source_path: *const c_char, out_package: *mut *mut FtldatPackage) -> FtldatResult {
+    open(source_path, out_package, |path| Package::from_path_pkg(path))
+}
+
+/// Writes the package to a temporary file next to `destination_path`, and then moves it into
+/// place, so that the destination is never left partially written.
+fn write_package(package: &Package, destination_path: &Path, dat: bool) -> Result<(), FtldatResult> {
+    write_via_temporary_file(destination_path, |destination_path_tmp| {
+        if dat {
+            package.to_path_dat(destination_path_tmp)
+        } else {
+            package.to_path_pkg(destination_path_tmp)
+        }
+    }).map_err(|error| fail(FtldatResult::WriteError, error))
+}
+
+unsafe fn save(package: *mut FtldatPackage, destination_path: *const c_char, dat: bool) -> FtldatResult {
+    into_result(|| {
+        let destination_path = Path::new(str_arg(destination_path, "destination_path")?);
+        let handle = handle_arg_mut(package)?;
+        if !handle.is_read_from(destination_path) {
+            return write_package(&handle.package, destination_path, dat);
+        }
+
+        // The entries are read from the file that is about to be overwritten, so move them into
+        // memory first; this releases the file, and keeps them intact if writing fails
+        let mut buffer = Cursor::new(Vec::new());
+        if dat {
+            handle.package.to_output_dat(&mut buffer)
+        } else {
+            handle.package.to_output_pkg(&mut buffer)
+        }.map_err(|error| fail(FtldatResult::WriteError, error))?;
+        handle.package = if dat {
+            Package::from_bytes_dat(buffer.into_inner())
+        } else {
+            Package::from_bytes_pkg(buffer.into_inner())
+        }.map_err(|error| fail(FtldatResult::ReadError, error))?;
+        handle.source_path = None;
+
+        write_package(&handle.package, destination_path, dat)?;
+
+        // Read entries from the written file again, rather than keeping them in memory
+        let written = if dat {
+            Package::from_path_dat(destination_path)
+        } else {
+            Package::from_path_pkg(destination_path)
+        };
+        if let Ok(written) = written {
+            handle.package = written;
+            handle.source_path = std::fs::canonicalize(destination_path).ok();
+        }
+        Ok(())
+    })
+}
+
+/// Writes the package in DAT format to `destination_path`, which may be the file it was read
+/// from.
+///
+/// If writing fails, the package is left unchanged.
+///
+/// # Safety
+/// `package` must be a valid handle, and `destination_path` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ftldat_package_save_dat(package: *mut FtldatPackage, destination_path: *const c_char) -> FtldatResult {
+    save(package, destination_path, true)
+}
+
+/// Writes the package in PKG format to `destination_path`, which may be the file it was read
+/// from.
+///
+/// If writing fails, the package is left unchanged.
+///
+/// # Safety
+/// `package` must be a valid handle, and `destination_path` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ftldat_package_save_pkg(package: *mut FtldatPackage, destination_path: *const c_char) -> FtldatResult {
+    save(package, destination_path, false)
+}
+// endregion
+
+// region <Entries>
+/// Returns the number of entries in the package, or 0 if `package` is NULL.
+///
+/// # Safety
+/// `package` must be NULL, or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn ftldat_package_entry_count(package: *const FtldatPackage) -> usize {
+    catch_panic(0, || {
+        package_arg(package)
+            .map(|package| package.entry_count())
+            .unwrap_or(0)
+    })
+}
+
+/// Copies the inner path of the entry at `index`, in package order, into `buffer` as a
+/// NUL-terminated string, and stores its length (without the NUL) in `out_length`.
+///
+/// # Safety
+/// `package` must be a valid handle, `buffer` must be NULL or valid for writes of `buffer_size`
+/// bytes, and `out_length` must be NULL or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ftldat_package_entry_path(package: *const FtldatPackage, index: usize, buffer: *mut c_char, buffer_size: usize, out_length: *mut usize) -> FtldatResult {
+    into_result(|| {
+        let package = package_arg(package)?;
+        let entry = package.entry_at(index)
+            .ok_or_else(|| fail(FtldatResult::NotFound, format!("entry index {} is out of range", index)))?;
+        match copy_out(entry.inner_path().as_bytes(), true, buffer as *mut u8, buffer_size, out_length) {
+            FtldatResult::Ok => Ok(()),
+            result => Err(result),
+        }
+    })
+}
+
+/// Returns `true` if the package has an entry under `inner_path`.
+///
+/// # Safety
+/// `package` must be NULL or a valid handle, and `inner_path` NULL or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ftldat_package_entry_exists(package: *const FtldatPackage, inner_path: *const c_char) -> bool {
+    catch_panic(false, || match (package_arg(package), str_arg(inner_path, "inner_path")) {
+        (Ok(package), Ok(inner_path)) => package.entry_exists(inner_path),
+        _ => false,
+    })
+}
+
+unsafe fn entry_arg<'a>(package: *const FtldatPackage, inner_path: *const c_char) -> Result<&'a PackageEntry, FtldatResult> {
+    let package = package_arg(package)?;
+    let inner_path = str_arg(inner_path, "inner_path")?;
+    package.entry_by_path(inner_path)
+        .ok_or_else(|| fail(FtldatResult::NotFound, crate::error::InnerPathNotFoundError(inner_path.to_string())))
+}
+
+/// Stores the content size of the entry under `inner_path` in `out_size`.
+///
+/// # Safety
+/// `package` must be a valid handle, `inner_path` a NUL-terminated string, and `out_size` valid
+/// for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ftldat_package_content_size(package: *const FtldatPackage, inner_path: *const c_char, out_size: *mut u64) -> FtldatResult {
+    into_result(|| {
+        let entry = entry_arg(package, inner_path)?;
+        let out_size = out_size.as_mut()
+            .ok_or_else(|| fail(FtldatResult::InvalidArgument, "argument 'out_size' is NULL"))?;
+        *out_size = entry.content_size()
+            .map_err(|error| fail(FtldatResult::ReadError, error))?;
+        Ok(())
+    })
+}
+
+/// Copies the content of the entry under `inner_path` into `buffer`, and stores its length in
+/// `out_length`.
+///
+/// # Safety
+/// `package` must be a valid handle, `inner_path` a NUL-terminated string, `buffer` NULL or valid
+/// for writes of `buffer_size` bytes, and `out_length` NULL or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ftldat_package_read_content(package: *const FtldatPackage, inner_path: *const c_char, buffer: *mut u8, buffer_size: usize, out_length: *mut usize) -> FtldatResult {
+    into_result(|| {
+        let content = entry_arg(package, inner_path)?
+            .content()
+            .map_err(|error| fail(FtldatResult::ReadError, error))?;
+        match copy_out(&content, false, buffer, buffer_size, out_length) {
+            FtldatResult::Ok => Ok(()),
+            result => Err(result),
+        }
+    })
+}
+
+unsafe fn entry_from_args(inner_path: *const c_char, content: *const u8, content_length: usize) -> Result<PackageEntry, FtldatResult> {
+    let inner_path = str_arg(inner_path, "inner_path")?;
+    let content = match content_length {
+        0 => Vec::new(),
+        _ if content.is_null() => return Err(fail(FtldatResult::InvalidArgument, "argument 'content' is NULL")),
+        _ => std::slice::from_raw_parts(content, content_length).to_vec(),
+    };
+    Ok(PackageEntry::from_byte_array(inner_path, content))
+}
+
+/// Puts a copy of `content` into the package under `inner_path`, replacing any existing entry.
+///
+/// # Safety
+/// `package` must be a valid handle, `inner_path` a NUL-terminated string, and `content` valid
+/// for reads of `content_length` bytes (or NULL, if `content_length` is 0).
+#[no_mangle]
+pub unsafe extern "C" fn ftldat_package_put_entry(package: *mut FtldatPackage, inner_path: *const c_char, content: *const u8, content_length: usize) -> FtldatResult {
+    into_result(|| {
+        let package = package_arg_mut(package)?;
+        package.put_entry(entry_from_args(inner_path, content, content_length)?);
+        Ok(())
+    })
+}
+
+/// Adds a copy of `content` to the package under `inner_path`, or returns
+/// [`FtldatResult::AlreadyExists`] if an entry already exists there.
+///
+/// # Safety
+/// `package` must be a valid handle, `inner_path` a NUL-terminated string, and `content` valid
+/// for reads of `content_length` bytes (or NULL, if `content_length` is 0).
+#[no_mangle]
+pub unsafe extern "C" fn ftldat_package_add_entry(package: *mut FtldatPackage, inner_path: *const c_char, content: *const u8, content_length: usize) -> FtldatResult {
+    into_result(|| {
+        let package = package_arg_mut(package)?;
+        package.add_entry(entry_from_args(inner_path, content, content_length)?)
+            .map_err(|error| fail(FtldatResult::AlreadyExists, error))
+    })
+}
+
+/// Removes the entry under `inner_path`, or returns [`FtldatResult::NotFound`] if there is none.
+///
+/// # Safety
+/// `package` must be a valid handle, and `inner_path` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ftldat_package_remove_entry(package: *mut FtldatPackage, inner_path: *const c_char) -> FtldatResult {
+    into_result(|| {
+        let package = package_arg_mut(package)?;
+        let inner_path = str_arg(inner_path, "inner_path")?;
+        if !package.remove_entry(inner_path) {
+            return Err(fail(FtldatResult::NotFound, crate::error::InnerPathNotFoundError(inner_path.to_string())));
+        }
+        Ok(())
+    })
+}
+// endregion
@@ -0,0 +1,44 @@
+use std::string::FromUtf8Error;
+
+use thiserror::Error;
+
+use crate::JournalConflict;
+
+#[derive(Error, Debug)]
+pub enum JournalReadError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error("journal file signature mismatch")]
+    SignatureMismatchError(),
+    #[error("unsupported journal format version '{0}'")]
+    UnsupportedVersionError(u32),
+    #[error("journal uses hash algorithm '{0}', but this build uses '{}'", crate::ContentHash::ALGORITHM)]
+    HashAlgorithmMismatchError(String),
+    #[error("unknown journal change tag '{0}'")]
+    UnknownChangeError(u8),
+    #[error("journal records a malformed content hash for inner path '{0}'")]
+    InvalidHashError(String),
+    #[error("replaced content recorded for inner path '{0}' does not match its hash")]
+    CorruptContentError(String),
+    #[error(transparent)]
+    InvalidStringError(#[from] FromUtf8Error),
+}
+
+#[derive(Error, Debug)]
+#[error("mod '{0}' is already recorded in the journal")]
+pub struct ModAlreadyInstalledError(pub(crate) String);
+
+#[derive(Error, Debug)]
+pub enum UninstallError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error("mod '{0}' is not recorded in the journal")]
+    ModNotInstalledError(String),
+    #[error("mod '{mod_name}' cannot be uninstalled, because later mods changed the same inner paths: {conflicts:?}")]
+    LaterModConflictError {
+        mod_name: String,
+        conflicts: Vec<JournalConflict>,
+    },
+    #[error("entry under inner path '{0}' was changed after the mod was installed")]
+    EntryModifiedError(String),
+}
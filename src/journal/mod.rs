@@ -0,0 +1,509 @@
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::{ContentHash, Package, PackageEntry};
+use crate::journal::error::{JournalReadError, ModAlreadyInstalledError, UninstallError};
+use crate::shared::binary::{read_bytes, read_counted, write_bytes};
+#[cfg(feature = "slipstream")]
+use crate::{FtlMod, ModApplyReport};
+#[cfg(feature = "slipstream")]
+use crate::error::ModApplyError;
+
+pub(crate) mod error;
+
+// Journal files have the following structure:
+// - `FTLJOURN` signature (8x u8)
+// - `version` := journal format version (1x u32)
+// - `str_len` := hash algorithm name length (1x u32)
+// - hash algorithm name (`str_len` x u8), see `ContentHash::ALGORITHM`
+// - `mod_count` := number of installed mods (1x u32)
+// - Mods (`mod_count` x Mod), in installation order
+//
+// Mods have the following structure:
+// - `str_len` := mod name length (1x u32)
+// - mod name (`str_len` x u8)
+// - `change_count` := number of changes (1x u32)
+// - Changes (`change_count` x Change)
+//
+// Changes have the following structure:
+// - `tag` := kind of the change: 0 = added, 1 = overwritten, 2 = removed (1x u8)
+// - `str_len` := inner path length (1x u32)
+// - inner path (`str_len` x u8)
+// - for overwritten and removed changes:
+//   - `hash_len` := replaced content hash length (1x u32)
+//   - replaced content hash (`hash_len` x u8)
+//   - `data_size` := replaced content length (1x u32)
+//   - replaced content (`data_size` x u8)
+// - for added and overwritten changes:
+//   - `hash_len` := installed content hash length (1x u32)
+//   - installed content hash (`hash_len` x u8)
+//
+// All numbers are little-endian, as in DAT packages.
+
+/// FTLJOURN
+static JOURNAL_SIGNATURE: [u8; 8] = [70, 84, 76, 74, 79, 85, 82, 78];
+static JOURNAL_VERSION: u32 = 1;
+static JOURNAL_EXTENSION: &str = ".journal";
+
+static CHANGE_ADDED: u8 = 0;
+static CHANGE_OVERWRITTEN: u8 = 1;
+static CHANGE_REMOVED: u8 = 2;
+
+/// Kind of a [`JournalChange`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalChangeKind {
+    /// The mod added an entry under a previously unused inner path.
+    Added,
+    /// The mod replaced the content of an existing entry.
+    Overwritten,
+    /// The mod removed an existing entry.
+    Removed,
+}
+
+/// A single change made to a [`Package`] by an installed mod.
+#[derive(Debug, Clone)]
+pub struct JournalChange {
+    inner_path: String,
+    kind: JournalChangeKind,
+    replaced: Option<(ContentHash, Vec<u8>)>,
+    installed_hash: Option<ContentHash>,
+}
+
+impl JournalChange {
+    /// Returns the `inner_path` of the changed entry.
+    pub fn inner_path(&self) -> &str {
+        &self.inner_path
+    }
+
+    /// Returns the kind of this change.
+    pub fn kind(&self) -> JournalChangeKind {
+        self.kind
+    }
+
+    /// Returns the hash of the content the mod replaced or removed, or `None` for added entries.
+    pub fn replaced_hash(&self) -> Option<&ContentHash> {
+        self.replaced.as_ref().map(|(hash, _)| hash)
+    }
+
+    /// Returns the hash of the content the mod installed, or `None` for removed entries.
+    pub fn installed_hash(&self) -> Option<&ContentHash> {
+        self.installed_hash.as_ref()
+    }
+}
+
+/// A change made by a later mod to an inner path that an earlier mod also changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalConflict {
+    inner_path: String,
+    mod_name: String,
+}
+
+impl JournalConflict {
+    /// Returns the `inner_path` changed by both mods.
+    pub fn inner_path(&self) -> &str {
+        &self.inner_path
+    }
+
+    /// Returns the name of the later mod.
+    pub fn mod_name(&self) -> &str {
+        &self.mod_name
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ModRecord {
+    mod_name: String,
+    changes: Vec<JournalChange>,
+}
+
+/// Record of the mods installed into a [`Package`], in installation order, along with the
+/// content each of them replaced, so that they can be uninstalled later.
+///
+/// Journals are usually stored next to the package they describe, see
+/// [`InstallJournal::sidecar_path`].
+#[derive(Debug, Default)]
+pub struct InstallJournal {
+    records: Vec<ModRecord>,
+}
+
+impl InstallJournal {
+    /// Creates a new, empty journal.
+    pub fn new() -> InstallJournal {
+        InstallJournal::default()
+    }
+
+    /// Returns the path of the journal belonging to the package at `package_path`, which is the
+    /// package path with `.journal` appended (e.g. `resource.dat.journal`).
+    pub fn sidecar_path<P: AsRef<Path>>(package_path: P) -> PathBuf {
+        let mut path = OsString::from(package_path.as_ref());
+        path.push(JOURNAL_EXTENSION);
+        PathBuf::from(path)
+    }
+
+    /// Returns the names of the installed mods, in installation order.
+    pub fn installed_mods(&self) -> impl Iterator<Item=&str> {
+        self.records.iter().map(|record| record.mod_name.as_str())
+    }
+
+    /// Returns `true` if a mod with the specified name is recorded in this journal.
+    pub fn is_installed<S: AsRef<str>>(&self, mod_name: S) -> bool {
+        self.position_of(mod_name.as_ref()).is_some()
+    }
+
+    /// Returns the changes made by the specified mod, or `None` if it is not installed.
+    pub fn changes<S: AsRef<str>>(&self, mod_name: S) -> Option<&[JournalChange]> {
+        self.position_of(mod_name.as_ref())
+            .map(|position| self.records[position].changes.as_slice())
+    }
+
+    /// Returns the changes of mods installed after the specified one, which touch the same inner
+    /// paths. The specified mod can only be uninstalled once there are no such conflicts.
+    pub fn conflicts<S: AsRef<str>>(&self, mod_name: S) -> Vec<JournalConflict> {
+        let position = match self.position_of(mod_name.as_ref()) {
+            Some(position) => position,
+            None => return Vec::new(),
+        };
+
+        let inner_paths: HashSet<&str> = self.records[position].changes.iter()
+            .map(|change| change.inner_path.as_str())
+            .collect();
+
+        self.records[position + 1..].iter()
+            .flat_map(|record| record.changes.iter()
+                .filter(|change| inner_paths.contains(change.inner_path.as_str()))
+                .map(|change| JournalConflict {
+                    inner_path: change.inner_path.clone(),
+                    mod_name: record.mod_name.clone(),
+                }))
+            .collect()
+    }
+
+    fn position_of(&self, mod_name: &str) -> Option<usize> {
+        self.records.iter().position(|record| record.mod_name == mod_name)
+    }
+
+    // region <Input>
+    /// Reads a journal from the file at the specified path.
+    pub fn from_path<P: AsRef<Path>>(source_path: P) -> Result<InstallJournal, JournalReadError> {
+        let file = File::options()
+            .read(true)
+            .open(source_path)?;
+        InstallJournal::from_input(BufReader::new(file))
+    }
+
+    /// Reads the journal belonging to the package at `package_path`.
+    ///
+    /// Returns an empty journal if the package has no journal yet.
+    pub fn from_sidecar<P: AsRef<Path>>(package_path: P) -> Result<InstallJournal, JournalReadError> {
+        let journal_path = InstallJournal::sidecar_path(package_path);
+        if !journal_path.exists() {
+            return Ok(InstallJournal::new());
+        }
+
+        InstallJournal::from_path(journal_path)
+    }
+
+    /// Reads a journal from the specified input.
+    pub fn from_input<I: Read>(mut input: I) -> Result<InstallJournal, JournalReadError> {
+        let mut signature = [0u8; 8];
+        input.read_exact(&mut signature)?;
+        if signature != JOURNAL_SIGNATURE {
+            return Err(JournalReadError::SignatureMismatchError());
+        }
+
+        let version = input.read_u32::<LittleEndian>()?;
+        if version != JOURNAL_VERSION {
+            return Err(JournalReadError::UnsupportedVersionError(version));
+        }
+
+        let algorithm = read_string(&mut input)?;
+        if algorithm != ContentHash::ALGORITHM {
+            return Err(JournalReadError::HashAlgorithmMismatchError(algorithm));
+        }
+
+        let records = read_counted(&mut input, read_record)?;
+        Ok(InstallJournal { records })
+    }
+    // endregion
+
+    // region <Output>
+    /// Writes this journal to file at the specified path.
+    pub fn to_path<P: AsRef<Path>>(&self, destination_path: P) -> Result<(), std::io::Error> {
+        let file = File::options()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(destination_path)?;
+
+        let mut output = BufWriter::new(file);
+        self.to_output(&mut output)?;
+        output.flush()
+    }
+
+    /// Writes this journal next to the package at `package_path`.
+    pub fn to_sidecar<P: AsRef<Path>>(&self, package_path: P) -> Result<(), std::io::Error> {
+        self.to_path(InstallJournal::sidecar_path(package_path))
+    }
+
+    /// Writes this journal to the specified output.
+    pub fn to_output<O: Write>(&self, mut output: O) -> Result<(), std::io::Error> {
+        output.write_all(&JOURNAL_SIGNATURE)?;
+        output.write_u32::<LittleEndian>(JOURNAL_VERSION)?;
+        write_bytes(ContentHash::ALGORITHM.as_bytes(), &mut output)?;
+        output.write_u32::<LittleEndian>(self.records.len() as u32)?;
+
+        for record in self.records.iter() {
+            write_bytes(record.mod_name.as_bytes(), &mut output)?;
+            output.write_u32::<LittleEndian>(record.changes.len() as u32)?;
+            for change in record.changes.iter() {
+                write_change(change, &mut output)?;
+            }
+        }
+
+        Ok(())
+    }
+    // endregion
+}
+
+/// A journaled installation of a single mod, started by [`Package::begin_install`].
+///
+/// Changes made through the transaction are applied to the package immediately. Before an inner
+/// path is changed for the first time, its previous content is kept, so that [`commit`] can record
+/// what was replaced. Dropping the transaction without committing it rolls the changes back.
+///
+/// [`commit`]: InstallTransaction::commit
+pub struct InstallTransaction<'a> {
+    package: &'a mut Package,
+    journal: &'a mut InstallJournal,
+    mod_name: String,
+    snapshots: Vec<(String, Option<Vec<u8>>)>,
+    snapshot_paths: HashSet<String>,
+}
+
+impl InstallTransaction<'_> {
+    /// Returns the package being modified.
+    pub fn package(&self) -> &Package {
+        self.package
+    }
+
+    /// Puts the specified entry into the package, see [`Package::put_entry`].
+    pub fn put_entry(&mut self, entry: PackageEntry) -> Result<(), std::io::Error> {
+        self.snapshot(entry.inner_path())?;
+        self.package.put_entry(entry);
+        Ok(())
+    }
+
+    /// Removes the entry under the specified `inner_path` from the package, see
+    /// [`Package::remove_entry`].
+    pub fn remove_entry<S: AsRef<str>>(&mut self, inner_path: S) -> Result<bool, std::io::Error> {
+        self.snapshot(inner_path.as_ref())?;
+        Ok(self.package.remove_entry(inner_path))
+    }
+
+    /// Applies the specified mod to the package, see [`Package::apply_mod`].
+    #[cfg(feature = "slipstream")]
+    pub fn apply_mod(&mut self, ftl_mod: &FtlMod) -> Result<ModApplyReport, ModApplyError> {
+        for file in ftl_mod.files() {
+            self.snapshot(file.inner_path())?;
+        }
+
+        self.package.apply_mod(ftl_mod)
+    }
+
+    /// Records the changes made through this transaction in the journal.
+    ///
+    /// Inner paths that ended up with their original content are not recorded.
+    pub fn commit(mut self) -> Result<(), std::io::Error> {
+        let mut changes = Vec::new();
+        for (inner_path, previous) in self.snapshots.iter() {
+            let installed_hash = self.package.entry_by_path(inner_path)
+                .map(|entry| entry.content_hash())
+                .transpose()?;
+            let replaced = previous.as_ref()
+                .map(|content| (ContentHash::of(content), content.clone()));
+
+            let kind = match (&replaced, &installed_hash) {
+                (None, None) => continue,
+                (Some((replaced_hash, _)), Some(installed_hash)) if replaced_hash == installed_hash => continue,
+                (None, Some(_)) => JournalChangeKind::Added,
+                (Some(_), Some(_)) => JournalChangeKind::Overwritten,
+                (Some(_), None) => JournalChangeKind::Removed,
+            };
+
+            changes.push(JournalChange {
+                inner_path: inner_path.clone(),
+                kind,
+                replaced,
+                installed_hash,
+            });
+        }
+
+        self.journal.records.push(ModRecord {
+            mod_name: std::mem::take(&mut self.mod_name),
+            changes,
+        });
+        self.snapshots.clear();
+        Ok(())
+    }
+
+    /// Reverts the changes made through this transaction. Same as dropping it.
+    ///
+    /// Removed entries are restored at the end of the package.
+    pub fn rollback(self) {}
+
+    fn snapshot(&mut self, inner_path: &str) -> Result<(), std::io::Error> {
+        if self.snapshot_paths.contains(inner_path) {
+            return Ok(());
+        }
+
+        let previous = self.package.entry_by_path(inner_path)
+            .map(|entry| entry.content())
+            .transpose()?;
+        self.snapshot_paths.insert(inner_path.to_string());
+        self.snapshots.push((inner_path.to_string(), previous));
+        Ok(())
+    }
+}
+
+impl Drop for InstallTransaction<'_> {
+    fn drop(&mut self) {
+        for (inner_path, previous) in self.snapshots.drain(..).rev() {
+            match previous {
+                Some(content) => self.package.put_entry(PackageEntry::from_byte_array(inner_path, content)),
+                None => {
+                    self.package.remove_entry(inner_path);
+                }
+            }
+        }
+    }
+}
+
+impl Package {
+    /// Starts a journaled installation of a mod called `mod_name` into this [`Package`].
+    ///
+    /// Returns a [ModAlreadyInstalledError] if the journal already records a mod of that name.
+    pub fn begin_install<'a, S: Into<String>>(&'a mut self, journal: &'a mut InstallJournal, mod_name: S) -> Result<InstallTransaction<'a>, ModAlreadyInstalledError> {
+        let mod_name = mod_name.into();
+        if journal.is_installed(&mod_name) {
+            return Err(ModAlreadyInstalledError(mod_name));
+        }
+
+        Ok(InstallTransaction {
+            package: self,
+            journal,
+            mod_name,
+            snapshots: Vec::new(),
+            snapshot_paths: HashSet::new(),
+        })
+    }
+
+    /// Reverts the changes of the mod called `mod_name`, and removes it from the journal.
+    ///
+    /// Nothing is changed if a mod installed later touched any of the same inner paths (see
+    /// [`InstallJournal::conflicts`]), or if an entry no longer has the content the mod installed.
+    /// Removed entries are restored at the end of this [`Package`].
+    pub fn uninstall_mod<S: AsRef<str>>(&mut self, journal: &mut InstallJournal, mod_name: S) -> Result<(), UninstallError> {
+        let mod_name = mod_name.as_ref();
+        let position = journal.position_of(mod_name)
+            .ok_or_else(|| UninstallError::ModNotInstalledError(mod_name.to_string()))?;
+
+        let conflicts = journal.conflicts(mod_name);
+        if !conflicts.is_empty() {
+            return Err(UninstallError::LaterModConflictError {
+                mod_name: mod_name.to_string(),
+                conflicts,
+            });
+        }
+
+        for change in journal.records[position].changes.iter() {
+            let current_hash = self.entry_by_path(&change.inner_path)
+                .map(|entry| entry.content_hash())
+                .transpose()?;
+            if current_hash != change.installed_hash {
+                return Err(UninstallError::EntryModifiedError(change.inner_path.clone()));
+            }
+        }
+
+        let record = journal.records.remove(position);
+        for change in record.changes.into_iter().rev() {
+            match change.replaced {
+                Some((_, content)) => self.put_entry(PackageEntry::from_byte_array(change.inner_path, content)),
+                None => {
+                    self.remove_entry(change.inner_path);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn read_record(input: &mut impl Read) -> Result<ModRecord, JournalReadError> {
+    let mod_name = read_string(input)?;
+    let changes = read_counted(input, read_change)?;
+    Ok(ModRecord { mod_name, changes })
+}
+
+fn read_change(input: &mut impl Read) -> Result<JournalChange, JournalReadError> {
+    let tag = input.read_u8()?;
+    let kind = match tag {
+        tag if tag == CHANGE_ADDED => JournalChangeKind::Added,
+        tag if tag == CHANGE_OVERWRITTEN => JournalChangeKind::Overwritten,
+        tag if tag == CHANGE_REMOVED => JournalChangeKind::Removed,
+        _ => return Err(JournalReadError::UnknownChangeError(tag)),
+    };
+    let inner_path = read_string(input)?;
+
+    let replaced = match kind {
+        JournalChangeKind::Added => None,
+        _ => {
+            let hash = read_hash(input, &inner_path)?;
+            let content = read_bytes(input)?;
+            if ContentHash::of(&content) != hash {
+                return Err(JournalReadError::CorruptContentError(inner_path));
+            }
+            Some((hash, content))
+        }
+    };
+
+    let installed_hash = match kind {
+        JournalChangeKind::Removed => None,
+        _ => Some(read_hash(input, &inner_path)?),
+    };
+
+    Ok(JournalChange { inner_path, kind, replaced, installed_hash })
+}
+
+fn read_hash(input: &mut impl Read, inner_path: &str) -> Result<ContentHash, JournalReadError> {
+    ContentHash::from_bytes(read_bytes(input)?)
+        .ok_or_else(|| JournalReadError::InvalidHashError(inner_path.to_string()))
+}
+
+fn read_string(input: &mut impl Read) -> Result<String, JournalReadError> {
+    Ok(String::from_utf8(read_bytes(input)?)?)
+}
+
+fn write_change(change: &JournalChange, output: &mut impl Write) -> Result<(), std::io::Error> {
+    let tag = match change.kind {
+        JournalChangeKind::Added => CHANGE_ADDED,
+        JournalChangeKind::Overwritten => CHANGE_OVERWRITTEN,
+        JournalChangeKind::Removed => CHANGE_REMOVED,
+    };
+    output.write_u8(tag)?;
+    write_bytes(change.inner_path.as_bytes(), output)?;
+
+    if let Some((hash, content)) = &change.replaced {
+        write_bytes(hash.as_bytes(), output)?;
+        write_bytes(content, output)?;
+    }
+    if let Some(hash) = &change.installed_hash {
+        write_bytes(hash.as_bytes(), output)?;
+    }
+
+    Ok(())
+}
@@ -1,12 +1,43 @@
 pub use crate::shared::entry::PackageEntry;
 pub use crate::shared::package::Package;
-pub use crate::shared::reader::PackageReader;
+pub use crate::shared::reader::{FileReadStrategy, IndexEntry, PackageReader, ReadStrategy, SeekableInput};
+pub use crate::shared::index::PackageIndex;
 pub use crate::shared::writer::PackageWriter;
+pub use crate::dat::{DatReader, DatWriter, SalvageReport};
+pub use crate::pkg::{calculate_path_hash, DeduplicationReport, PathHashCollision, PkgReadWarning, PkgReader, PkgWriter};
+pub use crate::shared::tree::{DirectoryNode, DirectoryWalk};
+pub use crate::shared::merge::{MergePolicy, MergeReport};
+pub use crate::shared::diff::PackageDiff;
+pub use crate::shared::hash::ContentHash;
+pub use crate::patch::PackagePatch;
+pub use crate::manifest::{Manifest, ManifestEntry, ManifestVerification};
+pub use crate::vanilla::{Game, Identification, KnownRelease, ReleaseDatabase};
+pub use crate::backup::{BackupStatus, PackageBackup};
+pub use crate::journal::{InstallJournal, InstallTransaction, JournalChange, JournalChangeKind, JournalConflict};
+#[cfg(feature = "slipstream")]
+pub use crate::slipstream::{FtlMod, ModApplyReport, ModConflictAnalysis, ModFile, ModFileAction, ModTouch, TouchedPath, XmlPatchReport};
 
 mod shared;
 mod dat;
 mod pkg;
+mod patch;
+mod manifest;
+mod journal;
+mod backup;
+mod vanilla;
+#[cfg(feature = "lua")]
+pub mod lua;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "slipstream")]
+mod slipstream;
 
 pub mod error {
     pub use crate::shared::error::*;
+    pub use crate::patch::error::*;
+    pub use crate::manifest::error::*;
+    pub use crate::journal::error::*;
+    pub use crate::backup::error::*;
+    #[cfg(feature = "slipstream")]
+    pub use crate::slipstream::error::*;
 }
@@ -0,0 +1,228 @@
+//! Lua 5.1 bindings, as used by the Into the Breach mod loader.
+//!
+//! With the `lua-module` feature, the library can be built as a dynamic library (see the README)
+//! and loaded into a Lua 5.1 or LuaJIT interpreter with `require("ftldat")`. Applications
+//! embedding Lua can register the same module with [`create_module`] instead.
+//!
+//! Functions that can fail for reasons other than being called incorrectly follow the usual Lua
+//! convention of returning `nil` and an error message, so that they can be wrapped in `assert`:
+//! ```lua
+//! local ftldat = require("ftldat")
+//!
+//! local package = assert(ftldat.from_path_dat("resource.dat"))
+//! package:put_entry("scripts/mod.lua", "return {}")
+//! assert(package:into_path_dat("resource.dat"))
+//! ```
+
+use std::cell::{Ref, RefCell, RefMut};
+use std::path::Path;
+use std::rc::Rc;
+
+use mlua::{Lua, MetaMethod, Table, UserData, UserDataMethods};
+
+use crate::{Package, PackageEntry};
+use crate::error::InnerPathNotFoundError;
+use crate::shared::writer::write_via_temporary_file;
+
+/// Outcome of a fallible operation, passed to Lua as the value on success, or as `nil` followed
+/// by the error message on failure.
+type LuaOutcome<T> = Result<T, String>;
+
+/// Creates the table returned by `require("ftldat")`.
+pub fn create_module(lua: &Lua) -> mlua::Result<Table<'_>> {
+    let module = lua.create_table()?;
+    module.set("_VERSION", env!("CARGO_PKG_VERSION"))?;
+
+    module.set("new_package", lua.create_function(|_, ()| {
+        Ok(LuaPackage::new(Package::new()))
+    })?)?;
+    module.set("from_path_dat", lua.create_function(|_, source_path: String| {
+        Ok(read_package(Package::from_path_dat(source_path)))
+    })?)?;
+    module.set("from_path_pkg", lua.create_function(|_, source_path: String| {
+        Ok(read_package(Package::from_path_pkg(source_path)))
+    })?)?;
+    module.set("from_bytes_dat", lua.create_function(|_, bytes: mlua::String| {
+        Ok(read_package(Package::from_bytes_dat(bytes.as_bytes().to_vec())))
+    })?)?;
+    module.set("from_bytes_pkg", lua.create_function(|_, bytes: mlua::String| {
+        Ok(read_package(Package::from_bytes_pkg(bytes.as_bytes().to_vec())))
+    })?)?;
+
+    Ok(module)
+}
+
+/// Entry point of the `require`-able module, `luaopen_ftldat`.
+#[cfg(feature = "lua-module")]
+#[mlua::lua_module]
+fn ftldat(lua: &Lua) -> mlua::Result<Table<'_>> {
+    create_module(lua)
+}
+
+fn read_package<E: ToString>(result: Result<Package, E>) -> LuaOutcome<LuaPackage> {
+    result
+        .map(LuaPackage::new)
+        .map_err(|error| error.to_string())
+}
+
+fn outcome<E: ToString>(result: Result<(), E>) -> LuaOutcome<bool> {
+    result
+        .map(|_| true)
+        .map_err(|error| error.to_string())
+}
+
+/// A [`Package`] shared with Lua. Consuming methods (`into_path_*`) leave it empty, after which
+/// any further use raises an error.
+#[derive(Clone)]
+struct LuaPackage(Rc<RefCell<Option<Package>>>);
+
+impl LuaPackage {
+    fn new(package: Package) -> LuaPackage {
+        LuaPackage(Rc::new(RefCell::new(Some(package))))
+    }
+
+    fn borrow(&self) -> mlua::Result<Ref<'_, Package>> {
+        Ref::filter_map(self.0.borrow(), Option::as_ref)
+            .map_err(|_| consumed_error())
+    }
+
+    fn borrow_mut(&self) -> mlua::Result<RefMut<'_, Package>> {
+        RefMut::filter_map(self.0.borrow_mut(), Option::as_mut)
+            .map_err(|_| consumed_error())
+    }
+
+    fn take(&self) -> mlua::Result<Package> {
+        self.0.borrow_mut()
+            .take()
+            .ok_or_else(consumed_error)
+    }
+}
+
+fn consumed_error() -> mlua::Error {
+    mlua::Error::RuntimeError("package was consumed by a previous call to `into_path_dat` or `into_path_pkg`".to_string())
+}
+
+impl UserData for LuaPackage {
+    fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method("entry_count", |_, this, ()| {
+            Ok(this.borrow()?.entry_count())
+        });
+        methods.add_meta_method(MetaMethod::Len, |_, this, ()| {
+            Ok(this.borrow()?.entry_count())
+        });
+        methods.add_method("inner_paths", |_, this, ()| {
+            Ok(this.borrow()?.inner_paths())
+        });
+        methods.add_method("entry_exists", |_, this, inner_path: String| {
+            Ok(this.borrow()?.entry_exists(inner_path))
+        });
+        methods.add_method("entry_by_path", |_, this, inner_path: String| {
+            let entry = this.borrow()?.entry_exists(&inner_path)
+                .then(|| LuaPackageEntry { package: this.clone(), inner_path });
+            Ok(entry)
+        });
+        methods.add_method("entries", |_, this, ()| {
+            let entries = this.borrow()?.iter()
+                .map(|entry| LuaPackageEntry { package: this.clone(), inner_path: entry.inner_path().to_string() })
+                .collect::<Vec<_>>();
+            Ok(entries)
+        });
+        methods.add_method("content_by_path", |lua, this, inner_path: String| {
+            let content = match this.borrow()?.entry_by_path(&inner_path) {
+                Some(entry) => entry.content().map_err(|error| error.to_string()),
+                None => Err(InnerPathNotFoundError(inner_path).to_string()),
+            };
+            match content {
+                Ok(content) => Ok(Ok(lua.create_string(content)?)),
+                Err(message) => Ok(Err(message)),
+            }
+        });
+
+        methods.add_method("put_entry", |_, this, (inner_path, content): (String, mlua::String)| {
+            this.borrow_mut()?.put_entry(PackageEntry::from_byte_array(inner_path, content.as_bytes().to_vec()));
+            Ok(())
+        });
+        methods.add_method("put_file", |_, this, (inner_path, source_path): (String, String)| {
+            if !Path::new(&source_path).is_file() {
+                return Ok(Err(format!("'{}' is not a file", source_path)));
+            }
+
+            this.borrow_mut()?.put_entry(PackageEntry::from_file(inner_path, source_path));
+            Ok(Ok(true))
+        });
+        methods.add_method("add_entry", |_, this, (inner_path, content): (String, mlua::String)| {
+            let entry = PackageEntry::from_byte_array(inner_path, content.as_bytes().to_vec());
+            Ok(outcome(this.borrow_mut()?.add_entry(entry)))
+        });
+        methods.add_method("remove_entry", |_, this, inner_path: String| {
+            Ok(this.borrow_mut()?.remove_entry(inner_path))
+        });
+        methods.add_method("rename_entry", |_, this, (old_inner_path, new_inner_path): (String, String)| {
+            Ok(outcome(this.borrow_mut()?.rename_entry(old_inner_path, new_inner_path)))
+        });
+        methods.add_method("clear", |_, this, ()| {
+            this.borrow_mut()?.clear();
+            Ok(())
+        });
+
+        methods.add_method("extract", |_, this, destination_path: String| {
+            Ok(outcome(this.borrow()?.extract(destination_path)))
+        });
+        // The package may still read entries from the destination, so write to a temporary file
+        // and move it into place, which leaves the file being read intact
+        methods.add_method("to_path_dat", |_, this, destination_path: String| {
+            let package = this.borrow()?;
+            Ok(outcome(write_via_temporary_file(Path::new(&destination_path), |path| package.to_path_dat(path))))
+        });
+        methods.add_method("to_path_pkg", |_, this, destination_path: String| {
+            let package = this.borrow()?;
+            Ok(outcome(write_via_temporary_file(Path::new(&destination_path), |path| package.to_path_pkg(path))))
+        });
+        methods.add_method("into_path_dat", |_, this, destination_path: String| {
+            Ok(outcome(this.take()?.into_path_dat(destination_path)))
+        });
+        methods.add_method("into_path_pkg", |_, this, destination_path: String| {
+            Ok(outcome(this.take()?.into_path_pkg(destination_path)))
+        });
+    }
+}
+
+/// A [`PackageEntry`] shared with Lua, looked up by its `inner_path` whenever it is used, so that
+/// it reflects later changes to the package.
+struct LuaPackageEntry {
+    package: LuaPackage,
+    inner_path: String,
+}
+
+impl LuaPackageEntry {
+    fn with_entry<T, E, F>(&self, f: F) -> mlua::Result<LuaOutcome<T>>
+        where E: ToString, F: FnOnce(&PackageEntry) -> Result<T, E>
+    {
+        let package = self.package.borrow()?;
+        let outcome = match package.entry_by_path(&self.inner_path) {
+            Some(entry) => f(entry).map_err(|error| error.to_string()),
+            None => Err(InnerPathNotFoundError(self.inner_path.clone()).to_string()),
+        };
+        Ok(outcome)
+    }
+}
+
+impl UserData for LuaPackageEntry {
+    fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method("inner_path", |_, this, ()| {
+            Ok(this.inner_path.clone())
+        });
+        methods.add_method("content", |lua, this, ()| {
+            match this.with_entry(|entry| entry.content())? {
+                Ok(content) => Ok(Ok(lua.create_string(content)?)),
+                Err(message) => Ok(Err(message)),
+            }
+        });
+        methods.add_method("content_size", |_, this, ()| {
+            this.with_entry(|entry| entry.content_size())
+        });
+        methods.add_method("content_hash", |_, this, ()| {
+            this.with_entry(|entry| entry.content_hash().map(|hash| hash.to_string()))
+        });
+    }
+}
@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ManifestReadError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error("manifest header is missing")]
+    MissingHeaderError(),
+    #[error("manifest uses hash algorithm '{0}', but this build uses '{}'", crate::ContentHash::ALGORITHM)]
+    HashAlgorithmMismatchError(String),
+    #[error("line {0}: expected '<hash>\\t<size>\\t<inner path>'")]
+    InvalidLineError(usize),
+}
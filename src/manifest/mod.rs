@@ -0,0 +1,240 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::{ContentHash, Package};
+use crate::manifest::error::ManifestReadError;
+
+pub(crate) mod error;
+
+// Manifest files are UTF-8 text with the following structure:
+// - header line: `#ftldat-manifest hash=<algorithm>`
+// - one line per entry, in package order: `<hash>\t<size>\t<inner path>`
+//   - `hash` := hexadecimal content hash, see `ContentHash`
+//   - `size` := decimal content size in bytes
+//
+// Empty lines are ignored.
+
+static HEADER_PREFIX: &str = "#ftldat-manifest hash=";
+
+/// A fingerprint of a [`Package`]: path, content size and content hash of each of its entries,
+/// in package order.
+#[derive(Debug, Default)]
+pub struct Manifest {
+    entries: Vec<ManifestEntry>,
+}
+
+/// A single entry of a [`Manifest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    inner_path: String,
+    size: u64,
+    hash: ContentHash,
+}
+
+impl ManifestEntry {
+    /// Returns the `inner_path` of the fingerprinted entry.
+    pub fn inner_path(&self) -> &str {
+        &self.inner_path
+    }
+
+    /// Returns the size of the fingerprinted entry's content, in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the hash of the fingerprinted entry's content.
+    pub fn hash(&self) -> &ContentHash {
+        &self.hash
+    }
+}
+
+/// Outcome of [`Manifest::verify`].
+#[derive(Debug, Default)]
+pub struct ManifestVerification {
+    missing: Vec<String>,
+    mismatched: Vec<String>,
+    unexpected: Vec<String>,
+}
+
+impl ManifestVerification {
+    /// Returns paths listed in the manifest, but absent from the package.
+    pub fn missing(&self) -> &[String] {
+        &self.missing
+    }
+
+    /// Returns paths whose content size or hash differs from the manifest.
+    pub fn mismatched(&self) -> &[String] {
+        &self.mismatched
+    }
+
+    /// Returns paths present in the package, but not listed in the manifest.
+    pub fn unexpected(&self) -> &[String] {
+        &self.unexpected
+    }
+
+    /// Returns `true` if the package matches the manifest exactly.
+    pub fn is_ok(&self) -> bool {
+        self.deviation_count() == 0
+    }
+
+    /// Returns the total number of paths that deviate from the manifest.
+    pub fn deviation_count(&self) -> usize {
+        self.missing.len() + self.mismatched.len() + self.unexpected.len()
+    }
+}
+
+impl Manifest {
+    /// Creates a manifest of the specified [`Package`], hashing the content of every entry.
+    pub fn from_package(package: &Package) -> Result<Manifest, std::io::Error> {
+        let mut entries = Vec::with_capacity(package.entry_count());
+        for entry in package.iter() {
+            let content = entry.content()?;
+            entries.push(ManifestEntry {
+                inner_path: entry.inner_path().to_string(),
+                size: content.len() as u64,
+                hash: ContentHash::of(&content),
+            });
+        }
+
+        Ok(Manifest { entries })
+    }
+
+    /// Returns the entries of this manifest, in package order.
+    pub fn entries(&self) -> &[ManifestEntry] {
+        &self.entries
+    }
+
+    /// Checks the specified [`Package`] against this manifest.
+    ///
+    /// Content is only read and hashed for entries whose size matches the manifest.
+    pub fn verify(&self, package: &Package) -> Result<ManifestVerification, std::io::Error> {
+        let mut result = ManifestVerification::default();
+
+        for manifest_entry in self.entries.iter() {
+            match package.entry_by_path(&manifest_entry.inner_path) {
+                None => result.missing.push(manifest_entry.inner_path.clone()),
+                Some(entry) => {
+                    if entry.content_size()? != manifest_entry.size
+                        || entry.content_hash()? != manifest_entry.hash {
+                        result.mismatched.push(manifest_entry.inner_path.clone());
+                    }
+                }
+            }
+        }
+
+        result.unexpected = self.unlisted_paths(package.iter().map(|entry| entry.inner_path()));
+        Ok(result)
+    }
+
+    /// Checks the package described by the `actual` manifest against this manifest, without
+    /// reading any content.
+    pub fn compare(&self, actual: &Manifest) -> ManifestVerification {
+        let mut result = ManifestVerification::default();
+
+        let actual_entries: HashMap<&str, &ManifestEntry> = actual.entries.iter()
+            .map(|entry| (entry.inner_path(), entry))
+            .collect();
+        for manifest_entry in self.entries.iter() {
+            match actual_entries.get(manifest_entry.inner_path()) {
+                None => result.missing.push(manifest_entry.inner_path.clone()),
+                Some(entry) => {
+                    if *entry != manifest_entry {
+                        result.mismatched.push(manifest_entry.inner_path.clone());
+                    }
+                }
+            }
+        }
+
+        result.unexpected = self.unlisted_paths(actual.entries.iter().map(|entry| entry.inner_path()));
+        result
+    }
+
+    /// Returns those of the `inner_paths` which are not listed in this manifest.
+    fn unlisted_paths<'a, I: Iterator<Item=&'a str>>(&self, inner_paths: I) -> Vec<String> {
+        let listed_paths: HashSet<&str> = self.entries.iter()
+            .map(|entry| entry.inner_path())
+            .collect();
+        inner_paths
+            .filter(|inner_path| !listed_paths.contains(inner_path))
+            .map(|inner_path| inner_path.to_string())
+            .collect()
+    }
+
+    // region <Input>
+    /// Reads a manifest from the file at the specified path.
+    pub fn from_path<P: AsRef<Path>>(source_path: P) -> Result<Manifest, ManifestReadError> {
+        let file = File::options()
+            .read(true)
+            .open(source_path)?;
+        Manifest::from_input(file)
+    }
+
+    /// Reads a manifest from the specified input.
+    ///
+    /// Returns a [ManifestReadError::HashAlgorithmMismatchError] if the manifest was created with
+    /// a different hash algorithm than the one this crate was compiled with.
+    pub fn from_input<I: Read>(input: I) -> Result<Manifest, ManifestReadError> {
+        let mut lines = BufReader::new(input).lines();
+
+        let header = lines.next()
+            .ok_or(ManifestReadError::MissingHeaderError())??;
+        let algorithm = header.trim_end()
+            .strip_prefix(HEADER_PREFIX)
+            .ok_or(ManifestReadError::MissingHeaderError())?;
+        if algorithm != ContentHash::ALGORITHM {
+            return Err(ManifestReadError::HashAlgorithmMismatchError(algorithm.to_string()));
+        }
+
+        let mut entries = Vec::new();
+        for (index, line) in lines.enumerate() {
+            let line = line?;
+            let line = line.trim_end_matches('\r');
+            if line.is_empty() {
+                continue;
+            }
+
+            // Header is line 1
+            let line_number = index + 2;
+            let mut fields = line.splitn(3, '\t');
+            let entry = match (fields.next(), fields.next(), fields.next()) {
+                (Some(hash), Some(size), Some(inner_path)) => ManifestEntry {
+                    inner_path: inner_path.to_string(),
+                    size: size.parse().map_err(|_| ManifestReadError::InvalidLineError(line_number))?,
+                    hash: hash.parse().map_err(|_| ManifestReadError::InvalidLineError(line_number))?,
+                },
+                _ => return Err(ManifestReadError::InvalidLineError(line_number)),
+            };
+            entries.push(entry);
+        }
+
+        Ok(Manifest { entries })
+    }
+    // endregion
+
+    // region <Output>
+    /// Writes this manifest to file at the specified path.
+    pub fn to_path<P: AsRef<Path>>(&self, destination_path: P) -> Result<(), std::io::Error> {
+        let file = File::options()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(destination_path)?;
+
+        let mut output = BufWriter::new(file);
+        self.to_output(&mut output)?;
+        output.flush()
+    }
+
+    /// Writes this manifest to the specified output.
+    pub fn to_output<O: Write>(&self, mut output: O) -> Result<(), std::io::Error> {
+        writeln!(output, "{}{}", HEADER_PREFIX, ContentHash::ALGORITHM)?;
+        for entry in self.entries.iter() {
+            writeln!(output, "{}\t{}\t{}", entry.hash, entry.size, entry.inner_path)?;
+        }
+
+        Ok(())
+    }
+    // endregion
+}
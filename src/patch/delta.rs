@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use crate::patch::error::{PatchApplyError, PatchCreateError};
+
+/// Length of base content blocks that are indexed when looking for matches.
+const BLOCK_SIZE: usize = 16;
+/// Multiplier of the polynomial rolling hash.
+const HASH_BASE: u64 = 257;
+
+/// A single step of reconstructing target content from base content.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum DeltaInstruction {
+    /// Copy `length` bytes starting at `offset` in the base content.
+    Copy { offset: u32, length: u32 },
+    /// Insert the literal bytes.
+    Insert(Vec<u8>),
+}
+
+/// Computes instructions that reconstruct `target` from `base`.
+///
+/// Base content is indexed in fixed-size blocks, and the target is scanned with a rolling hash
+/// to find occurrences of those blocks. Each match is then extended in both directions, so that
+/// matching regions don't have to be block-aligned.
+pub(super) fn compute_delta(inner_path: &str, base: &[u8], target: &[u8]) -> Result<Vec<DeltaInstruction>, PatchCreateError> {
+    let mut result = Vec::new();
+    if base.len() < BLOCK_SIZE || target.len() < BLOCK_SIZE {
+        if !target.is_empty() {
+            result.push(DeltaInstruction::Insert(target.to_vec()));
+        }
+        return Ok(result);
+    }
+
+    let mut block_index: HashMap<u64, usize> = HashMap::new();
+    for offset in (0..=base.len() - BLOCK_SIZE).step_by(BLOCK_SIZE) {
+        block_index.entry(hash_block(&base[offset..offset + BLOCK_SIZE])).or_insert(offset);
+    }
+
+    // Factor by which the outgoing byte was multiplied when it entered the window
+    let outgoing_factor = (1..BLOCK_SIZE).fold(1u64, |factor, _| factor.wrapping_mul(HASH_BASE));
+
+    let mut position = 0;
+    let mut pending_start = 0;
+    let mut hash = hash_block(&target[..BLOCK_SIZE]);
+    while position + BLOCK_SIZE <= target.len() {
+        let matched_offset = block_index.get(&hash)
+            .copied()
+            .filter(|offset| base[*offset..*offset + BLOCK_SIZE] == target[position..position + BLOCK_SIZE]);
+
+        match matched_offset {
+            Some(base_offset) => {
+                let (mut start, mut base_start) = (position, base_offset);
+                while start > pending_start && base_start > 0 && target[start - 1] == base[base_start - 1] {
+                    start -= 1;
+                    base_start -= 1;
+                }
+
+                let (mut end, mut base_end) = (position + BLOCK_SIZE, base_offset + BLOCK_SIZE);
+                while end < target.len() && base_end < base.len() && target[end] == base[base_end] {
+                    end += 1;
+                    base_end += 1;
+                }
+
+                if start > pending_start {
+                    result.push(DeltaInstruction::Insert(target[pending_start..start].to_vec()));
+                }
+                result.push(DeltaInstruction::Copy {
+                    offset: patch_u32(inner_path, base_start)?,
+                    length: patch_u32(inner_path, end - start)?,
+                });
+
+                position = end;
+                pending_start = end;
+                if position + BLOCK_SIZE <= target.len() {
+                    hash = hash_block(&target[position..position + BLOCK_SIZE]);
+                }
+            }
+            None => {
+                if position + BLOCK_SIZE < target.len() {
+                    hash = hash
+                        .wrapping_sub((target[position] as u64).wrapping_mul(outgoing_factor))
+                        .wrapping_mul(HASH_BASE)
+                        .wrapping_add(target[position + BLOCK_SIZE] as u64);
+                }
+                position += 1;
+            }
+        }
+    }
+
+    if pending_start < target.len() {
+        result.push(DeltaInstruction::Insert(target[pending_start..].to_vec()));
+    }
+
+    Ok(result)
+}
+
+/// Converts a length or offset within the content of the entry under `inner_path` into the u32
+/// that patch files store, or fails if it doesn't fit.
+pub(super) fn patch_u32(inner_path: &str, value: usize) -> Result<u32, PatchCreateError> {
+    u32::try_from(value).map_err(|_| PatchCreateError::EntryTooLargeError(inner_path.to_string()))
+}
+
+/// Reconstructs target content by applying `instructions` to `base`.
+pub(super) fn apply_delta(inner_path: &str, base: &[u8], instructions: &[DeltaInstruction]) -> Result<Vec<u8>, PatchApplyError> {
+    let mut result = Vec::with_capacity(base.len());
+    for instruction in instructions {
+        match instruction {
+            DeltaInstruction::Copy { offset, length } => {
+                let start = *offset as usize;
+                let end = start + *length as usize;
+                let slice = base.get(start..end)
+                    .ok_or_else(|| PatchApplyError::InvalidDeltaError(inner_path.to_string()))?;
+                result.extend_from_slice(slice);
+            }
+            DeltaInstruction::Insert(bytes) => {
+                result.extend_from_slice(bytes);
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+fn hash_block(block: &[u8]) -> u64 {
+    block.iter()
+        .fold(0u64, |hash, byte| hash.wrapping_mul(HASH_BASE).wrapping_add(*byte as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::patch::delta::{apply_delta, compute_delta, patch_u32, DeltaInstruction};
+
+    fn round_trip(base: &[u8], target: &[u8]) -> Vec<DeltaInstruction> {
+        let delta = compute_delta("test", base, target).unwrap();
+        assert_eq!(target, apply_delta("test", base, &delta).unwrap());
+        delta
+    }
+
+    #[test]
+    fn delta_of_identical_content_is_single_copy() {
+        let content = b"Lorem ipsum dolor sit amet, consectetur adipiscing elit".to_vec();
+
+        let delta = round_trip(&content, &content);
+
+        assert_eq!(vec![DeltaInstruction::Copy { offset: 0, length: content.len() as u32 }], delta);
+    }
+
+    #[test]
+    fn delta_reuses_unaligned_regions_of_base() {
+        let base = b"<ship name=\"kestrel\"><hull>30</hull><weapons>4</weapons></ship>".to_vec();
+        let target = b"<!-- mod --><ship name=\"kestrel\"><hull>45</hull><weapons>4</weapons></ship>".to_vec();
+
+        let delta = round_trip(&base, &target);
+
+        let inserted: usize = delta.iter()
+            .map(|instruction| match instruction {
+                DeltaInstruction::Insert(bytes) => bytes.len(),
+                DeltaInstruction::Copy { .. } => 0,
+            })
+            .sum();
+        assert!(inserted < 20, "inserted {} bytes", inserted);
+    }
+
+    #[test]
+    fn delta_handles_short_and_empty_content() {
+        round_trip(b"", b"short");
+        round_trip(b"short", b"");
+        round_trip(b"Lorem ipsum dolor sit amet", b"");
+    }
+
+    #[test]
+    fn apply_delta_fails_when_copy_is_out_of_bounds() {
+        let delta = vec![DeltaInstruction::Copy { offset: 4, length: 10 }];
+
+        let result = apply_delta("test", b"short", &delta);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn patch_u32_fails_when_value_exceeds_u32() {
+        assert_eq!(u32::MAX, patch_u32("test", u32::MAX as usize).unwrap());
+        assert!(patch_u32("test", u32::MAX as usize + 1).is_err());
+    }
+}
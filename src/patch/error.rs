@@ -0,0 +1,49 @@
+use std::string::FromUtf8Error;
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum PatchCreateError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error("content of inner path '{0}' is 4 GiB or larger, which patch files cannot describe")]
+    EntryTooLargeError(String),
+}
+
+#[derive(Error, Debug)]
+pub enum PatchReadError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error("patch file signature mismatch")]
+    SignatureMismatchError(),
+    #[error("unsupported patch format version '{0}'")]
+    UnsupportedVersionError(u32),
+    #[error("unknown patch operation tag '{0}'")]
+    UnknownOperationError(u8),
+    #[error("unknown delta instruction tag '{0}'")]
+    UnknownInstructionError(u8),
+    #[error(transparent)]
+    InvalidInnerPathError(#[from] FromUtf8Error),
+}
+
+#[derive(Error, Debug)]
+pub enum PatchApplyError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error("base package has no entry under inner path '{0}'")]
+    MissingBaseEntryError(String),
+    #[error("base package already has an entry under inner path '{0}'")]
+    UnexpectedBaseEntryError(String),
+    #[error("entry under inner path '{inner_path}' does not match the patch's base: expected size {expected_size} and CRC32 {expected_crc:08x}, but found size {actual_size} and CRC32 {actual_crc:08x}")]
+    BaseMismatchError {
+        inner_path: String,
+        expected_size: u32,
+        expected_crc: u32,
+        actual_size: u64,
+        actual_crc: u32,
+    },
+    #[error("delta for inner path '{0}' does not fit its base content")]
+    InvalidDeltaError(String),
+    #[error("patched content of inner path '{0}' does not match the patch's target")]
+    TargetMismatchError(String),
+}
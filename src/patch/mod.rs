@@ -0,0 +1,408 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::{Package, PackageEntry};
+use crate::patch::delta::{apply_delta, compute_delta, patch_u32, DeltaInstruction};
+use crate::patch::error::{PatchApplyError, PatchCreateError, PatchReadError};
+use crate::shared::binary::{read_bytes, read_counted, write_bytes};
+
+mod delta;
+pub(crate) mod error;
+
+// Patch files have the following structure:
+// - `FTLPATCH` signature (8x u8)
+// - `version` := patch format version (1x u32)
+// - `operation_count` := number of operations (1x u32)
+// - Operations (`operation_count` x Operation)
+//
+// Operations have the following structure:
+// - `tag` := kind of the operation: 0 = add, 1 = remove, 2 = modify (1x u8)
+// - `str_len` := inner path length (1x u32)
+// - inner path (`str_len` x u8)
+// - for add operations:
+//   - `data_size` := content length (1x u32)
+//   - content (`data_size` x u8)
+// - for remove operations:
+//   - base checksum (1x Checksum)
+// - for modify operations:
+//   - base checksum (1x Checksum)
+//   - target checksum (1x Checksum)
+//   - `instruction_count` := number of delta instructions (1x u32)
+//   - Instructions (`instruction_count` x Instruction)
+//
+// Checksums have the following structure:
+// - `data_size` := content length (1x u32)
+// - `crc` := CRC32 of the content (1x u32)
+//
+// Checksums are CRC32 even with the `sha256` feature, unlike `ContentHash`: they only guard against
+// applying a patch onto the wrong base by accident, and a fixed format allows applying patches
+// created by builds with either feature set.
+//
+// Contents, and therefore all lengths and offsets, must be smaller than 4 GiB.
+//
+// Instructions have the following structure:
+// - `tag` := kind of the instruction: 0 = copy from base, 1 = insert bytes (1x u8)
+// - for copy instructions:
+//   - `offset` := offset within the base content (1x u32)
+//   - `length` := number of bytes to copy (1x u32)
+// - for insert instructions:
+//   - `length` := number of bytes to insert (1x u32)
+//   - bytes (`length` x u8)
+//
+// All numbers are little-endian, as in DAT packages.
+
+/// FTLPATCH
+static PATCH_SIGNATURE: [u8; 8] = [70, 84, 76, 80, 65, 84, 67, 72];
+static PATCH_VERSION: u32 = 1;
+
+static OPERATION_ADD: u8 = 0;
+static OPERATION_REMOVE: u8 = 1;
+static OPERATION_MODIFY: u8 = 2;
+
+static INSTRUCTION_COPY: u8 = 0;
+static INSTRUCTION_INSERT: u8 = 1;
+
+/// A self-contained set of changes that turns one [`Package`] (the base) into another one (the
+/// target).
+///
+/// Added entries are stored in full, removed entries only by path, and modified entries as
+/// byte-level deltas against their base content. Every removed or modified entry also records the
+/// size and CRC32 of the base content it expects, so that a patch is never applied onto a package
+/// it wasn't created for.
+#[derive(Debug)]
+pub struct PackagePatch {
+    operations: Vec<PatchOperation>,
+}
+
+#[derive(Debug)]
+enum PatchOperation {
+    Add {
+        inner_path: String,
+        content: Vec<u8>,
+    },
+    Remove {
+        inner_path: String,
+        base: Checksum,
+    },
+    Modify {
+        inner_path: String,
+        base: Checksum,
+        target: Checksum,
+        instructions: Vec<DeltaInstruction>,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Checksum {
+    size: u32,
+    crc: u32,
+}
+
+impl Checksum {
+    /// Returns `None` if the content is too large for its size to be stored in a patch file.
+    fn of(content: &[u8]) -> Option<Checksum> {
+        Some(Checksum {
+            size: u32::try_from(content.len()).ok()?,
+            crc: crc32fast::hash(content),
+        })
+    }
+
+    fn of_entry(inner_path: &str, content: &[u8]) -> Result<Checksum, PatchCreateError> {
+        Checksum::of(content)
+            .ok_or_else(|| PatchCreateError::EntryTooLargeError(inner_path.to_string()))
+    }
+}
+
+impl PackagePatch {
+    /// Creates a patch containing all changes required to turn the `base` [`Package`] into the
+    /// `target` [`Package`].
+    ///
+    /// Returns a [PatchCreateError::EntryTooLargeError] if the content of an entry to be described
+    /// by the patch is 4 GiB or larger.
+    pub fn create(base: &Package, target: &Package) -> Result<PackagePatch, PatchCreateError> {
+        let diff = base.diff(target)?;
+        let mut operations = Vec::new();
+
+        for inner_path in diff.removed() {
+            let base_content = content_of(base, inner_path)?;
+            operations.push(PatchOperation::Remove {
+                inner_path: inner_path.clone(),
+                base: Checksum::of_entry(inner_path, &base_content)?,
+            });
+        }
+
+        for inner_path in diff.modified() {
+            let base_content = content_of(base, inner_path)?;
+            let target_content = content_of(target, inner_path)?;
+            operations.push(PatchOperation::Modify {
+                inner_path: inner_path.clone(),
+                base: Checksum::of_entry(inner_path, &base_content)?,
+                target: Checksum::of_entry(inner_path, &target_content)?,
+                instructions: compute_delta(inner_path, &base_content, &target_content)?,
+            });
+        }
+
+        for inner_path in diff.added() {
+            let content = content_of(target, inner_path)?;
+            patch_u32(inner_path, content.len())?;
+            operations.push(PatchOperation::Add {
+                inner_path: inner_path.clone(),
+                content,
+            });
+        }
+
+        Ok(PackagePatch { operations })
+    }
+
+    /// Returns paths of entries this patch adds to the base package.
+    pub fn added_paths(&self) -> Vec<&str> {
+        self.operations.iter()
+            .filter_map(|operation| match operation {
+                PatchOperation::Add { inner_path, .. } => Some(inner_path.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns paths of entries this patch removes from the base package.
+    pub fn removed_paths(&self) -> Vec<&str> {
+        self.operations.iter()
+            .filter_map(|operation| match operation {
+                PatchOperation::Remove { inner_path, .. } => Some(inner_path.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns paths of entries whose content this patch modifies.
+    pub fn modified_paths(&self) -> Vec<&str> {
+        self.operations.iter()
+            .filter_map(|operation| match operation {
+                PatchOperation::Modify { inner_path, .. } => Some(inner_path.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns `true` if this patch doesn't change anything.
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Applies this patch to the specified [`Package`].
+    ///
+    /// Before anything is changed, every removed and modified entry is checked against the size
+    /// and CRC32 recorded in the patch, and every added path is checked to be free. If any of
+    /// these checks fails, the package is left untouched and an error is returned.
+    ///
+    /// Modified entries keep their position and are stored in-memory; added entries are appended
+    /// at the end of the package.
+    pub fn apply(&self, package: &mut Package) -> Result<(), PatchApplyError> {
+        let mut removed_paths = Vec::new();
+        let mut patched_entries = Vec::new();
+        let mut added_entries = Vec::new();
+
+        for operation in self.operations.iter() {
+            match operation {
+                PatchOperation::Add { inner_path, content } => {
+                    if package.entry_exists(inner_path) {
+                        return Err(PatchApplyError::UnexpectedBaseEntryError(inner_path.clone()));
+                    }
+                    added_entries.push(PackageEntry::from_byte_array(inner_path, content.clone()));
+                }
+                PatchOperation::Remove { inner_path, base } => {
+                    verify_base_entry(package, inner_path, base)?;
+                    removed_paths.push(inner_path.as_str());
+                }
+                PatchOperation::Modify { inner_path, base, target, instructions } => {
+                    let base_content = verify_base_entry(package, inner_path, base)?;
+                    let content = apply_delta(inner_path, &base_content, instructions)?;
+                    if Checksum::of(&content).as_ref() != Some(target) {
+                        return Err(PatchApplyError::TargetMismatchError(inner_path.clone()));
+                    }
+                    patched_entries.push(PackageEntry::from_byte_array(inner_path, content));
+                }
+            }
+        }
+
+        package.remove_entries(removed_paths);
+        for entry in patched_entries {
+            package.put_entry(entry);
+        }
+        for entry in added_entries {
+            package.put_entry(entry);
+        }
+
+        Ok(())
+    }
+
+    // region <Input>
+    /// Reads a patch from the file at the specified path.
+    pub fn from_path<P: AsRef<Path>>(source_path: P) -> Result<PackagePatch, PatchReadError> {
+        let file = File::options()
+            .read(true)
+            .open(source_path)?;
+        PackagePatch::from_input(BufReader::new(file))
+    }
+
+    /// Reads a patch from the specified input.
+    pub fn from_input<I: Read>(mut input: I) -> Result<PackagePatch, PatchReadError> {
+        let mut signature = [0u8; 8];
+        input.read_exact(&mut signature)?;
+        if signature != PATCH_SIGNATURE {
+            return Err(PatchReadError::SignatureMismatchError());
+        }
+
+        let version = input.read_u32::<LittleEndian>()?;
+        if version != PATCH_VERSION {
+            return Err(PatchReadError::UnsupportedVersionError(version));
+        }
+
+        let operations = read_counted(&mut input, read_operation)?;
+        Ok(PackagePatch { operations })
+    }
+    // endregion
+
+    // region <Output>
+    /// Writes this patch to file at the specified path.
+    pub fn to_path<P: AsRef<Path>>(&self, destination_path: P) -> Result<(), std::io::Error> {
+        let file = File::options()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(destination_path)?;
+
+        let mut output = BufWriter::new(file);
+        self.to_output(&mut output)?;
+        output.flush()
+    }
+
+    /// Writes this patch to the specified output.
+    pub fn to_output<O: Write>(&self, mut output: O) -> Result<(), std::io::Error> {
+        output.write_all(&PATCH_SIGNATURE)?;
+        output.write_u32::<LittleEndian>(PATCH_VERSION)?;
+        output.write_u32::<LittleEndian>(self.operations.len() as u32)?;
+
+        for operation in self.operations.iter() {
+            write_operation(operation, &mut output)?;
+        }
+
+        Ok(())
+    }
+    // endregion
+}
+
+fn content_of(package: &Package, inner_path: &str) -> Result<Vec<u8>, std::io::Error> {
+    package.entry_by_path(inner_path)
+        .expect("Entry listed in diff is missing from package")
+        .content()
+}
+
+/// Checks that the entry under `inner_path` matches the `expected` checksum, and returns its content.
+fn verify_base_entry(package: &Package, inner_path: &str, expected: &Checksum) -> Result<Vec<u8>, PatchApplyError> {
+    let content = package.entry_by_path(inner_path)
+        .ok_or_else(|| PatchApplyError::MissingBaseEntryError(inner_path.to_string()))?
+        .content()?;
+
+    if Checksum::of(&content).as_ref() != Some(expected) {
+        return Err(PatchApplyError::BaseMismatchError {
+            inner_path: inner_path.to_string(),
+            expected_size: expected.size,
+            expected_crc: expected.crc,
+            actual_size: content.len() as u64,
+            actual_crc: crc32fast::hash(&content),
+        });
+    }
+
+    Ok(content)
+}
+
+fn read_operation(input: &mut impl Read) -> Result<PatchOperation, PatchReadError> {
+    let tag = input.read_u8()?;
+    let inner_path = String::from_utf8(read_bytes(input)?)?;
+
+    if tag == OPERATION_ADD {
+        Ok(PatchOperation::Add {
+            inner_path,
+            content: read_bytes(input)?,
+        })
+    } else if tag == OPERATION_REMOVE {
+        Ok(PatchOperation::Remove {
+            inner_path,
+            base: read_checksum(input)?,
+        })
+    } else if tag == OPERATION_MODIFY {
+        let base = read_checksum(input)?;
+        let target = read_checksum(input)?;
+        let instructions = read_counted(input, read_instruction)?;
+        Ok(PatchOperation::Modify { inner_path, base, target, instructions })
+    } else {
+        Err(PatchReadError::UnknownOperationError(tag))
+    }
+}
+
+fn read_instruction(input: &mut impl Read) -> Result<DeltaInstruction, PatchReadError> {
+    let tag = input.read_u8()?;
+    if tag == INSTRUCTION_COPY {
+        Ok(DeltaInstruction::Copy {
+            offset: input.read_u32::<LittleEndian>()?,
+            length: input.read_u32::<LittleEndian>()?,
+        })
+    } else if tag == INSTRUCTION_INSERT {
+        Ok(DeltaInstruction::Insert(read_bytes(input)?))
+    } else {
+        Err(PatchReadError::UnknownInstructionError(tag))
+    }
+}
+
+fn read_checksum(input: &mut impl Read) -> Result<Checksum, std::io::Error> {
+    Ok(Checksum {
+        size: input.read_u32::<LittleEndian>()?,
+        crc: input.read_u32::<LittleEndian>()?,
+    })
+}
+
+fn write_operation(operation: &PatchOperation, output: &mut impl Write) -> Result<(), std::io::Error> {
+    match operation {
+        PatchOperation::Add { inner_path, content } => {
+            output.write_u8(OPERATION_ADD)?;
+            write_bytes(inner_path.as_bytes(), output)?;
+            write_bytes(content, output)?;
+        }
+        PatchOperation::Remove { inner_path, base } => {
+            output.write_u8(OPERATION_REMOVE)?;
+            write_bytes(inner_path.as_bytes(), output)?;
+            write_checksum(base, output)?;
+        }
+        PatchOperation::Modify { inner_path, base, target, instructions } => {
+            output.write_u8(OPERATION_MODIFY)?;
+            write_bytes(inner_path.as_bytes(), output)?;
+            write_checksum(base, output)?;
+            write_checksum(target, output)?;
+            output.write_u32::<LittleEndian>(instructions.len() as u32)?;
+            for instruction in instructions {
+                match instruction {
+                    DeltaInstruction::Copy { offset, length } => {
+                        output.write_u8(INSTRUCTION_COPY)?;
+                        output.write_u32::<LittleEndian>(*offset)?;
+                        output.write_u32::<LittleEndian>(*length)?;
+                    }
+                    DeltaInstruction::Insert(bytes) => {
+                        output.write_u8(INSTRUCTION_INSERT)?;
+                        write_bytes(bytes, output)?;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn write_checksum(checksum: &Checksum, output: &mut impl Write) -> Result<(), std::io::Error> {
+    output.write_u32::<LittleEndian>(checksum.size)?;
+    output.write_u32::<LittleEndian>(checksum.crc)
+}
@@ -3,6 +3,7 @@ use crate::shared::error::{PackageReadError, PackageWriteError};
 
 #[derive(Error, Debug)]
 #[error("package file is corrupt")]
+#[allow(clippy::enum_variant_names)]
 pub(super) enum FileCorruptError {
     #[error("signature: expected bytes '{expected}', but found '{actual}'")]
     SignatureMismatchError {
@@ -21,9 +22,9 @@ pub(super) enum FileCorruptError {
     },
 }
 
-impl Into<PackageReadError> for FileCorruptError {
-    fn into(self) -> PackageReadError {
-        PackageReadError(Box::new(self))
+impl From<FileCorruptError> for PackageReadError {
+    fn from(error: FileCorruptError) -> PackageReadError {
+        PackageReadError(Box::new(error))
     }
 }
 
@@ -53,9 +54,9 @@ pub(super) enum PkgWriteError {
     PathAreaSizeExceededError(usize)
 }
 
-impl Into<PackageWriteError> for PkgWriteError {
-    fn into(self) -> PackageWriteError {
-        PackageWriteError(Box::new(self))
+impl From<PkgWriteError> for PackageWriteError {
+    fn from(error: PkgWriteError) -> PackageWriteError {
+        PackageWriteError(Box::new(error))
     }
 }
 
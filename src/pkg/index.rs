@@ -0,0 +1,94 @@
+use std::io::{Cursor, ErrorKind, Seek, SeekFrom};
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::pkg::reader::{EntryBuilder, PkgHeader};
+use crate::pkg::shared::calculate_path_hash;
+use crate::shared::error::PackageReadError;
+use crate::shared::reader::IndexEntry;
+
+/// Read-only view of a PKG package's index, which parses entry headers only when they are needed.
+///
+/// Only the `inner_path_hash` of each entry header is read up front; looking up an entry hashes
+/// the requested inner path, and compares inner paths only for entries with a matching hash.
+#[derive(Debug)]
+pub(crate) struct PkgIndex {
+    header: PkgHeader,
+    /// `(inner_path_hash, position)` of every entry, sorted by hash.
+    hashes: Vec<(u32, usize)>,
+}
+
+impl PkgIndex {
+    pub(crate) fn read(bytes: &[u8]) -> Result<PkgIndex, PackageReadError> {
+        let mut input = Cursor::new(bytes);
+        let header = PkgHeader::read(&mut input, false, &mut Vec::new())?;
+
+        let path_region_end = header.path_region_offset() + header.path_region_size;
+        if path_region_end > bytes.len() {
+            return Err(std::io::Error::from(ErrorKind::UnexpectedEof).into());
+        }
+
+        let mut hashes = Vec::with_capacity(header.entry_count);
+        for position in 0..header.entry_count {
+            input.seek(SeekFrom::Start(header.entry_header_offset(position)))?;
+            hashes.push((input.read_u32::<BigEndian>()?, position));
+        }
+        hashes.sort_unstable();
+
+        Ok(PkgIndex { header, hashes })
+    }
+
+    pub(crate) fn entry_count(&self) -> usize {
+        self.header.entry_count
+    }
+
+    pub(crate) fn entry_at(&self, bytes: &[u8], position: usize) -> Result<IndexEntry, PackageReadError> {
+        let mut input = Cursor::new(bytes);
+        input.seek(SeekFrom::Start(self.header.entry_header_offset(position)))?;
+
+        let mut entry_builder = EntryBuilder::read_entry_header(&mut input, self.header.entry_size)?;
+        entry_builder.read_inner_path(&mut Cursor::new(self.path_region(bytes)))?;
+        entry_builder.validate(false, &mut Vec::new())?;
+        Ok(entry_builder.build())
+    }
+
+    pub(crate) fn position_of(&self, bytes: &[u8], inner_path: &str) -> Result<Option<usize>, PackageReadError> {
+        let mut input = Cursor::new(bytes);
+        let path_region = self.path_region(bytes);
+        for position in self.positions_by_path_hash(calculate_path_hash(inner_path)) {
+            // Skip the entry's `inner_path_hash` and `entry_options`
+            input.seek(SeekFrom::Start(self.header.entry_header_offset(position) + 5))?;
+            let inner_path_offset = input.read_u24::<BigEndian>()? as usize;
+
+            let candidate = path_region.get(inner_path_offset..)
+                .and_then(|candidate| candidate.split(|byte| *byte == 0).next())
+                .ok_or_else(|| std::io::Error::from(ErrorKind::UnexpectedEof))?;
+            if candidate == inner_path.as_bytes() {
+                return Ok(Some(position));
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub(crate) fn positions_by_path_hash(&self, path_hash: u32) -> impl Iterator<Item=usize> + '_ {
+        let start = self.hashes.partition_point(|(entry_hash, _)| *entry_hash < path_hash);
+        self.hashes[start..].iter()
+            .take_while(move |(entry_hash, _)| *entry_hash == path_hash)
+            .map(|(_, position)| *position)
+    }
+
+    /// Returns the positions of entries in every group of entries sharing the same stored hash,
+    /// ordered by hash.
+    pub(crate) fn path_hash_collisions(&self) -> Vec<(u32, Vec<usize>)> {
+        self.hashes.chunk_by(|(left, _), (right, _)| left == right)
+            .filter(|group| group.len() > 1)
+            .map(|group| (group[0].0, group.iter().map(|(_, position)| *position).collect()))
+            .collect()
+    }
+
+    fn path_region<'a>(&self, bytes: &'a [u8]) -> &'a [u8] {
+        let path_region_offset = self.header.path_region_offset();
+        &bytes[path_region_offset..path_region_offset + self.header.path_region_size]
+    }
+}
@@ -1,8 +1,11 @@
 pub mod reader;
 pub mod writer;
+pub(crate) mod index;
 mod constants;
 mod shared;
 mod error;
 
 pub use crate::pkg::reader::*;
-pub use crate::pkg::writer::*;
\ No newline at end of file
+pub use crate::pkg::writer::*;
+pub use crate::pkg::shared::{calculate_path_hash, PathHashCollision};
+pub(crate) use crate::pkg::shared::find_path_hash_collisions;
\ No newline at end of file
@@ -1,15 +1,15 @@
-use std::fs::File;
-use std::io::{Cursor, Read, Seek, SeekFrom};
-use std::rc::Rc;
+use std::fmt::{Display, Formatter};
+use std::io::{Cursor, ErrorKind, Read, Seek, SeekFrom};
 
 use byteorder::{BigEndian, ReadBytesExt};
-use memmap2::Mmap;
 
-use crate::{Package, PackageEntry, PackageReader};
+use crate::PackageReader;
 use crate::pkg::constants::{ENTRY_SIZE, INDEX_SIZE, PKG_DEFLATED, PKG_SIGNATURE};
 use crate::pkg::error::{EntryReadError, FileCorruptError};
 use crate::pkg::shared::calculate_path_hash;
+use crate::shared::binary::read_vec;
 use crate::shared::error::PackageReadError;
+use crate::shared::reader::{IndexEntry, SeekableInput};
 
 // PKG packages have the following structure:
 // - `PKG\n` signature (4x u8)
@@ -29,19 +29,142 @@ use crate::shared::error::PackageReadError;
 // - padding for 4-byte alignment (u8/u16/u24, depending on length of path region)
 // - Entries / data region (`Entry.data_size` x `entry_count`, until EOF)
 
-pub struct PkgReader();
+/// Reads packages in PKG format.
+///
+/// By default the reader is strict, and fails on any inconsistency in the package. A
+/// [lenient](PkgReader::lenient) reader instead tolerates problems that do not prevent reading
+/// entries' content, and reports them as [warnings](PkgReadWarning):
+/// - headers larger than this crate knows about, whose trailing bytes are skipped,
+/// - inner paths that do not match the hash stored in their entry header,
+/// - unknown bits in entries' option flags.
+///
+/// Warnings are returned by [`read_index_with_warnings`](PkgReader::read_index_with_warnings).
+/// Reading a package through [`PackageReader`] discards them, so use
+/// [`Package::from_path_pkg_lenient`](crate::Package::from_path_pkg_lenient) or
+/// [`Package::from_bytes_pkg_lenient`](crate::Package::from_bytes_pkg_lenient) to get them along
+/// with the package:
+/// ```no_run
+/// use ftldat::Package;
+///
+/// let (package, warnings) = Package::from_path_pkg_lenient("path/to/file.pkg").unwrap();
+/// for warning in warnings {
+///     println!("{}", warning);
+/// }
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PkgReader {
+    lenient: bool,
+}
+
+/// A problem encountered while reading a PKG package, which did not prevent reading it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PkgReadWarning {
+    /// The package's header is larger than expected; the unknown trailing bytes were skipped.
+    ExtendedIndexHeader {
+        index_size: u16,
+    },
+    /// Entry headers are larger than expected; the unknown trailing bytes of each were skipped.
+    ExtendedEntryHeader {
+        entry_size: u16,
+    },
+    /// The entry's inner path does not match the hash stored in its entry header.
+    PathHashMismatch {
+        inner_path: String,
+        expected: u32,
+        actual: u32,
+    },
+    /// The entry's options have bits set that this crate does not know about; they were ignored.
+    UnknownEntryOptions {
+        inner_path: String,
+        entry_options: u8,
+    },
+}
+
+impl PkgReader {
+    /// Creates a strict reader.
+    pub fn new() -> PkgReader {
+        PkgReader::default()
+    }
+
+    /// Creates a lenient reader, which records non-fatal problems as warnings instead of failing.
+    pub fn lenient() -> PkgReader {
+        PkgReader {
+            lenient: true,
+        }
+    }
+
+    /// Reads the index of a package like [`PackageReader::read_index`], and returns it along with
+    /// the warnings recorded while reading it.
+    ///
+    /// Strict readers fail on most problems, so they only record unknown entry options.
+    pub fn read_index_with_warnings(&self, input: &mut dyn SeekableInput) -> Result<(Vec<IndexEntry>, Vec<PkgReadWarning>), PackageReadError> {
+        let input_length = input.seek(SeekFrom::End(0))?;
+        let mut warnings = Vec::new();
+        let header = PkgHeader::read(input, self.lenient, &mut warnings)?;
+
+        let path_region_end = header.path_region_offset() as u64 + header.path_region_size as u64;
+        if path_region_end > input_length {
+            return Err(std::io::Error::from(ErrorKind::UnexpectedEof).into());
+        }
+
+        let mut entry_builders: Vec<EntryBuilder> = Vec::with_capacity(header.entry_count);
+        for _ in 0..header.entry_count {
+            let entry_builder = EntryBuilder::read_entry_header(input, header.entry_size)?;
+            entry_builders.push(entry_builder);
+        }
+
+        let mut cursor = Cursor::new(read_vec(input, header.path_region_size)?);
+
+        let mut result = Vec::with_capacity(header.entry_count);
+        for mut entry_builder in entry_builders {
+            entry_builder.read_inner_path(&mut cursor)?;
+            entry_builder.validate(self.lenient, &mut warnings)?;
+            result.push(entry_builder.build());
+        }
+
+        Ok((result, warnings))
+    }
+}
 
 impl PackageReader for PkgReader {
-    fn read_package_from_file(&self, file: File) -> Result<Package, PackageReadError> {
-        let mut result = Package::new();
+    fn read_index(&self, input: &mut dyn SeekableInput) -> Result<Vec<IndexEntry>, PackageReadError> {
+        self.read_index_with_warnings(input)
+            .map(|(index, _)| index)
+    }
+}
+
+impl Display for PkgReadWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PkgReadWarning::ExtendedIndexHeader { index_size } =>
+                write!(f, "header: skipped {} unknown bytes of header", index_size.saturating_sub(INDEX_SIZE)),
+            PkgReadWarning::ExtendedEntryHeader { entry_size } =>
+                write!(f, "header: skipped {} unknown bytes of each entry header", entry_size.saturating_sub(ENTRY_SIZE)),
+            PkgReadWarning::PathHashMismatch { inner_path, expected, actual } =>
+                write!(f, "entry: expected inner path '{}' hash to match {}, but was {}", inner_path, expected, actual),
+            PkgReadWarning::UnknownEntryOptions { inner_path, entry_options } =>
+                write!(f, "entry: ignored unknown options {:#04x} of inner path '{}'", entry_options, inner_path),
+        }
+    }
+}
 
-        let mmap = unsafe {
-            Mmap::map(&file)
-        }?;
+/// Fixed-size header at the start of PKG packages.
+#[derive(Debug)]
+pub(super) struct PkgHeader {
+    pub(super) index_size: u16,
+    pub(super) entry_size: u16,
+    pub(super) entry_count: usize,
+    pub(super) path_region_size: usize,
+}
 
-        let mut cursor = Cursor::new(&mmap[..INDEX_SIZE as usize]);
+impl PkgHeader {
+    /// Reads and validates the header, leaving the input positioned at the first entry header.
+    ///
+    /// If `lenient` is set, headers larger than expected are accepted, and recorded in `warnings`.
+    pub(super) fn read(input: &mut (impl Read + Seek + ?Sized), lenient: bool, warnings: &mut Vec<PkgReadWarning>) -> Result<PkgHeader, PackageReadError> {
+        input.seek(SeekFrom::Start(0))?;
         for expected_signature_byte in PKG_SIGNATURE {
-            let signature_byte = cursor.read_u8()?;
+            let signature_byte = input.read_u8()?;
             if signature_byte != expected_signature_byte {
                 return Err(FileCorruptError::SignatureMismatchError {
                     expected: expected_signature_byte,
@@ -50,49 +173,55 @@ impl PackageReader for PkgReader {
             }
         }
 
-        let index_size = cursor.read_u16::<BigEndian>()?;
-        if index_size != INDEX_SIZE {
+        let index_size = input.read_u16::<BigEndian>()?;
+        if index_size != INDEX_SIZE && !(lenient && index_size > INDEX_SIZE) {
             return Err(FileCorruptError::HeaderSizeMismatchError {
                 expected: INDEX_SIZE,
                 actual: index_size,
             }.into());
         }
 
-        let entry_size = cursor.read_u16::<BigEndian>()?;
-        if entry_size != ENTRY_SIZE {
+        let entry_size = input.read_u16::<BigEndian>()?;
+        if entry_size != ENTRY_SIZE && !(lenient && entry_size > ENTRY_SIZE) {
             return Err(FileCorruptError::EntriesHeaderSizeMismatchError {
                 expected: ENTRY_SIZE,
                 actual: entry_size,
             }.into());
         }
 
-        let entry_count = cursor.read_u32::<BigEndian>()? as usize;
-        let path_region_size = cursor.read_u32::<BigEndian>()? as usize;
-        let path_region_offset = INDEX_SIZE as usize + (ENTRY_SIZE as usize * entry_count) as usize;
+        let header = PkgHeader {
+            index_size,
+            entry_size,
+            entry_count: input.read_u32::<BigEndian>()? as usize,
+            path_region_size: input.read_u32::<BigEndian>()? as usize,
+        };
 
-        let mut cursor = Cursor::new(&mmap[INDEX_SIZE as usize..path_region_offset]);
-        let mut entry_builders: Vec<EntryBuilder> = Vec::with_capacity(entry_count);
-        for _ in 0..entry_count {
-            let entry_builder = EntryBuilder::read_entry_header(&mut cursor)?;
-            entry_builders.push(entry_builder);
+        if index_size > INDEX_SIZE {
+            warnings.push(PkgReadWarning::ExtendedIndexHeader { index_size });
+            input.seek(SeekFrom::Start(index_size as u64))?;
+        }
+        if entry_size > ENTRY_SIZE {
+            warnings.push(PkgReadWarning::ExtendedEntryHeader { entry_size });
         }
 
-        let path_region_slice = mmap[path_region_offset .. path_region_offset + path_region_size].to_vec();
-        let mut cursor = Cursor::new(path_region_slice);
+        Ok(header)
+    }
 
-        let mmap_rc = Rc::new(mmap);
-        for mut entry_builder in entry_builders {
-            entry_builder.read_inner_path(&mut cursor)?;
-            let entry = entry_builder.build(mmap_rc.clone());
-            result.add_entry(entry)?;
-        }
+    /// Returns the offset of the header of the entry at the specified position, relative to the
+    /// start of the package.
+    pub(super) fn entry_header_offset(&self, position: usize) -> u64 {
+        self.index_size as u64 + self.entry_size as u64 * position as u64
+    }
 
-        Ok(result)
+    /// Returns the offset of the path region, relative to the start of the package.
+    pub(super) fn path_region_offset(&self) -> usize {
+        self.entry_header_offset(self.entry_count) as usize
     }
 }
 
-struct EntryBuilder {
+pub(super) struct EntryBuilder {
     inner_path_hash: u32,
+    entry_options: u8,
     inner_path_offset: u32,
     data_offset: u32,
     data_size: u32,
@@ -100,7 +229,8 @@ struct EntryBuilder {
 }
 
 impl EntryBuilder {
-    fn read_entry_header(input: &mut impl Read) -> Result<EntryBuilder, PackageReadError> {
+    /// Reads an entry header of the specified size, skipping any bytes beyond the known fields.
+    pub(super) fn read_entry_header(input: &mut (impl Read + ?Sized), entry_size: u16) -> Result<EntryBuilder, PackageReadError> {
         let inner_path_hash = input.read_u32::<BigEndian>()?;
         let entry_options = input.read_u8()?;
         let is_data_deflated = (entry_options & PKG_DEFLATED) != 0;
@@ -110,12 +240,16 @@ impl EntryBuilder {
         let data_size = input.read_u32::<BigEndian>()?;
         let _unpacked_size = input.read_u32::<BigEndian>()?;
 
+        let unknown_size = entry_size.saturating_sub(ENTRY_SIZE) as u64;
+        std::io::copy(&mut input.take(unknown_size), &mut std::io::sink())?;
+
         if is_data_deflated {
             return Err(EntryReadError::UnsupportedDeflatedEntryError().into());
         }
 
         Ok(EntryBuilder {
             inner_path_hash,
+            entry_options,
             inner_path_offset,
             data_offset,
             data_size,
@@ -123,27 +257,47 @@ impl EntryBuilder {
         })
     }
 
-    fn read_inner_path(&mut self, path_region_input: &mut Cursor<Vec<u8>>) -> Result<(), PackageReadError> {
+    pub(super) fn read_inner_path(&mut self, path_region_input: &mut (impl Read + Seek)) -> Result<(), PackageReadError> {
         path_region_input.seek(SeekFrom::Start(self.inner_path_offset as u64))?;
         self.inner_path = Some(read_null_terminated_string(path_region_input)?);
+        Ok(())
+    }
 
-        let inner_path = self.inner_path.as_ref().unwrap();
+    /// Checks the entry's inner path against its hash, and its options for unknown bits.
+    ///
+    /// If `lenient` is set, a hash mismatch is recorded in `warnings` instead of failing.
+    pub(super) fn validate(&self, lenient: bool, warnings: &mut Vec<PkgReadWarning>) -> Result<(), PackageReadError> {
+        let inner_path = self.inner_path.as_ref().expect("Missing inner path!");
         let calculated_hash = calculate_path_hash(inner_path);
         if calculated_hash != self.inner_path_hash {
-            return Err(EntryReadError::PathHashMismatchError {
+            if !lenient {
+                return Err(EntryReadError::PathHashMismatchError {
+                    inner_path: inner_path.to_string(),
+                    expected: self.inner_path_hash,
+                    actual: calculated_hash,
+                }.into());
+            }
+
+            warnings.push(PkgReadWarning::PathHashMismatch {
                 inner_path: inner_path.to_string(),
                 expected: self.inner_path_hash,
                 actual: calculated_hash,
-            }.into());
+            });
+        }
+
+        if self.entry_options & !PKG_DEFLATED != 0 {
+            warnings.push(PkgReadWarning::UnknownEntryOptions {
+                inner_path: inner_path.to_string(),
+                entry_options: self.entry_options,
+            });
         }
 
         Ok(())
     }
 
-    fn build(self, input: Rc<Mmap>) -> PackageEntry {
-        PackageEntry::from_memory_mapped_file(
+    pub(super) fn build(self) -> IndexEntry {
+        IndexEntry::new(
             self.inner_path.expect("Missing inner path!"),
-            input,
             self.data_offset as u64,
             self.data_size as u64
         )
@@ -1,17 +1,63 @@
 
-pub(super) fn calculate_path_hash<S: AsRef<str>>(inner_path: S) -> u32 {
+use std::collections::BTreeMap;
+
+/// Calculates the hash of an inner path, as stored in the entry headers of PKG packages.
+///
+/// The hash is case-insensitive, so paths differing only in case share the same hash.
+pub fn calculate_path_hash<S: AsRef<str>>(inner_path: S) -> u32 {
     let mut hash: u32 = 0;
 
     let inner_path = inner_path.as_ref();
     for lowercase_char in inner_path.to_lowercase().chars() {
         let byte = lowercase_char as u32;
         hash = hash.rotate_right(5);
-        hash = hash ^ byte;
+        hash ^= byte;
     }
 
     hash
 }
 
+/// A group of entries in a package whose inner paths share the same hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathHashCollision {
+    path_hash: u32,
+    inner_paths: Vec<String>,
+}
+
+impl PathHashCollision {
+    pub(crate) fn new(path_hash: u32, inner_paths: Vec<String>) -> PathHashCollision {
+        PathHashCollision { path_hash, inner_paths }
+    }
+
+    /// Returns the hash shared by the colliding inner paths.
+    pub fn path_hash(&self) -> u32 {
+        self.path_hash
+    }
+
+    /// Returns the colliding inner paths, in the order in which the package stores them.
+    pub fn inner_paths(&self) -> &[String] {
+        &self.inner_paths
+    }
+}
+
+/// Groups the specified inner paths by their hash, and returns the groups that contain more than
+/// one path, ordered by hash.
+pub(crate) fn find_path_hash_collisions<I, S>(inner_paths: I) -> Vec<PathHashCollision>
+    where I: IntoIterator<Item=S>, S: AsRef<str> {
+    let mut inner_paths_by_hash: BTreeMap<u32, Vec<String>> = BTreeMap::new();
+    for inner_path in inner_paths {
+        let inner_path = inner_path.as_ref();
+        inner_paths_by_hash.entry(calculate_path_hash(inner_path))
+            .or_default()
+            .push(inner_path.to_string());
+    }
+
+    inner_paths_by_hash.into_iter()
+        .filter(|(_, inner_paths)| inner_paths.len() > 1)
+        .map(|(path_hash, inner_paths)| PathHashCollision::new(path_hash, inner_paths))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use crate::pkg::shared::calculate_path_hash;
@@ -1,17 +1,57 @@
+use std::collections::HashMap;
 use std::io::{Seek, SeekFrom, Write};
 
 use byteorder::{BigEndian, WriteBytesExt};
 
-use crate::{Package, PackageEntry, PackageWriter};
+use crate::{ContentHash, Package, PackageEntry, PackageWriter};
 use crate::pkg::constants::{ENTRY_SIZE, INDEX_SIZE, PKG_SIGNATURE};
 use crate::pkg::error::PkgWriteError;
 use crate::pkg::shared::calculate_path_hash;
 use crate::shared::error::PackageWriteError;
 
-pub struct PkgWriter();
+/// Writes [`Package`]s in PKG format.
+///
+/// PKG entry headers point to their content by offset and size, so several entries may share the
+/// same data region. With deduplication enabled (see [`PkgWriter::with_deduplication`]), entries
+/// with identical content are written only once.
+#[derive(Debug, Default)]
+pub struct PkgWriter {
+    deduplicate: bool,
+}
 
-impl PackageWriter for PkgWriter {
-    fn write_package_to_output<T: Write + Seek>(&self, package: &Package, mut output: T) -> Result<(), PackageWriteError> {
+/// Describes the effect of deduplication performed by [`PkgWriter::write_package`].
+#[derive(Debug, Default)]
+pub struct DeduplicationReport {
+    deduplicated_entry_count: usize,
+    bytes_saved: u64,
+}
+
+impl DeduplicationReport {
+    /// Returns the number of entries that share their data region with an earlier entry.
+    pub fn deduplicated_entry_count(&self) -> usize {
+        self.deduplicated_entry_count
+    }
+
+    /// Returns the number of content bytes that did not have to be written.
+    pub fn bytes_saved(&self) -> u64 {
+        self.bytes_saved
+    }
+}
+
+impl PkgWriter {
+    /// Creates a writer that writes the content of every entry separately.
+    pub fn new() -> PkgWriter {
+        PkgWriter { deduplicate: false }
+    }
+
+    /// Creates a writer that hashes entry contents, and writes each distinct content only once.
+    pub fn with_deduplication() -> PkgWriter {
+        PkgWriter { deduplicate: true }
+    }
+
+    /// Writes the [`Package`] to the specified output, and reports how much data was saved by
+    /// deduplication. The report is empty if deduplication is disabled.
+    pub fn write_package<T: Write + Seek>(&self, package: &Package, mut output: T) -> Result<DeduplicationReport, PackageWriteError> {
         output.write_all(&PKG_SIGNATURE)?;
         output.write_u16::<BigEndian>(INDEX_SIZE)?;
         output.write_u16::<BigEndian>(ENTRY_SIZE)?;
@@ -22,14 +62,42 @@ impl PackageWriter for PkgWriter {
 
         output.write_u32::<BigEndian>(package.entry_count() as u32)?;
 
+        let mut report = DeduplicationReport::default();
         let mut data_offset: u32 = 0;
         let mut entry_headers: Vec<EntryHeader> = Vec::with_capacity(package.entry_count());
         let mut path_region_buffer: Vec<u8> = Vec::new();
+        // Entries whose content has to be written to the data region, in order
+        let mut data_entries: Vec<&PackageEntry> = Vec::with_capacity(package.entry_count());
+        // Distinct contents written so far, by size and hash
+        let mut blobs: HashMap<(usize, ContentHash), Vec<(&PackageEntry, u32)>> = HashMap::new();
         for entry in package.iter() {
-            let mut entry_header = EntryHeader::from(entry);
+            let content = entry.content()?;
+            let mut entry_header = EntryHeader::new(entry, content.len() as u32);
             entry_header.inner_path_offset = path_region_buffer.len() as u32;
-            entry_header.data_offset = data_offset;
-            data_offset += entry.content()?.len() as u32;
+
+            let shared_data_offset = if self.deduplicate {
+                let candidates = blobs.entry((content.len(), ContentHash::of(&content))).or_default();
+                let shared_data_offset = find_identical_blob(candidates, &content)?;
+                if shared_data_offset.is_none() {
+                    candidates.push((entry, data_offset));
+                }
+                shared_data_offset
+            } else {
+                None
+            };
+
+            match shared_data_offset {
+                Some(shared_data_offset) => {
+                    entry_header.data_offset = shared_data_offset;
+                    report.deduplicated_entry_count += 1;
+                    report.bytes_saved += content.len() as u64;
+                }
+                None => {
+                    entry_header.data_offset = data_offset;
+                    data_offset += content.len() as u32;
+                    data_entries.push(entry);
+                }
+            }
 
             path_region_buffer.extend_from_slice(entry.inner_path().as_bytes());
             // Append null terminator
@@ -58,14 +126,33 @@ impl PackageWriter for PkgWriter {
         drop(path_region_buffer);
 
         output.seek(SeekFrom::Start(data_region_offset))?;
-        for entry in package.iter() {
+        for entry in data_entries {
             output.write_all(&entry.content()?)?;
         }
 
-        Ok(())
+        Ok(report)
     }
 }
 
+impl PackageWriter for PkgWriter {
+    fn write_package_to_output<T: Write + Seek>(&self, package: &Package, output: T) -> Result<(), PackageWriteError> {
+        self.write_package(package, output)
+            .map(|_| ())
+    }
+}
+
+/// Returns the data offset of the candidate whose content is identical to `content`, if any.
+/// Contents are compared byte by byte, so that hash collisions never merge different contents.
+fn find_identical_blob(candidates: &[(&PackageEntry, u32)], content: &[u8]) -> Result<Option<u32>, PackageWriteError> {
+    for (candidate, data_offset) in candidates {
+        if candidate.content()? == content {
+            return Ok(Some(*data_offset));
+        }
+    }
+
+    Ok(None)
+}
+
 struct EntryHeader {
     inner_path_hash: u32,
     entry_options: u8,
@@ -76,6 +163,18 @@ struct EntryHeader {
 }
 
 impl EntryHeader {
+    fn new(entry: &PackageEntry, data_size: u32) -> EntryHeader {
+        EntryHeader {
+            inner_path_hash: calculate_path_hash(entry.inner_path()),
+            // We do not support deflated entries, so always write out 0 for entry options.
+            entry_options: 0,
+            inner_path_offset: 0,
+            data_offset: 0,
+            data_size,
+            unpacked_data_size: data_size,
+        }
+    }
+
     fn write_entry_header(self, output: &mut impl Write) -> Result<(), PackageWriteError> {
         output.write_u32::<BigEndian>(self.inner_path_hash)?;
         output.write_u8(self.entry_options)?;
@@ -87,18 +186,3 @@ impl EntryHeader {
         Ok(())
     }
 }
-
-impl From<&PackageEntry> for EntryHeader {
-    fn from(entry: &PackageEntry) -> Self {
-        let content = entry.content().expect("Failed to read content of entry");
-        EntryHeader {
-            inner_path_hash: calculate_path_hash(&entry.inner_path()),
-            // We do not support deflated entries, so always write out 0 for entry options.
-            entry_options: 0,
-            inner_path_offset: 0,
-            data_offset: 0,
-            data_size: content.len() as u32,
-            unpacked_data_size: content.len() as u32,
-        }
-    }
-}
\ No newline at end of file
@@ -0,0 +1,59 @@
+//! Primitives shared by readers and writers of binary formats.
+
+use std::io::{Read, Write};
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+/// Reads a length-prefixed byte array, see [`read_vec`].
+pub(crate) fn read_bytes(input: &mut impl Read) -> Result<Vec<u8>, std::io::Error> {
+    let length = input.read_u32::<LittleEndian>()? as usize;
+    read_vec(input, length)
+}
+
+/// Reads exactly `length` bytes.
+///
+/// The buffer grows as bytes are actually read, so a corrupt `length` fails with
+/// [`UnexpectedEof`](std::io::ErrorKind::UnexpectedEof) instead of allocating up front.
+pub(crate) fn read_vec(input: &mut (impl Read + ?Sized), length: usize) -> Result<Vec<u8>, std::io::Error> {
+    let mut buffer = Vec::new();
+    input.take(length as u64).read_to_end(&mut buffer)?;
+    if buffer.len() != length {
+        return Err(std::io::ErrorKind::UnexpectedEof.into());
+    }
+
+    Ok(buffer)
+}
+
+/// Reads `input` to its end, unless it holds more than `limit` bytes, in which case `None` is
+/// returned without reading past the limit.
+#[cfg(feature = "slipstream")]
+pub(crate) fn read_limited(input: &mut (impl Read + ?Sized), limit: u64) -> Result<Option<Vec<u8>>, std::io::Error> {
+    let mut buffer = Vec::new();
+    input.take(limit.saturating_add(1)).read_to_end(&mut buffer)?;
+    if buffer.len() as u64 > limit {
+        return Ok(None);
+    }
+
+    Ok(Some(buffer))
+}
+
+/// Reads a count-prefixed sequence of items, each of which is read with `read_item`.
+///
+/// The result grows as items are actually read, so a corrupt count fails once the input runs out
+/// instead of allocating up front.
+pub(crate) fn read_counted<I, T, E, F>(input: &mut I, mut read_item: F) -> Result<Vec<T>, E>
+    where I: Read, E: From<std::io::Error>, F: FnMut(&mut I) -> Result<T, E> {
+    let count = input.read_u32::<LittleEndian>()?;
+    let mut result = Vec::new();
+    for _ in 0..count {
+        result.push(read_item(input)?);
+    }
+
+    Ok(result)
+}
+
+/// Writes a length-prefixed byte array.
+pub(crate) fn write_bytes(bytes: &[u8], output: &mut impl Write) -> Result<(), std::io::Error> {
+    output.write_u32::<LittleEndian>(bytes.len() as u32)?;
+    output.write_all(bytes)
+}
@@ -0,0 +1,107 @@
+use std::fmt::{Display, Formatter};
+
+use crate::{Package, PackageEntry};
+
+/// Describes structural differences between two [`Package`]s, as computed by [`Package::diff`].
+///
+/// Removed paths are listed in the old package's order of entries; all other paths are listed
+/// in the new package's order.
+#[derive(Debug, Default)]
+pub struct PackageDiff {
+    added: Vec<String>,
+    removed: Vec<String>,
+    modified: Vec<String>,
+    unchanged: Vec<String>,
+}
+
+impl PackageDiff {
+    /// Returns paths of entries that exist only in the new package.
+    pub fn added(&self) -> &[String] {
+        &self.added
+    }
+
+    /// Returns paths of entries that exist only in the old package.
+    pub fn removed(&self) -> &[String] {
+        &self.removed
+    }
+
+    /// Returns paths of entries that exist in both packages, but with different content.
+    pub fn modified(&self) -> &[String] {
+        &self.modified
+    }
+
+    /// Returns paths of entries that exist in both packages with identical content.
+    pub fn unchanged(&self) -> &[String] {
+        &self.unchanged
+    }
+
+    /// Returns `true` if both packages contain the same paths with identical content.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Formats the diff as one line per changed entry, each prefixed with a status letter:
+/// `A` for added, `D` for removed, and `M` for modified entries.
+impl Display for PackageDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for inner_path in self.added.iter() {
+            writeln!(f, "A\t{}", inner_path)?;
+        }
+        for inner_path in self.removed.iter() {
+            writeln!(f, "D\t{}", inner_path)?;
+        }
+        for inner_path in self.modified.iter() {
+            writeln!(f, "M\t{}", inner_path)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Package {
+    /// Compares this (old) [Package] with the `other` (new) [Package], and returns a [PackageDiff]
+    /// describing added, removed, modified and unchanged entries.
+    ///
+    /// Entries present in both packages are compared by content size first, and their content is
+    /// only read if the sizes match. With the `sha256` feature, contents are then compared by
+    /// hash; otherwise, they are compared byte by byte, since CRC32 collisions are easy to come by.
+    pub fn diff(&self, other: &Package) -> Result<PackageDiff, std::io::Error> {
+        let mut result = PackageDiff::default();
+
+        for entry in self.iter() {
+            if !other.entry_exists(entry.inner_path()) {
+                result.removed.push(entry.inner_path().to_string());
+            }
+        }
+
+        for new_entry in other.iter() {
+            let inner_path = new_entry.inner_path().to_string();
+            match self.entry_by_path(&inner_path) {
+                None => result.added.push(inner_path),
+                Some(old_entry) => {
+                    if entries_have_same_content(old_entry, new_entry)? {
+                        result.unchanged.push(inner_path);
+                    } else {
+                        result.modified.push(inner_path);
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+fn entries_have_same_content(a: &PackageEntry, b: &PackageEntry) -> Result<bool, std::io::Error> {
+    if a.content_size()? != b.content_size()? {
+        return Ok(false);
+    }
+
+    // CRC32 collisions are easy to come by, so only compare hashes if they are SHA-256
+    if cfg!(feature = "sha256") {
+        Ok(a.content_hash()? == b.content_hash()?)
+    } else {
+        Ok(a.content()? == b.content()?)
+    }
+}
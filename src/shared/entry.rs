@@ -1,11 +1,15 @@
-use std::fmt::{Display, Formatter};
+use std::cell::RefCell;
+use std::fmt::{Debug, Display, Formatter};
 use std::fs::File;
-use std::io::{BufReader, Read};
+use std::io::{BufReader, Read, SeekFrom};
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 use memmap2::Mmap;
 
+use crate::shared::hash::ContentHash;
+use crate::shared::reader::SeekableInput;
+
 // Documentation imports
 #[allow(unused)]
 use crate::Package;
@@ -29,6 +33,18 @@ enum DataSource {
     FileOnDisk(PathBuf),
     MemoryMappedFile(Rc<Mmap>, u64, u64),
     InMemoryByteArray(Vec<u8>),
+    SharedByteArray(Rc<[u8]>, u64, u64),
+    OpenFile(Rc<File>, u64, u64),
+    Input(SharedInput, u64, u64),
+}
+
+/// Input shared by all entries read lazily from it.
+struct SharedInput(Rc<RefCell<dyn SeekableInput>>);
+
+impl Debug for SharedInput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SharedInput")
+    }
 }
 
 impl PackageEntry {
@@ -68,6 +84,48 @@ impl PackageEntry {
         }
     }
 
+    /// Constructs an [`PackageEntry`] from the given `inner_path`, and a region of an in-memory
+    /// buffer shared with other entries.
+    pub(crate) fn from_shared_byte_array<S: AsRef<str>>(
+        inner_path: S,
+        bytes: Rc<[u8]>,
+        offset: u64,
+        length: u64,
+    ) -> PackageEntry {
+        PackageEntry {
+            inner_path: inner_path.as_ref().to_string(),
+            source: DataSource::SharedByteArray(bytes, offset, length),
+        }
+    }
+
+    /// Constructs an [`PackageEntry`] from the given `inner_path`, and a region of an open file
+    /// shared with other entries, which is read with a positioned read when the content is requested.
+    pub(crate) fn from_open_file<S: AsRef<str>>(
+        inner_path: S,
+        file: Rc<File>,
+        offset: u64,
+        length: u64,
+    ) -> PackageEntry {
+        PackageEntry {
+            inner_path: inner_path.as_ref().to_string(),
+            source: DataSource::OpenFile(file, offset, length),
+        }
+    }
+
+    /// Constructs an [`PackageEntry`] from the given `inner_path`, and a region of an input
+    /// shared with other entries, which is read by seeking when the content is requested.
+    pub(crate) fn from_seekable_input<S: AsRef<str>>(
+        inner_path: S,
+        input: Rc<RefCell<dyn SeekableInput>>,
+        offset: u64,
+        length: u64,
+    ) -> PackageEntry {
+        PackageEntry {
+            inner_path: inner_path.as_ref().to_string(),
+            source: DataSource::Input(SharedInput(input), offset, length),
+        }
+    }
+
     /// Constructs an [`PackageEntry`] from the given `inner_path` and text `content`.
     ///
     /// * `inner_path` - path under which the file will be stored within the [`Package`].
@@ -101,6 +159,34 @@ impl PackageEntry {
         &self.inner_path
     }
 
+    pub(crate) fn set_inner_path(&mut self, inner_path: String) {
+        self.inner_path = inner_path;
+    }
+
+    /// Returns the size of this entry's content in bytes, without reading the content itself.
+    pub fn content_size(&self) -> Result<u64, std::io::Error> {
+        match &self.source {
+            DataSource::InMemoryByteArray(slice) => {
+                Ok(slice.len() as u64)
+            }
+            DataSource::FileOnDisk(path) => {
+                Ok(std::fs::metadata(path)?.len())
+            }
+            DataSource::MemoryMappedFile(_, _, length)
+            | DataSource::SharedByteArray(_, _, length)
+            | DataSource::OpenFile(_, _, length)
+            | DataSource::Input(_, _, length) => {
+                Ok(*length)
+            }
+        }
+    }
+
+    /// Calculates the hash of this entry's content, using the algorithm this crate was compiled
+    /// with (see [`ContentHash`]).
+    pub fn content_hash(&self) -> Result<ContentHash, std::io::Error> {
+        Ok(ContentHash::of(&self.content()?))
+    }
+
     /// Returns a view of this entry's content as bytes.
     pub fn content(&self) -> Result<Vec<u8>, std::io::Error> {
         match &self.source {
@@ -125,8 +211,65 @@ impl PackageEntry {
                 let slice = mmap[offset..offset + length].to_vec();
                 Ok(slice)
             }
+            DataSource::SharedByteArray(bytes, offset, length) => {
+                let offset = *offset as usize;
+                let length = *length as usize;
+                Ok(bytes[offset..offset + length].to_vec())
+            }
+            DataSource::OpenFile(file, offset, length) => {
+                let mut buffer = vec![0u8; *length as usize];
+                read_exact_at(file, &mut buffer, *offset)?;
+                Ok(buffer)
+            }
+            DataSource::Input(input, offset, length) => {
+                let mut input = input.0.borrow_mut();
+                input.seek(SeekFrom::Start(*offset))?;
+
+                let mut buffer = vec![0u8; *length as usize];
+                input.read_exact(&mut buffer)?;
+
+                Ok(buffer)
+            }
+        }
+    }
+}
+
+/// Fills the `buffer` with bytes read from the `file` at the specified `offset`, without changing
+/// the file's cursor.
+#[cfg(unix)]
+fn read_exact_at(file: &File, buffer: &mut [u8], offset: u64) -> Result<(), std::io::Error> {
+    use std::os::unix::fs::FileExt;
+    file.read_exact_at(buffer, offset)
+}
+
+/// Fills the `buffer` with bytes read from the `file` at the specified `offset`.
+#[cfg(windows)]
+fn read_exact_at(file: &File, mut buffer: &mut [u8], mut offset: u64) -> Result<(), std::io::Error> {
+    use std::os::windows::fs::FileExt;
+    while !buffer.is_empty() {
+        match file.seek_read(buffer, offset) {
+            Ok(0) => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+            Ok(read) => {
+                buffer = &mut buffer[read..];
+                offset += read as u64;
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(error),
         }
     }
+    Ok(())
+}
+
+/// Fills the `buffer` with bytes read from the `file` at the specified `offset`.
+///
+/// Targets without positioned reads seek instead. The cursor is shared with every clone of the
+/// file, but entries read from a file never rely on its position.
+#[cfg(not(any(unix, windows)))]
+fn read_exact_at(file: &File, buffer: &mut [u8], offset: u64) -> Result<(), std::io::Error> {
+    use std::io::Seek;
+    let mut file = file.try_clone()?;
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(buffer)
 }
 
 impl Display for PackageEntry {
@@ -5,6 +5,46 @@ use thiserror::Error;
 #[error("inner path '{0}' already exists within the package")]
 pub struct InnerPathAlreadyExistsError(pub(crate) String);
 
+#[derive(Error, Debug)]
+#[error("inner path '{0}' does not exist within the package")]
+pub struct InnerPathNotFoundError(pub(crate) String);
+
+#[derive(Error, Debug)]
+pub enum RenameError {
+    #[error(transparent)]
+    NotFound(#[from] InnerPathNotFoundError),
+    #[error(transparent)]
+    AlreadyExists(#[from] InnerPathAlreadyExistsError),
+}
+
+#[derive(Error, Debug)]
+#[error("merged package conflicts with existing entries under inner paths: {0:?}")]
+pub struct MergeConflictError(pub(crate) Vec<String>);
+
+impl MergeConflictError {
+    /// Returns the conflicting `inner_path`s.
+    pub fn inner_paths(&self) -> &[String] {
+        &self.0
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("'{0}' is not a valid content hash")]
+pub struct InvalidContentHashError(pub(crate) String);
+
+#[derive(Error, Debug)]
+#[error("invalid glob pattern '{0}'")]
+pub struct InvalidGlobPatternError(pub(crate) String, #[source] pub(crate) glob::PatternError);
+
+#[derive(Error, Debug)]
+#[error("content of entry '{inner_path}' ({data_size} bytes at offset {data_offset}) lies outside of the package ({package_size} bytes)")]
+pub struct EntryOutOfBoundsError {
+    pub(crate) inner_path: String,
+    pub(crate) data_offset: u64,
+    pub(crate) data_size: u64,
+    pub(crate) package_size: u64,
+}
+
 #[derive(Error, Debug)]
 #[error(transparent)]
 pub struct PackageReadError(#[from] pub(crate) Box<dyn Error>);
@@ -29,4 +69,10 @@ impl From<InnerPathAlreadyExistsError> for PackageReadError {
     fn from(error: InnerPathAlreadyExistsError) -> Self {
         Self(Box::new(error))
     }
-}
\ No newline at end of file
+}
+
+impl From<EntryOutOfBoundsError> for PackageReadError {
+    fn from(error: EntryOutOfBoundsError) -> Self {
+        Self(Box::new(error))
+    }
+}
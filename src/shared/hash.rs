@@ -0,0 +1,124 @@
+use std::fmt::{Display, Formatter};
+use std::io::Write;
+use std::str::FromStr;
+
+use crate::error::InvalidContentHashError;
+
+/// Hash of an entry's content, as returned by [`PackageEntry::content_hash`](crate::PackageEntry::content_hash).
+///
+/// The algorithm is chosen at compile time: SHA-256 if the `sha256` feature is enabled, and CRC32
+/// otherwise. [`ContentHash::ALGORITHM`] names the algorithm in use.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ContentHash(Vec<u8>);
+
+impl ContentHash {
+    /// Name of the hash algorithm this crate was compiled with.
+    #[cfg(feature = "sha256")]
+    pub const ALGORITHM: &'static str = "sha256";
+    /// Name of the hash algorithm this crate was compiled with.
+    #[cfg(not(feature = "sha256"))]
+    pub const ALGORITHM: &'static str = "crc32";
+
+    /// Length of the hash in bytes.
+    #[cfg(feature = "sha256")]
+    const LENGTH: usize = 32;
+    #[cfg(not(feature = "sha256"))]
+    const LENGTH: usize = 4;
+
+    /// Calculates the hash of the specified content.
+    #[cfg(feature = "sha256")]
+    pub fn of(content: &[u8]) -> ContentHash {
+        use sha2::{Digest, Sha256};
+        ContentHash(Sha256::digest(content).to_vec())
+    }
+
+    /// Calculates the hash of the specified content.
+    #[cfg(not(feature = "sha256"))]
+    pub fn of(content: &[u8]) -> ContentHash {
+        ContentHash(crc32fast::hash(content).to_be_bytes().to_vec())
+    }
+
+    /// Returns the raw bytes of this hash.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Wraps raw hash bytes, as returned by [`ContentHash::as_bytes`].
+    ///
+    /// Returns `None` if the length does not match the algorithm in use.
+    pub(crate) fn from_bytes(bytes: Vec<u8>) -> Option<ContentHash> {
+        if bytes.len() == ContentHash::LENGTH {
+            Some(ContentHash(bytes))
+        } else {
+            None
+        }
+    }
+}
+
+/// Calculates a [`ContentHash`] of content written to it piece by piece, e.g. with
+/// [`std::io::copy`], so that large files don't have to be held in memory.
+pub(crate) struct ContentHasher {
+    #[cfg(feature = "sha256")]
+    hasher: sha2::Sha256,
+    #[cfg(not(feature = "sha256"))]
+    hasher: crc32fast::Hasher,
+}
+
+impl ContentHasher {
+    pub(crate) fn new() -> ContentHasher {
+        ContentHasher { hasher: Default::default() }
+    }
+
+    #[cfg(feature = "sha256")]
+    pub(crate) fn finish(self) -> ContentHash {
+        use sha2::Digest;
+        ContentHash(self.hasher.finalize().to_vec())
+    }
+
+    #[cfg(not(feature = "sha256"))]
+    pub(crate) fn finish(self) -> ContentHash {
+        ContentHash(self.hasher.finalize().to_be_bytes().to_vec())
+    }
+}
+
+impl Write for ContentHasher {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        #[cfg(feature = "sha256")]
+        sha2::Digest::update(&mut self.hasher, buf);
+        #[cfg(not(feature = "sha256"))]
+        self.hasher.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Formats the hash as lowercase hexadecimal digits.
+impl Display for ContentHash {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for byte in self.0.iter() {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a hash from hexadecimal digits, as produced by its [`Display`] implementation.
+impl FromStr for ContentHash {
+    type Err = InvalidContentHashError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != ContentHash::LENGTH * 2 || !s.is_ascii() {
+            return Err(InvalidContentHashError(s.to_string()));
+        }
+
+        (0..s.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&s[index..index + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map(ContentHash)
+            .map_err(|_| InvalidContentHashError(s.to_string()))
+    }
+}
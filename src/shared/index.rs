@@ -0,0 +1,254 @@
+use std::fmt::{Debug, Formatter};
+use std::fs::File;
+use std::path::Path;
+use std::rc::Rc;
+
+use memmap2::Mmap;
+
+use crate::dat::index::DatIndex;
+use crate::error::PackageReadError;
+use crate::pkg::index::PkgIndex;
+use crate::pkg::{calculate_path_hash, find_path_hash_collisions, PathHashCollision};
+use crate::shared::reader::{validate_index, IndexEntry};
+use crate::{Package, PackageEntry};
+
+/// A lightweight, read-only view of a package's index.
+///
+/// Unlike [`Package`], a [`PackageIndex`] does not read all entries up front; entry headers are
+/// only parsed when an entry is looked up. This makes it well suited for checking whether a few
+/// specific entries exist in a large package. For PKG packages, lookups use the inner path hashes
+/// stored in the package, and take roughly constant time.
+///
+/// When the package needs to be edited, the index can be converted into a [`Package`] with
+/// [`into_package`](PackageIndex::into_package).
+pub struct PackageIndex {
+    source: IndexSource,
+    format: IndexFormat,
+}
+
+enum IndexSource {
+    MemoryMappedFile(Rc<Mmap>),
+    ByteArray(Rc<[u8]>),
+}
+
+#[derive(Debug)]
+enum IndexFormat {
+    Dat(DatIndex),
+    Pkg(PkgIndex),
+}
+
+impl PackageIndex {
+    // region <Constructors>
+    /// Memory-maps the file at the specified path, and reads its index using DAT format.
+    pub fn from_path_dat<P: AsRef<Path>>(source_path: P) -> Result<PackageIndex, PackageReadError> {
+        PackageIndex::from_file_dat(File::open(source_path)?)
+    }
+
+    /// Memory-maps the specified file, and reads its index using DAT format.
+    pub fn from_file_dat(file: File) -> Result<PackageIndex, PackageReadError> {
+        PackageIndex::new(IndexSource::map(file)?, |bytes| Ok(IndexFormat::Dat(DatIndex::read(bytes)?)))
+    }
+
+    /// Reads the index of a DAT package held in memory.
+    pub fn from_bytes_dat(bytes: Vec<u8>) -> Result<PackageIndex, PackageReadError> {
+        PackageIndex::new(IndexSource::ByteArray(bytes.into()), |bytes| Ok(IndexFormat::Dat(DatIndex::read(bytes)?)))
+    }
+
+    /// Memory-maps the file at the specified path, and reads its index using PKG format.
+    pub fn from_path_pkg<P: AsRef<Path>>(source_path: P) -> Result<PackageIndex, PackageReadError> {
+        PackageIndex::from_file_pkg(File::open(source_path)?)
+    }
+
+    /// Memory-maps the specified file, and reads its index using PKG format.
+    pub fn from_file_pkg(file: File) -> Result<PackageIndex, PackageReadError> {
+        PackageIndex::new(IndexSource::map(file)?, |bytes| Ok(IndexFormat::Pkg(PkgIndex::read(bytes)?)))
+    }
+
+    /// Reads the index of a PKG package held in memory.
+    pub fn from_bytes_pkg(bytes: Vec<u8>) -> Result<PackageIndex, PackageReadError> {
+        PackageIndex::new(IndexSource::ByteArray(bytes.into()), |bytes| Ok(IndexFormat::Pkg(PkgIndex::read(bytes)?)))
+    }
+
+    fn new<F>(source: IndexSource, read_format: F) -> Result<PackageIndex, PackageReadError>
+        where F: FnOnce(&[u8]) -> Result<IndexFormat, PackageReadError> {
+        let format = read_format(source.bytes())?;
+        Ok(PackageIndex { source, format })
+    }
+    // endregion
+
+    /// Returns the number of entries in the package.
+    pub fn entry_count(&self) -> usize {
+        match &self.format {
+            IndexFormat::Dat(index) => index.entry_count(),
+            IndexFormat::Pkg(index) => index.entry_count(),
+        }
+    }
+
+    /// Parses the header of the entry at the specified position in the package.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `position` is not less than [`entry_count`](PackageIndex::entry_count).
+    pub fn entry_at(&self, position: usize) -> Result<IndexEntry, PackageReadError> {
+        assert!(position < self.entry_count(),
+                "position (is {}) should be < entry_count (is {})", position, self.entry_count());
+
+        let bytes = self.source.bytes();
+        let entry = match &self.format {
+            IndexFormat::Dat(index) => index.entry_at(bytes, position)?,
+            IndexFormat::Pkg(index) => index.entry_at(bytes, position)?,
+        };
+
+        validate_index(std::slice::from_ref(&entry), bytes.len() as u64)?;
+        Ok(entry)
+    }
+
+    /// Returns the position of the entry with the specified `inner_path`, or [`None`] if the
+    /// package does not contain such an entry.
+    pub fn position_of<S: AsRef<str>>(&self, inner_path: S) -> Result<Option<usize>, PackageReadError> {
+        let bytes = self.source.bytes();
+        match &self.format {
+            IndexFormat::Dat(index) => index.position_of(bytes, inner_path.as_ref()),
+            IndexFormat::Pkg(index) => index.position_of(bytes, inner_path.as_ref()),
+        }
+    }
+
+    /// Parses the header of the entry with the specified `inner_path`, or returns [`None`] if the
+    /// package does not contain such an entry.
+    pub fn find<S: AsRef<str>>(&self, inner_path: S) -> Result<Option<IndexEntry>, PackageReadError> {
+        self.position_of(inner_path)?
+            .map(|position| self.entry_at(position))
+            .transpose()
+    }
+
+    /// Checks whether the package contains an entry with the specified `inner_path`.
+    pub fn entry_exists<S: AsRef<str>>(&self, inner_path: S) -> Result<bool, PackageReadError> {
+        Ok(self.position_of(inner_path)?.is_some())
+    }
+
+    /// Returns the [`PackageEntry`] with the specified `inner_path`, or [`None`] if the package
+    /// does not contain such an entry.
+    ///
+    /// The returned entry reads its content lazily from the same memory map or buffer as this index.
+    pub fn entry_by_path<S: AsRef<str>>(&self, inner_path: S) -> Result<Option<PackageEntry>, PackageReadError> {
+        Ok(self.find(inner_path)?.map(|entry| self.source.to_package_entry(entry)))
+    }
+
+    /// Returns the content of the entry with the specified `inner_path`, or [`None`] if the package
+    /// does not contain such an entry.
+    pub fn content_by_path<S: AsRef<str>>(&self, inner_path: S) -> Result<Option<Vec<u8>>, PackageReadError> {
+        Ok(self.find(inner_path)?.map(|entry| {
+            let start = entry.data_offset() as usize;
+            self.source.bytes()[start..start + entry.data_size() as usize].to_vec()
+        }))
+    }
+
+    /// Returns an iterator that parses the headers of all entries, in the order in which the
+    /// package stores them.
+    pub fn iter(&self) -> impl Iterator<Item=Result<IndexEntry, PackageReadError>> + '_ {
+        (0..self.entry_count()).map(|position| self.entry_at(position))
+    }
+
+    /// Parses the headers of all entries whose `inner_path` has the specified PKG path hash, see
+    /// [`calculate_path_hash`], in the order in which they are stored.
+    ///
+    /// For PKG packages, this uses the hashes stored in the package, and only parses the headers
+    /// of matching entries. DAT packages store no hashes, so all entry headers are parsed.
+    pub fn entries_by_path_hash(&self, path_hash: u32) -> Result<Vec<IndexEntry>, PackageReadError> {
+        match &self.format {
+            IndexFormat::Dat(_) => {
+                let mut result = Vec::new();
+                for entry in self.iter() {
+                    let entry = entry?;
+                    if calculate_path_hash(entry.inner_path()) == path_hash {
+                        result.push(entry);
+                    }
+                }
+
+                Ok(result)
+            }
+            IndexFormat::Pkg(index) => {
+                index.positions_by_path_hash(path_hash)
+                    .map(|position| self.entry_at(position))
+                    .collect()
+            }
+        }
+    }
+
+    /// Returns every group of entries whose `inner_path`s share the same PKG path hash, ordered
+    /// by hash.
+    pub fn path_hash_collisions(&self) -> Result<Vec<PathHashCollision>, PackageReadError> {
+        match &self.format {
+            IndexFormat::Dat(_) => {
+                let index = self.iter().collect::<Result<Vec<IndexEntry>, PackageReadError>>()?;
+                Ok(find_path_hash_collisions(index.iter().map(|entry| entry.inner_path())))
+            }
+            IndexFormat::Pkg(index) => {
+                let mut result = Vec::new();
+                for (path_hash, positions) in index.path_hash_collisions() {
+                    let inner_paths = positions.into_iter()
+                        .map(|position| Ok(self.entry_at(position)?.inner_path().to_string()))
+                        .collect::<Result<Vec<String>, PackageReadError>>()?;
+                    result.push(PathHashCollision::new(path_hash, inner_paths));
+                }
+
+                Ok(result)
+            }
+        }
+    }
+
+    /// Converts this index into a [`Package`], which can be edited.
+    ///
+    /// Entries of the resulting package keep reading their content from the same memory map or
+    /// buffer as this index.
+    pub fn into_package(self) -> Result<Package, PackageReadError> {
+        let mut result = Package::with_capacity(self.entry_count());
+        for entry in self.iter() {
+            result.add_entry(self.source.to_package_entry(entry?))?;
+        }
+
+        Ok(result)
+    }
+}
+
+impl Debug for PackageIndex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PackageIndex")
+            .field("format", &self.format)
+            .finish_non_exhaustive()
+    }
+}
+
+impl IndexSource {
+    fn map(file: File) -> Result<IndexSource, PackageReadError> {
+        let mmap = unsafe {
+            Mmap::map(&file)
+        }?;
+
+        Ok(IndexSource::MemoryMappedFile(Rc::new(mmap)))
+    }
+
+    fn bytes(&self) -> &[u8] {
+        match self {
+            IndexSource::MemoryMappedFile(mmap) => &mmap[..],
+            IndexSource::ByteArray(bytes) => bytes,
+        }
+    }
+
+    fn to_package_entry(&self, entry: IndexEntry) -> PackageEntry {
+        match self {
+            IndexSource::MemoryMappedFile(mmap) => PackageEntry::from_memory_mapped_file(
+                entry.inner_path(),
+                mmap.clone(),
+                entry.data_offset(),
+                entry.data_size(),
+            ),
+            IndexSource::ByteArray(bytes) => PackageEntry::from_shared_byte_array(
+                entry.inner_path(),
+                bytes.clone(),
+                entry.data_offset(),
+                entry.data_size(),
+            ),
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use crate::error::MergeConflictError;
+use crate::Package;
+
+/// Describes how [`Package::merge`] resolves entries whose `inner_path` exists in both packages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Entries from the merged package replace existing entries, which keep their position.
+    Overwrite,
+    /// Existing entries are kept, and conflicting entries from the merged package are skipped.
+    KeepExisting,
+    /// The merge fails without changing the package if any `inner_path` exists in both packages.
+    ErrorOnConflict,
+}
+
+/// Lists `inner_path`s affected by [`Package::merge`], in the merged package's order of entries.
+#[derive(Debug, Default)]
+pub struct MergeReport {
+    added: Vec<String>,
+    overwritten: Vec<String>,
+    skipped: Vec<String>,
+}
+
+impl MergeReport {
+    /// Returns paths of entries that did not exist in the package before the merge.
+    pub fn added(&self) -> &[String] {
+        &self.added
+    }
+
+    /// Returns paths of existing entries that were replaced by the merge.
+    pub fn overwritten(&self) -> &[String] {
+        &self.overwritten
+    }
+
+    /// Returns paths of entries from the merged package that were discarded, because the package
+    /// already contained an entry under the same path.
+    pub fn skipped(&self) -> &[String] {
+        &self.skipped
+    }
+}
+
+impl Package {
+    /// Merges all entries of the `other` [Package] into this one, layering them over the existing
+    /// entries according to the specified [MergePolicy].
+    ///
+    /// New entries are appended in the `other` package's order. Entries are moved along with their
+    /// content sources, so no content is read or copied.
+    ///
+    /// Returns a [MergeReport] describing the outcome, or a [MergeConflictError] listing all
+    /// conflicting paths if the policy is [MergePolicy::ErrorOnConflict] and any conflict exists.
+    pub fn merge(&mut self, other: Package, policy: MergePolicy) -> Result<MergeReport, MergeConflictError> {
+        if policy == MergePolicy::ErrorOnConflict {
+            let conflicts: Vec<String> = other.iter()
+                .map(|entry| entry.inner_path())
+                .filter(|inner_path| self.entry_exists(inner_path))
+                .map(|inner_path| inner_path.to_string())
+                .collect();
+
+            if !conflicts.is_empty() {
+                return Err(MergeConflictError(conflicts));
+            }
+        }
+
+        let mut report = MergeReport::default();
+        for entry in other {
+            let inner_path = entry.inner_path().to_string();
+            if !self.entry_exists(&inner_path) {
+                self.put_entry(entry);
+                report.added.push(inner_path);
+            } else if policy == MergePolicy::Overwrite {
+                self.put_entry(entry);
+                report.overwritten.push(inner_path);
+            } else {
+                report.skipped.push(inner_path);
+            }
+        }
+
+        Ok(report)
+    }
+}
@@ -3,3 +3,9 @@ pub(crate) mod package;
 pub(crate) mod entry;
 pub(crate) mod writer;
 pub(crate) mod reader;
+pub(crate) mod tree;
+pub(crate) mod merge;
+pub(crate) mod diff;
+pub(crate) mod hash;
+pub(crate) mod index;
+pub(crate) mod binary;
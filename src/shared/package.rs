@@ -1,15 +1,22 @@
-use std::collections::BTreeMap;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt::{Display, Formatter};
 use std::fs::File;
-use std::io::{BufWriter, Seek, Write};
+use std::io::{BufWriter, Cursor, Read, Seek, Write};
+use std::ops::Bound;
 use std::path::Path;
 use std::slice::Iter;
 
+use glob::{MatchOptions, Pattern};
+
 use crate::{PackageReader, PackageWriter};
-use crate::dat::{DatReader, DatWriter};
-use crate::error::{InnerPathAlreadyExistsError, PackageReadError, PackageWriteError};
-use crate::pkg::{PkgReader, PkgWriter};
+use crate::dat::{salvage, DatReader, DatWriter, SalvageReport};
+use crate::error::{InnerPathAlreadyExistsError, InnerPathNotFoundError, InvalidGlobPatternError, PackageReadError, PackageWriteError, RenameError};
+use crate::pkg::{calculate_path_hash, find_path_hash_collisions, PathHashCollision, PkgReadWarning, PkgReader, PkgWriter};
 use crate::shared::entry::PackageEntry;
+use crate::shared::reader::{map_file, package_from_bytes, package_from_mmap, FileReadStrategy, ReadStrategy};
+use crate::shared::tree::DirectoryNode;
+use crate::shared::writer::write_via_temporary_file;
 
 /// Represents the internal structure of a package.
 ///
@@ -25,6 +32,7 @@ pub struct Package {
 impl Package {
     // region <Constructors>
     /// Creates a new empty [`Package`].
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Package {
         Package {
             entries: Vec::new(),
@@ -60,6 +68,21 @@ impl Package {
         Package::from_file(file, DatReader())
     }
 
+    /// Reads a package held in memory using DAT format, and creates a [`Package`] instance.
+    ///
+    /// The buffer is shared by all entries read from it, and lives as long as the longest-lived
+    /// of them.
+    pub fn from_bytes_dat(bytes: Vec<u8>) -> Result<Package, PackageReadError> {
+        Package::from_bytes(bytes, DatReader())
+    }
+
+    /// Reads the specified input using DAT format, and creates a [`Package`] instance.
+    ///
+    /// Content of entries is accessed according to the specified [`ReadStrategy`].
+    pub fn from_input_dat<I: Read + Seek + 'static>(input: I, strategy: ReadStrategy) -> Result<Package, PackageReadError> {
+        Package::from_input(input, DatReader(), strategy)
+    }
+
     /// Reads the file at the specified path using PKG format, and creates a [`Package`] instance.
     ///
     /// This function memory-maps the file, whose lifetime is as long as the longest-lived entry
@@ -67,7 +90,7 @@ impl Package {
     /// If the [`Package`] instance created by this function goes out of scope, and its entries are
     /// not referenced anywhere, the memory map will be correctly disposed.
     pub fn from_path_pkg<P: AsRef<Path>>(source_path: P) -> Result<Package, PackageReadError> {
-        Package::from_path(source_path, PkgReader())
+        Package::from_path(source_path, PkgReader::new())
     }
 
     /// Reads the specified file using PKG format, and creates a [`Package`] instance.
@@ -77,7 +100,56 @@ impl Package {
     /// If the [`Package`] instance created by this function goes out of scope, and its entries are
     /// not referenced anywhere, the memory map will be correctly disposed.
     pub fn from_file_pkg(file: File) -> Result<Package, PackageReadError> {
-        Package::from_file(file, PkgReader())
+        Package::from_file(file, PkgReader::new())
+    }
+
+    /// Reads a package held in memory using PKG format, and creates a [`Package`] instance.
+    ///
+    /// The buffer is shared by all entries read from it, and lives as long as the longest-lived
+    /// of them.
+    pub fn from_bytes_pkg(bytes: Vec<u8>) -> Result<Package, PackageReadError> {
+        Package::from_bytes(bytes, PkgReader::new())
+    }
+
+    /// Reads the specified input using PKG format, and creates a [`Package`] instance.
+    ///
+    /// Content of entries is accessed according to the specified [`ReadStrategy`].
+    pub fn from_input_pkg<I: Read + Seek + 'static>(input: I, strategy: ReadStrategy) -> Result<Package, PackageReadError> {
+        Package::from_input(input, PkgReader::new(), strategy)
+    }
+
+    /// Reads the file at the specified path using a [lenient](PkgReader::lenient) PKG reader, and
+    /// creates a [`Package`] instance, along with the warnings recorded while reading it.
+    pub fn from_path_pkg_lenient<P: AsRef<Path>>(source_path: P) -> Result<(Package, Vec<PkgReadWarning>), PackageReadError> {
+        let mmap = map_file(&File::open(source_path)?)?;
+        let (index, warnings) = PkgReader::lenient().read_index_with_warnings(&mut Cursor::new(&mmap[..]))?;
+        Ok((package_from_mmap(index, mmap)?, warnings))
+    }
+
+    /// Reads a package held in memory using a [lenient](PkgReader::lenient) PKG reader, and
+    /// creates a [`Package`] instance, along with the warnings recorded while reading it.
+    pub fn from_bytes_pkg_lenient(bytes: Vec<u8>) -> Result<(Package, Vec<PkgReadWarning>), PackageReadError> {
+        let (index, warnings) = PkgReader::lenient().read_index_with_warnings(&mut Cursor::new(&bytes[..]))?;
+        Ok((package_from_bytes(index, bytes)?, warnings))
+    }
+
+    /// Recovers as many entries as possible from the DAT file at the specified path, which may be
+    /// truncated or have a damaged offset table, see [`Package::salvage_bytes_dat`].
+    ///
+    /// The file is read into memory, so that it can't change while its entries are in use.
+    pub fn salvage_path_dat<P: AsRef<Path>>(source_path: P) -> Result<(Package, SalvageReport), PackageReadError> {
+        Ok(Package::salvage_bytes_dat(std::fs::read(source_path)?))
+    }
+
+    /// Recovers as many entries as possible from a DAT package held in memory, which may be
+    /// truncated or have a damaged offset table.
+    ///
+    /// Entries are first looked up through the offset table, as far as it points to plausible
+    /// entries. The rest of the package is then scanned for plausible entry headers. Entries whose
+    /// content is incomplete can't be recovered. The returned [`SalvageReport`] lists the byte
+    /// ranges that could not be attributed to any recovered entry.
+    pub fn salvage_bytes_dat(bytes: Vec<u8>) -> (Package, SalvageReport) {
+        salvage(bytes)
     }
 
     /// Reads the file at the specified path using format provided by the specified [`PackageReader`],
@@ -94,6 +166,41 @@ impl Package {
     pub fn from_file<T: PackageReader>(file: File, reader: T) -> Result<Package, PackageReadError> {
         reader.read_package_from_file(file)
     }
+
+    /// Reads the file at the specified path using format provided by the specified [`PackageReader`],
+    /// and creates a [`Package`] instance.
+    ///
+    /// Content of entries is accessed according to the specified [`FileReadStrategy`]. Use
+    /// [`FileReadStrategy::PositionedRead`] or [`FileReadStrategy::Load`] if the file may be
+    /// modified by other processes while the package is in use.
+    pub fn from_path_with_strategy<P: AsRef<Path>, T: PackageReader>(source_path: P, reader: T, strategy: FileReadStrategy) -> Result<Package, PackageReadError> {
+        let file = File::options()
+            .read(true)
+            .open(source_path)?;
+        Package::from_file_with_strategy(file, reader, strategy)
+    }
+
+    /// Reads the specified file using format provided by the specified [`PackageReader`], and creates
+    /// a [`Package`] instance.
+    ///
+    /// Content of entries is accessed according to the specified [`FileReadStrategy`].
+    pub fn from_file_with_strategy<T: PackageReader>(file: File, reader: T, strategy: FileReadStrategy) -> Result<Package, PackageReadError> {
+        reader.read_package_from_file_with_strategy(file, strategy)
+    }
+
+    /// Reads a package held in memory using format provided by the specified [`PackageReader`],
+    /// and creates a [`Package`] instance.
+    pub fn from_bytes<T: PackageReader>(bytes: Vec<u8>, reader: T) -> Result<Package, PackageReadError> {
+        reader.read_package_from_bytes(bytes)
+    }
+
+    /// Reads the specified input using format provided by the specified [`PackageReader`], and
+    /// creates a [`Package`] instance.
+    ///
+    /// Content of entries is accessed according to the specified [`ReadStrategy`].
+    pub fn from_input<I: Read + Seek + 'static, T: PackageReader>(input: I, reader: T, strategy: ReadStrategy) -> Result<Package, PackageReadError> {
+        reader.read_package_from_input(input, strategy)
+    }
     // endregion
 
     // region <Output>
@@ -152,7 +259,7 @@ impl Package {
     ///
     /// For a non-consuming variant, see [Package::to_path_pkg] instead.
     pub fn into_path_pkg<P: AsRef<Path>>(self, destination_path: P) -> Result<(), PackageWriteError> {
-        self.into_path(destination_path, PkgWriter())
+        self.into_path(destination_path, PkgWriter::new())
     }
 
     /// Consumes and writes this [`Package`] in PKG format to the specified output.
@@ -163,7 +270,7 @@ impl Package {
     ///
     /// For a non-consuming variant, see [Package::to_output_pkg] instead.
     pub fn into_output_pkg<O: Write + Seek>(self, output: O) -> Result<(), PackageWriteError> {
-        self.into_output(output, PkgWriter())
+        self.into_output(output, PkgWriter::new())
     }
 
     /// Writes this [Package] in PKG format to file at the specified path.
@@ -174,7 +281,7 @@ impl Package {
     ///
     /// If this is what you want to do, use [Package::into_path_pkg] instead.
     pub fn to_path_pkg<P: AsRef<Path>>(&self, destination_path: P) -> Result<(), PackageWriteError> {
-        self.to_path(destination_path, PkgWriter())
+        self.to_path(destination_path, PkgWriter::new())
     }
 
     /// Writes this [Package] in PKG format to the specified output.
@@ -185,7 +292,7 @@ impl Package {
     ///
     /// If this is what you want to do, use [Package::into_output_pkg] instead.
     pub fn to_output_pkg<O: Write + Seek>(&self, output: O) -> Result<(), PackageWriteError> {
-        self.to_output(output, PkgWriter())
+        self.to_output(output, PkgWriter::new())
     }
     // endregion
 
@@ -198,21 +305,18 @@ impl Package {
     ///
     /// For a non-consuming variant, see [Package::to_output] instead.
     pub fn into_path<P: AsRef<Path>, T: PackageWriter>(self, destination_path: P, writer: T) -> Result<(), PackageWriteError> {
-        let destination_path = destination_path.as_ref();
-        let destination_path_tmp = destination_path.with_extension("tmp");
-
-        let file = File::options()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(&destination_path_tmp)?;
-
-        self.into_output(BufWriter::new(file), writer)?;
-
-        std::fs::remove_file(destination_path)?;
-        std::fs::rename(destination_path_tmp, destination_path)?;
+        write_via_temporary_file(destination_path.as_ref(), |temporary_path| {
+            let file = File::options()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(temporary_path)?;
 
-        Ok(())
+            let mut output = BufWriter::new(file);
+            self.into_output(&mut output, writer)?;
+            output.flush()?;
+            Ok(())
+        })
     }
 
     /// Consumes and writes this [Package] using format provided by the specified [PackageWriter],
@@ -242,7 +346,10 @@ impl Package {
             .truncate(true)
             .open(destination_path)?;
 
-        self.to_output(BufWriter::new(file), writer)
+        let mut output = BufWriter::new(file);
+        self.to_output(&mut output, writer)?;
+        output.flush()?;
+        Ok(())
     }
 
     /// Writes this [Package] using format provided by the specified [PackageWriter], to the
@@ -278,9 +385,7 @@ impl Package {
         let maybe_index = self.inner_path_to_entry_index.get(inner_path);
         match maybe_index {
             Some(index) => {
-                let index = *index;
-                self.entries.remove(index);
-                self.entries.insert(index, entry);
+                self.entries[*index] = entry;
             }
             None => {
                 self.append_new_entry(entry);
@@ -288,6 +393,20 @@ impl Package {
         }
     }
 
+    /// Retrieves the [entry](PackageEntry) under the `inner_path` in this [Package].
+    ///
+    /// Returns `None` if the `inner_path` doesn't have any entry associated with it.
+    pub fn entry_by_path<S: AsRef<str>>(&self, inner_path: S) -> Option<&PackageEntry> {
+        self.inner_path_to_entry_index.get(inner_path.as_ref())
+            .map(|index| &self.entries[*index])
+    }
+
+    /// Retrieves the [entry](PackageEntry) at the specified `index`, in the order in which this
+    /// [Package] stores its entries.
+    pub fn entry_at(&self, index: usize) -> Option<&PackageEntry> {
+        self.entries.get(index)
+    }
+
     /// Retrieves content under the `inner_path` in this [Package].
     ///
     /// Returns a copy of the content if found, or `None` if the `inner_path` doesn't
@@ -308,21 +427,238 @@ impl Package {
     /// Returns `true` if the entry was removed, `false` if no entry was found under the
     /// specified path.
     pub fn remove_entry<S: AsRef<str>>(&mut self, inner_path: S) -> bool {
-        let maybe_index = self.inner_path_to_entry_index.get(inner_path.as_ref());
+        let maybe_index = self.inner_path_to_entry_index.remove(inner_path.as_ref());
         match maybe_index {
             Some(index) => {
-                self.entries.remove(*index);
+                self.entries.remove(index);
+                // Entries following the removed one have shifted back by one
+                for entry_index in self.inner_path_to_entry_index.values_mut() {
+                    if *entry_index > index {
+                        *entry_index -= 1;
+                    }
+                }
                 true
             }
             None => false
         }
     }
 
+    /// Renames the entry under `old_inner_path` to `new_inner_path`.
+    ///
+    /// The entry retains its content source and its position within this [Package].
+    ///
+    /// Returns an [InnerPathNotFoundError] if there is no entry under `old_inner_path`, or an
+    /// [InnerPathAlreadyExistsError] if another entry is already stored under `new_inner_path`.
+    pub fn rename_entry<S: AsRef<str>, T: AsRef<str>>(&mut self, old_inner_path: S, new_inner_path: T) -> Result<(), RenameError> {
+        let old_inner_path = old_inner_path.as_ref();
+        let new_inner_path = new_inner_path.as_ref();
+        let index = match self.inner_path_to_entry_index.get(old_inner_path) {
+            Some(index) => *index,
+            None => return Err(InnerPathNotFoundError(old_inner_path.to_string()).into()),
+        };
+        if old_inner_path == new_inner_path {
+            return Ok(());
+        }
+        if self.inner_path_to_entry_index.contains_key(new_inner_path) {
+            return Err(InnerPathAlreadyExistsError(new_inner_path.to_string()).into());
+        }
+
+        self.inner_path_to_entry_index.remove(old_inner_path);
+        self.inner_path_to_entry_index.insert(new_inner_path.to_string(), index);
+        self.entries[index].set_inner_path(new_inner_path.to_string());
+        Ok(())
+    }
+
+    /// Moves all entries located under the `old_directory` to the `new_directory`, recursively.
+    /// Either directory may be given with or without a trailing `/`; an empty string denotes the
+    /// root of the package.
+    ///
+    /// Entries retain their content sources and their positions within this [Package].
+    /// Either all entries are moved, or none are.
+    ///
+    /// Returns the number of moved entries, an [InnerPathNotFoundError] if there are no entries
+    /// under `old_directory`, or an [InnerPathAlreadyExistsError] if any of the new paths is
+    /// already taken by an entry that is not being moved.
+    pub fn rename_directory<S: AsRef<str>, T: AsRef<str>>(&mut self, old_directory: S, new_directory: T) -> Result<usize, RenameError> {
+        let old_prefix = directory_prefix(old_directory.as_ref());
+        let new_prefix = directory_prefix(new_directory.as_ref());
+
+        let renames: Vec<(String, String, usize)> = self.inner_path_to_entry_index
+            .range::<str, _>((Bound::Included(old_prefix.as_str()), Bound::Unbounded))
+            .take_while(|(inner_path, _)| inner_path.starts_with(&old_prefix))
+            .map(|(inner_path, index)| {
+                let new_inner_path = format!("{}{}", new_prefix, &inner_path[old_prefix.len()..]);
+                (inner_path.clone(), new_inner_path, *index)
+            })
+            .collect();
+
+        if renames.is_empty() {
+            return Err(InnerPathNotFoundError(old_prefix).into());
+        }
+        if old_prefix == new_prefix {
+            return Ok(renames.len());
+        }
+
+        // A new path only conflicts if it's taken by an entry which is not being moved itself
+        for (_, new_inner_path, _) in renames.iter() {
+            if self.inner_path_to_entry_index.contains_key(new_inner_path)
+                && !new_inner_path.starts_with(&old_prefix) {
+                return Err(InnerPathAlreadyExistsError(new_inner_path.clone()).into());
+            }
+        }
+
+        for (old_inner_path, _, _) in renames.iter() {
+            self.inner_path_to_entry_index.remove(old_inner_path);
+        }
+        for (_, new_inner_path, index) in renames.iter() {
+            self.inner_path_to_entry_index.insert(new_inner_path.clone(), *index);
+            self.entries[*index].set_inner_path(new_inner_path.clone());
+        }
+
+        Ok(renames.len())
+    }
+
+    // region <Bulk removal>
+    /// Retains only the entries for which the specified `predicate` returns `true`, removing all
+    /// others in a single pass. The order of retained entries is preserved.
+    pub fn retain<F: FnMut(&PackageEntry) -> bool>(&mut self, mut predicate: F) {
+        self.partition_entries(|entry| !predicate(entry));
+    }
+
+    /// Removes all entries for which the specified `predicate` returns `true` in a single pass,
+    /// and returns them as a new [Package].
+    ///
+    /// Both packages preserve the relative order of their entries, and moved entries retain their
+    /// content sources.
+    pub fn drain_filter<F: FnMut(&PackageEntry) -> bool>(&mut self, predicate: F) -> Package {
+        let removed_entries = self.partition_entries(predicate);
+
+        let mut result = Package::with_capacity(removed_entries.len());
+        for entry in removed_entries {
+            result.append_new_entry(entry);
+        }
+        result
+    }
+
+    /// Removes the entries under all of the specified `inner_paths` in a single pass.
+    /// Paths which have no entry associated with them are ignored.
+    ///
+    /// Returns the number of removed entries.
+    pub fn remove_entries<I, S>(&mut self, inner_paths: I) -> usize
+        where I: IntoIterator<Item=S>, S: AsRef<str> {
+        let inner_paths: HashSet<String> = inner_paths.into_iter()
+            .map(|inner_path| inner_path.as_ref().to_string())
+            .collect();
+
+        self.partition_entries(|entry| inner_paths.contains(entry.inner_path()))
+            .len()
+    }
+
+    /// Removes entries matching the `predicate` from this [Package], rebuilding the path index for
+    /// the remaining entries, and returns the removed entries in their original order.
+    fn partition_entries<F: FnMut(&PackageEntry) -> bool>(&mut self, mut predicate: F) -> Vec<PackageEntry> {
+        let entries = std::mem::take(&mut self.entries);
+        self.inner_path_to_entry_index.clear();
+
+        let mut removed_entries = Vec::new();
+        for entry in entries {
+            if predicate(&entry) {
+                removed_entries.push(entry);
+            } else {
+                self.append_new_entry(entry);
+            }
+        }
+        removed_entries
+    }
+    // endregion
+
+    // region <Ordering>
+    /// Returns the position of the entry under the specified `inner_path` within this [Package]'s
+    /// order of entries, or `None` if no entry was found under the specified path.
+    pub fn index_of<S: AsRef<str>>(&self, inner_path: S) -> Option<usize> {
+        self.inner_path_to_entry_index.get(inner_path.as_ref()).copied()
+    }
+
+    /// Inserts the specified entry into this [Package] at position `index`, shifting all entries
+    /// after it to the right.
+    /// Returns an [InnerPathAlreadyExistsError] if this [Package] already contains an entry under
+    /// the specified entry's `inner_path`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > entry_count()`.
+    pub fn insert_entry(&mut self, index: usize, entry: PackageEntry) -> Result<(), InnerPathAlreadyExistsError> {
+        let inner_path = entry.inner_path().to_string();
+        if self.inner_path_to_entry_index.contains_key(&inner_path) {
+            return Err(InnerPathAlreadyExistsError(inner_path));
+        }
+
+        self.entries.insert(index, entry);
+        self.inner_path_to_entry_index.insert(inner_path, index);
+        self.reindex_range(index + 1, self.entries.len());
+        Ok(())
+    }
+
+    /// Moves the entry under the specified `inner_path` to position `index`, shifting the entries
+    /// in between accordingly.
+    /// Returns an [InnerPathNotFoundError] if no entry was found under the specified path.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index >= entry_count()`.
+    pub fn move_entry<S: AsRef<str>>(&mut self, inner_path: S, index: usize) -> Result<(), InnerPathNotFoundError> {
+        let inner_path = inner_path.as_ref();
+        let current_index = self.index_of(inner_path)
+            .ok_or_else(|| InnerPathNotFoundError(inner_path.to_string()))?;
+        assert!(index < self.entries.len(), "index {} is out of bounds", index);
+
+        if current_index < index {
+            self.entries[current_index..=index].rotate_left(1);
+            self.reindex_range(current_index, index + 1);
+        } else {
+            self.entries[index..=current_index].rotate_right(1);
+            self.reindex_range(index, current_index + 1);
+        }
+        Ok(())
+    }
+
+    /// Sorts entries of this [Package] lexicographically by their `inner_path`.
+    pub fn sort_by_inner_path(&mut self) {
+        self.sort_by(|a, b| a.inner_path().cmp(b.inner_path()));
+    }
+
+    /// Sorts entries of this [Package] by the extension of their `inner_path`, and then by the
+    /// `inner_path` itself. Entries without an extension come first.
+    pub fn sort_by_extension(&mut self) {
+        self.sort_by(|a, b| {
+            extension(a.inner_path()).cmp(extension(b.inner_path()))
+                .then_with(|| a.inner_path().cmp(b.inner_path()))
+        });
+    }
+
+    /// Sorts entries of this [Package] with the specified comparator function.
+    ///
+    /// The sort is stable, ie. entries which compare as equal retain their relative order.
+    pub fn sort_by<F: FnMut(&PackageEntry, &PackageEntry) -> Ordering>(&mut self, compare: F) {
+        self.entries.sort_by(compare);
+        self.reindex_range(0, self.entries.len());
+    }
+
+    /// Updates the path index for entries within the specified range of positions.
+    fn reindex_range(&mut self, from: usize, to: usize) {
+        for index in from..to {
+            let inner_path = self.entries[index].inner_path();
+            *self.inner_path_to_entry_index.get_mut(inner_path)
+                .expect("Entry is missing from the path index") = index;
+        }
+    }
+    // endregion
+
     /// Checks if this [Package] has any entry associated with the given `inner_path`.
     ///
     /// Returns `true` if an entry is found, `false` otherwise.
     pub fn entry_exists<S: AsRef<str>>(&self, inner_path: S) -> bool {
-        self.inner_path_to_entry_index.get(inner_path.as_ref()).is_some()
+        self.inner_path_to_entry_index.contains_key(inner_path.as_ref())
     }
 
     /// Removes all entries from this [Package].
@@ -355,6 +691,88 @@ impl Package {
         self.entries.iter()
     }
 
+    // region <Queries>
+    /// Returns an iterator over [entries](PackageEntry) whose `inner_path` starts with the
+    /// specified `prefix`, in lexicographical order of their `inner_path`s.
+    ///
+    /// The lookup is a range scan over the sorted path index, so only matching entries are visited.
+    pub fn entries_with_prefix<S: AsRef<str>>(&self, prefix: S) -> impl Iterator<Item=&PackageEntry> {
+        let prefix = prefix.as_ref().to_string();
+        self.inner_path_to_entry_index
+            .range::<str, _>((Bound::Included(prefix.as_str()), Bound::Unbounded))
+            .take_while(move |(inner_path, _)| inner_path.starts_with(&prefix))
+            .map(|(_, index)| &self.entries[*index])
+    }
+
+    /// Returns an iterator over [entries](PackageEntry) located directly in the specified
+    /// `directory`, without descending into its subdirectories, in lexicographical order of their
+    /// `inner_path`s.
+    ///
+    /// The `directory` may be given with or without a trailing `/`. An empty string denotes the
+    /// root of the package.
+    pub fn entries_in_directory<S: AsRef<str>>(&self, directory: S) -> impl Iterator<Item=&PackageEntry> {
+        let prefix = directory_prefix(directory.as_ref());
+        let prefix_length = prefix.len();
+        self.entries_with_prefix(prefix)
+            .filter(move |entry| !entry.inner_path()[prefix_length..].contains('/'))
+    }
+
+    /// Returns an iterator over [entries](PackageEntry) whose `inner_path` matches the specified
+    /// glob `pattern`, in lexicographical order of their `inner_path`s.
+    ///
+    /// `?` and `*` match within a single path segment, `**` matches any number of segments,
+    /// and `[...]` matches a character class. Matching is case-sensitive.
+    ///
+    /// Returns an [InvalidGlobPatternError] if the `pattern` is malformed.
+    pub fn entries_matching_glob<S: AsRef<str>>(&self, pattern: S) -> Result<impl Iterator<Item=&PackageEntry>, InvalidGlobPatternError> {
+        let pattern = pattern.as_ref();
+        let compiled_pattern = Pattern::new(pattern)
+            .map_err(|error| InvalidGlobPatternError(pattern.to_string(), error))?;
+
+        // Only entries sharing the pattern's literal prefix can match, so narrow the scan down to them.
+        let literal_prefix = glob_literal_prefix(pattern).to_string();
+        Ok(self.entries_with_prefix(literal_prefix)
+            .filter(move |entry| compiled_pattern.matches_with(entry.inner_path(), GLOB_MATCH_OPTIONS)))
+    }
+    // endregion
+
+    // region <Path hashes>
+    /// Returns an iterator over [entries](PackageEntry) whose `inner_path` has the specified PKG
+    /// path hash, see [`calculate_path_hash`], in the order in which they are stored.
+    pub fn entries_by_path_hash(&self, path_hash: u32) -> impl Iterator<Item=&PackageEntry> {
+        self.entries.iter()
+            .filter(move |entry| calculate_path_hash(entry.inner_path()) == path_hash)
+    }
+
+    /// Returns every group of [entries](PackageEntry) whose `inner_path`s share the same PKG path
+    /// hash, ordered by hash.
+    pub fn path_hash_collisions(&self) -> Vec<PathHashCollision> {
+        find_path_hash_collisions(self.entries.iter().map(|entry| entry.inner_path()))
+    }
+    // endregion
+
+    // region <Directory tree>
+    /// Returns a virtual directory tree of all [entries](PackageEntry) in this [Package], derived
+    /// from their `inner_path`s.
+    pub fn directory_tree(&self) -> DirectoryNode<'_> {
+        self.directory("").unwrap_or_else(|| DirectoryNode::build("", std::iter::empty()))
+    }
+
+    /// Returns a virtual directory tree rooted at the specified `directory`.
+    ///
+    /// The `directory` may be given with or without a trailing `/`. An empty string denotes the
+    /// root of the package.
+    ///
+    /// Returns `None` if there are no entries under the specified `directory`.
+    pub fn directory<S: AsRef<str>>(&self, directory: S) -> Option<DirectoryNode<'_>> {
+        let prefix = directory_prefix(directory.as_ref());
+        let mut entries = self.entries_with_prefix(&prefix).peekable();
+        entries.peek()?;
+
+        Some(DirectoryNode::build(&prefix, entries))
+    }
+    // endregion
+
     /// Extracts all [entries](PackageEntry) in this [Package] into the specified directory.
     /// The complete directory structure will be created if it doesn't exist yet.
     pub fn extract<P: AsRef<Path>>(&self, destination_path: P) -> Result<(), std::io::Error> {
@@ -363,24 +781,60 @@ impl Package {
         for entry in self.iter() {
             let entry_dest_path = destination_path.join(entry.inner_path());
 
-            std::fs::create_dir_all(&entry_dest_path.parent().unwrap())?;
+            std::fs::create_dir_all(entry_dest_path.parent().unwrap())?;
             let mut file = File::options()
                 .write(true)
                 .create(true)
                 .truncate(true)
                 .open(&entry_dest_path)?;
 
-            let result = file.write(entry.content()?.as_ref());
-
-            if let Err(error) = result {
-                return Err(error);
-            }
+            file.write_all(entry.content()?.as_ref())?;
         }
 
         Ok(())
     }
 }
 
+const GLOB_MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Returns the part of the glob `pattern` preceding its first special character.
+fn glob_literal_prefix(pattern: &str) -> &str {
+    let end = pattern.find(['*', '?', '[']).unwrap_or(pattern.len());
+    &pattern[..end]
+}
+
+/// Returns the extension of the file at `inner_path`, or an empty string if it has none.
+fn extension(inner_path: &str) -> &str {
+    let file_name = inner_path.rsplit('/').next().unwrap_or_default();
+    match file_name.rfind('.') {
+        Some(index) => &file_name[index + 1..],
+        None => "",
+    }
+}
+
+/// Converts the `directory` into a prefix shared by all `inner_path`s located within it.
+fn directory_prefix(directory: &str) -> String {
+    if directory.is_empty() || directory.ends_with('/') {
+        directory.to_string()
+    } else {
+        format!("{}/", directory)
+    }
+}
+
+impl IntoIterator for Package {
+    type Item = PackageEntry;
+    type IntoIter = std::vec::IntoIter<PackageEntry>;
+
+    /// Consumes this [Package], returning its [entries](PackageEntry) in order.
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
 impl Display for Package {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "Package [entries: '{}']", self.entry_count())
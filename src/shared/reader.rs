@@ -1,11 +1,246 @@
+use std::cell::RefCell;
 use std::fs::File;
-use crate::error::PackageReadError;
-use crate::Package;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
+use std::rc::Rc;
+
+use memmap2::Mmap;
+
+use crate::error::{EntryOutOfBoundsError, PackageReadError};
+use crate::{Package, PackageEntry};
+
+/// Describes how the content of entries is accessed when reading a package from a generic
+/// [`Read`] + [`Seek`] input, see [`PackageReader::read_package_from_input`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadStrategy {
+    /// Keep the input open, and seek to read an entry's content only when it is requested.
+    Lazy,
+    /// Read the content of all entries into memory right away; the input is not retained.
+    Eager,
+}
+
+/// Describes how the content of entries is accessed when reading a package from a file, see
+/// [`PackageReader::read_package_from_file_with_strategy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FileReadStrategy {
+    /// Memory-map the file. This is the fastest option, but if the file is truncated or
+    /// rewritten while the package is in use, reading an entry's content may crash the process
+    /// or return garbage.
+    #[default]
+    MemoryMap,
+    /// Keep the file open, and read an entry's content with a positioned read when it is
+    /// requested. If the file is truncated while the package is in use, reading an entry's
+    /// content fails with an I/O error.
+    PositionedRead,
+    /// Read the whole file into memory right away; the file is not retained.
+    Load,
+}
+
+/// Location of a single entry's content within a package, as read from the package's index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    inner_path: String,
+    data_offset: u64,
+    data_size: u64,
+}
+
+impl IndexEntry {
+    /// Constructs an [`IndexEntry`].
+    ///
+    /// * `inner_path` - path under which the entry is stored within the package.
+    /// * `data_offset` - offset to the entry's content, relative to the start of the package.
+    /// * `data_size` - length of the entry's content.
+    pub fn new<S: AsRef<str>>(inner_path: S, data_offset: u64, data_size: u64) -> IndexEntry {
+        IndexEntry {
+            inner_path: inner_path.as_ref().to_string(),
+            data_offset,
+            data_size,
+        }
+    }
+
+    /// Returns the `inner_path` of this entry.
+    pub fn inner_path(&self) -> &str {
+        &self.inner_path
+    }
+
+    /// Returns the offset to this entry's content, relative to the start of the package.
+    pub fn data_offset(&self) -> u64 {
+        self.data_offset
+    }
+
+    /// Returns the length of this entry's content.
+    pub fn data_size(&self) -> u64 {
+        self.data_size
+    }
+}
 
 /// A trait that describes how a [`Package`] object should be read from a specific file format.
 ///
-/// The trait is defined by a single required method, [`read_package_from_file`](PackageReader::read_package_from_file),
-/// which implements the conversion of the file's binary content to a [`Package`].
+/// The trait is defined by a single required method, [`read_index`](PackageReader::read_index),
+/// which parses the format's index into the locations of entries' content. The provided methods
+/// use it to read packages from files, in-memory buffers, and generic [`Read`] + [`Seek`] inputs.
+///
+/// The trait is object-safe, so readers can be selected at runtime as `&dyn PackageReader`; only
+/// [`read_package_from_input`](PackageReader::read_package_from_input) requires a concrete reader.
 pub trait PackageReader {
-    fn read_package_from_file(&self, file: File) -> Result<Package, PackageReadError>;
+    /// Reads the index of a package from the specified input, and returns entries in the order
+    /// in which the package stores them.
+    ///
+    /// The index is read from a damaged or hostile file just as well, so counts and lengths read
+    /// from it must be checked against the input's length before allocating anything for them.
+    fn read_index(&self, input: &mut dyn SeekableInput) -> Result<Vec<IndexEntry>, PackageReadError>;
+
+    /// Reads a package from the specified file.
+    ///
+    /// The file is memory-mapped, and the memory map lives as long as the longest-lived entry
+    /// read from it.
+    fn read_package_from_file(&self, file: File) -> Result<Package, PackageReadError> {
+        let mmap = map_file(&file)?;
+        let index = self.read_index(&mut Cursor::new(&mmap[..]))?;
+        package_from_mmap(index, mmap)
+    }
+
+    /// Reads a package from the specified file, accessing the content of its entries according to
+    /// the specified [`FileReadStrategy`].
+    fn read_package_from_file_with_strategy(&self, file: File, strategy: FileReadStrategy) -> Result<Package, PackageReadError> {
+        match strategy {
+            FileReadStrategy::MemoryMap => self.read_package_from_file(file),
+            FileReadStrategy::Load => {
+                let mut bytes = Vec::new();
+                BufReader::new(file).read_to_end(&mut bytes)?;
+                self.read_package_from_bytes(bytes)
+            }
+            FileReadStrategy::PositionedRead => {
+                let index = self.read_index(&mut BufReader::new(&file))?;
+                validate_index(&index, file.metadata()?.len())?;
+
+                let file_rc = Rc::new(file);
+                build_package(index, |entry| {
+                    Ok(PackageEntry::from_open_file(
+                        entry.inner_path,
+                        file_rc.clone(),
+                        entry.data_offset,
+                        entry.data_size,
+                    ))
+                })
+            }
+        }
+    }
+
+    /// Reads a package held in memory.
+    ///
+    /// The buffer is shared by all entries read from it, and lives as long as the longest-lived
+    /// of them.
+    fn read_package_from_bytes(&self, bytes: Vec<u8>) -> Result<Package, PackageReadError> {
+        let index = self.read_index(&mut Cursor::new(&bytes[..]))?;
+        package_from_bytes(index, bytes)
+    }
+
+    /// Reads a package from the specified input, accessing the content of its entries according
+    /// to the specified [`ReadStrategy`].
+    fn read_package_from_input<I: Read + Seek + 'static>(&self, mut input: I, strategy: ReadStrategy) -> Result<Package, PackageReadError>
+        where Self: Sized {
+        let index = self.read_index(&mut input)?;
+        let input_length = input.seek(SeekFrom::End(0))?;
+        validate_index(&index, input_length)?;
+
+        match strategy {
+            ReadStrategy::Eager => {
+                build_package(index, |entry| {
+                    input.seek(SeekFrom::Start(entry.data_offset))?;
+                    let mut content = vec![0u8; entry.data_size as usize];
+                    input.read_exact(&mut content)?;
+                    Ok(PackageEntry::from_byte_array(entry.inner_path, content))
+                })
+            }
+            ReadStrategy::Lazy => {
+                let input_rc: Rc<RefCell<dyn SeekableInput>> = Rc::new(RefCell::new(input));
+                build_package(index, |entry| {
+                    Ok(PackageEntry::from_seekable_input(
+                        entry.inner_path,
+                        input_rc.clone(),
+                        entry.data_offset,
+                        entry.data_size,
+                    ))
+                })
+            }
+        }
+    }
+}
+
+/// Allows passing a reader by reference, eg. one selected at runtime as `&dyn PackageReader`.
+impl<T: PackageReader + ?Sized> PackageReader for &T {
+    fn read_index(&self, input: &mut dyn SeekableInput) -> Result<Vec<IndexEntry>, PackageReadError> {
+        (**self).read_index(input)
+    }
+}
+
+/// A [`Read`] + [`Seek`] input, from which a package's index or entries' content can be read.
+///
+/// Implemented for all types implementing both traits.
+pub trait SeekableInput: Read + Seek {}
+
+impl<T: Read + Seek> SeekableInput for T {}
+
+/// Checks that the content of every entry in the `index` lies within the package.
+pub(crate) fn validate_index(index: &[IndexEntry], package_size: u64) -> Result<(), EntryOutOfBoundsError> {
+    for entry in index {
+        let end = entry.data_offset.checked_add(entry.data_size);
+        if end.map(|end| end > package_size).unwrap_or(true) {
+            return Err(EntryOutOfBoundsError {
+                inner_path: entry.inner_path.clone(),
+                data_offset: entry.data_offset,
+                data_size: entry.data_size,
+                package_size,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Memory-maps the specified file, see [`PackageReader::read_package_from_file`].
+pub(crate) fn map_file(file: &File) -> Result<Mmap, std::io::Error> {
+    unsafe {
+        Mmap::map(file)
+    }
+}
+
+/// Creates a package whose entries are read from the memory-mapped file according to its `index`.
+pub(crate) fn package_from_mmap(index: Vec<IndexEntry>, mmap: Mmap) -> Result<Package, PackageReadError> {
+    validate_index(&index, mmap.len() as u64)?;
+
+    let mmap_rc = Rc::new(mmap);
+    build_package(index, |entry| {
+        Ok(PackageEntry::from_memory_mapped_file(
+            entry.inner_path,
+            mmap_rc.clone(),
+            entry.data_offset,
+            entry.data_size,
+        ))
+    })
+}
+
+/// Creates a package whose entries are read from the buffer according to its `index`.
+pub(crate) fn package_from_bytes(index: Vec<IndexEntry>, bytes: Vec<u8>) -> Result<Package, PackageReadError> {
+    validate_index(&index, bytes.len() as u64)?;
+
+    let bytes: Rc<[u8]> = bytes.into();
+    build_package(index, |entry| {
+        Ok(PackageEntry::from_shared_byte_array(
+            entry.inner_path,
+            bytes.clone(),
+            entry.data_offset,
+            entry.data_size,
+        ))
+    })
+}
+
+fn build_package<F>(index: Vec<IndexEntry>, mut build_entry: F) -> Result<Package, PackageReadError>
+    where F: FnMut(IndexEntry) -> Result<PackageEntry, PackageReadError> {
+    let mut result = Package::with_capacity(index.len());
+    for entry in index {
+        result.add_entry(build_entry(entry)?)?;
+    }
+
+    Ok(result)
 }
@@ -0,0 +1,131 @@
+use std::collections::BTreeMap;
+
+use crate::shared::entry::PackageEntry;
+
+// Documentation imports
+#[allow(unused)]
+use crate::Package;
+
+/// Represents a virtual directory within a [`Package`].
+///
+/// Packages store their entries as a flat list of `inner_path`s; directory nodes are derived
+/// from those paths by splitting them on `/`, and don't exist in the on-disk formats.
+/// Subdirectories and files of each node are ordered lexicographically by name.
+#[derive(Debug)]
+pub struct DirectoryNode<'a> {
+    path: String,
+    directories: BTreeMap<String, DirectoryNode<'a>>,
+    files: Vec<&'a PackageEntry>,
+}
+
+impl<'a> DirectoryNode<'a> {
+    /// Builds a directory tree rooted at `path` from the specified entries.
+    ///
+    /// * `path` - path of the root directory, either empty or ending with `/`.
+    /// * `entries` - entries located under `path`, sorted by their `inner_path`.
+    pub(crate) fn build<I: Iterator<Item=&'a PackageEntry>>(path: &str, entries: I) -> DirectoryNode<'a> {
+        let mut root = DirectoryNode::new(path.trim_end_matches('/').to_string());
+
+        for entry in entries {
+            let relative_path = &entry.inner_path()[path.len()..];
+            let mut node = &mut root;
+            let mut segments = relative_path.split('/').peekable();
+            while let Some(segment) = segments.next() {
+                if segments.peek().is_none() {
+                    node.files.push(entry);
+                    break;
+                }
+
+                let child_path = if node.path.is_empty() {
+                    segment.to_string()
+                } else {
+                    format!("{}/{}", node.path, segment)
+                };
+                node = node.directories
+                    .entry(segment.to_string())
+                    .or_insert_with(|| DirectoryNode::new(child_path));
+            }
+        }
+
+        root
+    }
+
+    fn new(path: String) -> DirectoryNode<'a> {
+        DirectoryNode {
+            path,
+            directories: BTreeMap::new(),
+            files: Vec::new(),
+        }
+    }
+
+    /// Returns the path of this directory within the package, without a trailing `/`.
+    /// The root directory has an empty path.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the name of this directory, ie. the last segment of its path.
+    pub fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or_default()
+    }
+
+    /// Returns an iterator over the immediate subdirectories of this directory.
+    pub fn directories(&self) -> impl Iterator<Item=&DirectoryNode<'a>> {
+        self.directories.values()
+    }
+
+    /// Returns the immediate subdirectory with the specified `name`, if it exists.
+    pub fn directory<S: AsRef<str>>(&self, name: S) -> Option<&DirectoryNode<'a>> {
+        self.directories.get(name.as_ref())
+    }
+
+    /// Returns the [entries](PackageEntry) located directly in this directory.
+    pub fn files(&self) -> &[&'a PackageEntry] {
+        &self.files
+    }
+
+    /// Returns the number of entries in this directory and all of its subdirectories.
+    pub fn entry_count(&self) -> usize {
+        self.files.len() + self.directories()
+            .map(|directory| directory.entry_count())
+            .sum::<usize>()
+    }
+
+    /// Returns the combined content size, in bytes, of all entries in this directory and all of
+    /// its subdirectories.
+    pub fn total_size(&self) -> Result<u64, std::io::Error> {
+        let mut result = 0;
+        for entry in self.files.iter() {
+            result += entry.content_size()?;
+        }
+        for directory in self.directories() {
+            result += directory.total_size()?;
+        }
+
+        Ok(result)
+    }
+
+    /// Returns an iterator walking this directory and all of its subdirectories recursively,
+    /// depth-first, visiting each directory before its subdirectories.
+    pub fn walk(&self) -> DirectoryWalk<'_, 'a> {
+        DirectoryWalk {
+            stack: vec![self],
+        }
+    }
+}
+
+/// Iterator returned by [`DirectoryNode::walk`].
+pub struct DirectoryWalk<'n, 'a> {
+    stack: Vec<&'n DirectoryNode<'a>>,
+}
+
+impl<'n, 'a> Iterator for DirectoryWalk<'n, 'a> {
+    type Item = &'n DirectoryNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        // Push in reverse so that subdirectories are visited in lexicographical order
+        self.stack.extend(node.directories.values().rev());
+        Some(node)
+    }
+}
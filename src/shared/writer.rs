@@ -1,4 +1,7 @@
+use std::ffi::OsString;
 use std::io::{Seek, Write};
+use std::path::{Path, PathBuf};
+
 use crate::error::PackageWriteError;
 use crate::Package;
 
@@ -9,3 +12,21 @@ use crate::Package;
 pub trait PackageWriter {
     fn write_package_to_output<T: Write + Seek>(&self, package: &Package, output: T) -> Result<(), PackageWriteError>;
 }
+
+/// Writes the file at `destination_path` by having `write` create a temporary file next to it,
+/// named after the destination with `.tmp` appended, which is then moved into place. This way the
+/// destination is never left partially written. The temporary file is removed if anything fails.
+pub(crate) fn write_via_temporary_file<E, F>(destination_path: &Path, write: F) -> Result<(), E>
+    where E: From<std::io::Error>, F: FnOnce(&Path) -> Result<(), E> {
+    let mut temporary_path = OsString::from(destination_path);
+    temporary_path.push(".tmp");
+    let temporary_path = PathBuf::from(temporary_path);
+
+    let result = write(&temporary_path)
+        .and_then(|_| std::fs::rename(&temporary_path, destination_path).map_err(E::from));
+    if result.is_err() {
+        // The original error is more useful than one about the temporary file
+        let _ = std::fs::remove_file(&temporary_path);
+    }
+    result
+}
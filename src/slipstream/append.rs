@@ -0,0 +1,104 @@
+use crate::{Package, PackageEntry};
+use crate::error::InnerPathNotFoundError;
+use crate::slipstream::error::XmlAppendError;
+
+// FTL's XML files are usually not well-formed documents: they may contain multiple top-level
+// elements, and since FTL 1.6 they are wrapped in a `<FTL>` root element instead. FTL ignores
+// anything after the closing `</FTL>` tag, so appended content has to go inside of it.
+
+static BYTE_ORDER_MARK: char = '\u{feff}';
+pub(super) static ROOT_ELEMENT_NAME: &str = "FTL";
+
+impl Package {
+    /// Appends XML content to the XML entry at the specified `inner_path`, the way FTL mods'
+    /// `.xml.append` files are applied, and replaces the entry with the merged result held in memory.
+    ///
+    /// The appended content is stripped of its byte order mark, its XML declaration, and its
+    /// `<FTL>` root element, if any. If the existing entry is wrapped in an `<FTL>` root element,
+    /// the content is inserted before its closing tag; otherwise, it is added at the end.
+    pub fn append_xml<S: AsRef<str>>(&mut self, inner_path: S, appended_content: &[u8]) -> Result<(), XmlAppendError> {
+        let inner_path = inner_path.as_ref();
+        let existing_text = self.xml_text(inner_path)?;
+        let merged_text = appended_xml(inner_path, &existing_text, appended_content)?;
+        self.put_entry(PackageEntry::from_string(inner_path, merged_text));
+        Ok(())
+    }
+
+    /// Returns the content of the entry at the specified `inner_path` as text.
+    pub(super) fn xml_text(&self, inner_path: &str) -> Result<String, XmlAppendError> {
+        let content = self.entry_by_path(inner_path)
+            .ok_or_else(|| InnerPathNotFoundError(inner_path.to_string()))?
+            .content()?;
+
+        xml_text_of(inner_path, content)
+    }
+}
+
+/// Interprets the content of the entry at `inner_path` as text.
+pub(super) fn xml_text_of(inner_path: &str, content: Vec<u8>) -> Result<String, XmlAppendError> {
+    String::from_utf8(content)
+        .map_err(|_| XmlAppendError::InvalidEncodingError(inner_path.to_string()))
+}
+
+/// Returns the `existing_text` of the entry at `inner_path` with `appended_content` appended to
+/// it, see [`Package::append_xml`].
+pub(super) fn appended_xml(inner_path: &str, existing_text: &str, appended_content: &[u8]) -> Result<String, XmlAppendError> {
+    let appended_text = std::str::from_utf8(appended_content)
+        .map_err(|_| XmlAppendError::InvalidEncodingError(inner_path.to_string()))?;
+
+    Ok(merge_xml(existing_text, appended_text))
+}
+
+/// Merges the `appended` XML text into the `base` XML text, see [`Package::append_xml`].
+fn merge_xml(base: &str, appended: &str) -> String {
+    let appended = strip_appended_prolog(appended).trim();
+
+    let base = base.trim_end();
+    let closing_tag = format!("</{}>", ROOT_ELEMENT_NAME);
+    match base.strip_suffix(&closing_tag) {
+        Some(base_content) => format!("{}\n{}\n{}\n", base_content.trim_end(), appended, closing_tag),
+        None => format!("{}\n{}\n", base, appended),
+    }
+}
+
+/// Removes the byte order mark, XML declaration and `<FTL>` root element from appended XML text.
+pub(super) fn strip_appended_prolog(appended: &str) -> &str {
+    strip_root_element(strip_declaration(appended.trim_start_matches(BYTE_ORDER_MARK)))
+}
+
+/// Splits the byte order mark, leading whitespace and XML declaration off the start of the
+/// specified text, if it has one.
+pub(super) fn split_prolog(text: &str) -> (&str, &str) {
+    let body = strip_declaration(text.trim_start_matches(BYTE_ORDER_MARK));
+    text.split_at(text.len() - body.len())
+}
+
+/// Removes the XML declaration (`<?xml ... ?>`) from the start of the specified text.
+fn strip_declaration(text: &str) -> &str {
+    let trimmed_text = text.trim_start();
+    if trimmed_text.starts_with("<?xml") {
+        if let Some(end) = trimmed_text.find("?>") {
+            return &trimmed_text[end + 2..];
+        }
+    }
+
+    text
+}
+
+/// Removes the `<FTL>` root element's tags from the specified text, if the whole text is wrapped
+/// in it.
+fn strip_root_element(text: &str) -> &str {
+    let trimmed_text = text.trim();
+    let closing_tag = format!("</{}>", ROOT_ELEMENT_NAME);
+
+    let opening_tag_content = trimmed_text.strip_prefix('<')
+        .and_then(|text| text.strip_prefix(ROOT_ELEMENT_NAME))
+        .filter(|text| text.starts_with(|c: char| c == '>' || c.is_whitespace()));
+
+    match (opening_tag_content, trimmed_text.ends_with(&closing_tag)) {
+        (Some(opening_tag_content), true) => opening_tag_content.find('>')
+            .and_then(|end| opening_tag_content.get(end + 1..opening_tag_content.len() - closing_tag.len()))
+            .unwrap_or(text),
+        _ => text,
+    }
+}
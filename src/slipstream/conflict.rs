@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+
+use crate::{ContentHash, FtlMod, ModFileAction};
+
+/// A single mod file applied to an inner path, see [`TouchedPath`].
+#[derive(Debug, Clone)]
+pub struct ModTouch {
+    mod_name: String,
+    archive_path: String,
+    action: ModFileAction,
+    content_hash: ContentHash,
+    content: Vec<u8>,
+}
+
+impl ModTouch {
+    /// Returns the name the mod was given in the analysis.
+    pub fn mod_name(&self) -> &str {
+        &self.mod_name
+    }
+
+    /// Returns the path of the file within the mod.
+    pub fn archive_path(&self) -> &str {
+        &self.archive_path
+    }
+
+    /// Returns how the file is applied to the entry.
+    pub fn action(&self) -> ModFileAction {
+        self.action
+    }
+
+    /// Returns the hash of the file's content.
+    pub fn content_hash(&self) -> &ContentHash {
+        &self.content_hash
+    }
+
+    /// Returns `true` if the file is appended to the entry, rather than replacing it.
+    pub fn is_append(&self) -> bool {
+        self.action != ModFileAction::Replace
+    }
+
+    /// Returns `true` if both files have identical content. Hashes only rule out identical
+    /// content, since CRC32 collisions are easy to come by, so matching ones are compared byte by byte.
+    fn has_same_content(&self, other: &ModTouch) -> bool {
+        self.content_hash == other.content_hash && self.content == other.content
+    }
+}
+
+/// An inner path, along with every mod file applied to it, in installation order.
+#[derive(Debug, Clone)]
+pub struct TouchedPath {
+    inner_path: String,
+    touches: Vec<ModTouch>,
+}
+
+impl TouchedPath {
+    /// Returns the `inner_path` of the entry.
+    pub fn inner_path(&self) -> &str {
+        &self.inner_path
+    }
+
+    /// Returns the mod files applied to the entry, in installation order.
+    pub fn touches(&self) -> &[ModTouch] {
+        &self.touches
+    }
+
+    /// Returns `true` if more than one mod file is applied to the entry.
+    pub fn is_conflict(&self) -> bool {
+        self.touches.len() > 1
+    }
+
+    /// Returns the last mod file that replaces the entry, which any later appends are applied to.
+    ///
+    /// Returns `None` if all files append to the game's own entry.
+    pub fn winner(&self) -> Option<&ModTouch> {
+        self.touches.iter()
+            .rev()
+            .find(|touch| !touch.is_append())
+    }
+
+    /// Returns `true` if all mod files are applied the same way, with identical content, so that
+    /// it makes no difference which of them wins.
+    pub fn is_identical(&self) -> bool {
+        self.touches.windows(2)
+            .all(|pair| pair[0].action == pair[1].action && pair[0].has_same_content(&pair[1]))
+    }
+
+    /// Returns `true` if no mod file's change is lost, because it is replaced by a later one with
+    /// different content. This is the case if all replacing files have identical content, and all
+    /// of them come before the first appending file.
+    pub fn is_mergeable(&self) -> bool {
+        self.touches.iter()
+            .enumerate()
+            .filter(|(_, touch)| !touch.is_append())
+            .all(|(index, touch)| self.touches[..index].iter()
+                .all(|earlier| !earlier.is_append() && earlier.has_same_content(touch)))
+    }
+}
+
+/// Lists which mods touch which inner paths, when applied in the given order.
+#[derive(Debug, Default)]
+pub struct ModConflictAnalysis {
+    paths: Vec<TouchedPath>,
+}
+
+impl ModConflictAnalysis {
+    /// Analyzes the specified mods, given as pairs of name and mod, in installation order.
+    ///
+    /// Packages and unpacked mod directories can be analyzed by converting them with
+    /// [`FtlMod::from_package`] and [`FtlMod::from_directory`] first.
+    pub fn new<'a, S, I>(ftl_mods: I) -> ModConflictAnalysis
+        where S: Into<String>, I: IntoIterator<Item=(S, &'a FtlMod)>
+    {
+        let mut paths: BTreeMap<String, Vec<ModTouch>> = BTreeMap::new();
+        for (mod_name, ftl_mod) in ftl_mods {
+            let mod_name = mod_name.into();
+            for file in ftl_mod.files() {
+                paths.entry(file.inner_path().to_string())
+                    .or_default()
+                    .push(ModTouch {
+                        mod_name: mod_name.clone(),
+                        archive_path: file.archive_path().to_string(),
+                        action: file.action(),
+                        content_hash: ContentHash::of(file.content()),
+                        content: file.content().to_vec(),
+                    });
+            }
+        }
+
+        ModConflictAnalysis {
+            paths: paths.into_iter()
+                .map(|(inner_path, touches)| TouchedPath { inner_path, touches })
+                .collect(),
+        }
+    }
+
+    /// Returns every inner path touched by any of the mods, sorted by inner path.
+    pub fn paths(&self) -> &[TouchedPath] {
+        &self.paths
+    }
+
+    /// Returns the inner paths touched by more than one mod file, sorted by inner path.
+    pub fn conflicts(&self) -> impl Iterator<Item=&TouchedPath> {
+        self.paths.iter().filter(|path| path.is_conflict())
+    }
+
+    /// Returns the mod files applied to the specified inner path, or `None` if no mod touches it.
+    pub fn path<S: AsRef<str>>(&self, inner_path: S) -> Option<&TouchedPath> {
+        self.paths.binary_search_by(|path| path.inner_path.as_str().cmp(inner_path.as_ref()))
+            .ok()
+            .map(|index| &self.paths[index])
+    }
+}
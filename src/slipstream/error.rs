@@ -0,0 +1,46 @@
+use thiserror::Error;
+
+use crate::shared::error::InnerPathNotFoundError;
+
+#[derive(Error, Debug)]
+pub enum ModReadError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error("mod archive is invalid: {0}")]
+    ArchiveError(#[from] zip::result::ZipError),
+    #[error("file '{0}' of mod archive is larger than {} bytes", crate::FtlMod::MAX_FILE_SIZE)]
+    FileTooLargeError(String),
+}
+
+#[derive(Error, Debug)]
+pub enum ModApplyError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    XmlAppendError(#[from] XmlAppendError),
+}
+
+#[derive(Error, Debug)]
+pub enum XmlAppendError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    InnerPathNotFoundError(#[from] InnerPathNotFoundError),
+    #[error("XML content of inner path '{0}' is not valid UTF-8")]
+    InvalidEncodingError(String),
+    #[error("inner path '{inner_path}', line {line}: {message}")]
+    InvalidXmlError {
+        inner_path: String,
+        line: usize,
+        message: String,
+    },
+    #[error("patch, line {line}: {message}")]
+    InvalidPatchError {
+        line: usize,
+        message: String,
+    },
+    #[error("invalid patch directive {0}")]
+    InvalidDirectiveError(String),
+    #[error("{0} did not match any element")]
+    UnmatchedSelectorError(String),
+}
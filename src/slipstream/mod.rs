@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+
+use zip::ZipArchive;
+
+use crate::{Package, PackageEntry};
+use crate::shared::binary::read_limited;
+use crate::slipstream::error::{ModApplyError, ModReadError};
+
+pub use crate::slipstream::conflict::{ModConflictAnalysis, ModTouch, TouchedPath};
+pub use crate::slipstream::patch::XmlPatchReport;
+
+pub(crate) mod error;
+mod append;
+mod conflict;
+mod patch;
+mod xml;
+
+// Slipstream mods are zip archives, usually with the `.ftl` extension. Files located in one of
+// the top-level directories known to the game are applied to the package under the same inner
+// path; everything else (eg. `mod-appendix/metadata.xml`) is ignored.
+//
+// A file's suffix determines how it is applied:
+// - `*.xml.append` / `*.append.xml` := appended to the `*.xml` entry
+// - `*.xml.rawappend` / `*.rawappend.xml` := appended to the `*.xml` entry, as raw text
+// - `*.xml.rawclobber` / `*.rawclobber.xml` := replaces the `*.xml` entry
+// - anything else := replaces the entry with the same inner path, or is added as a new entry
+
+static TOP_LEVEL_DIRECTORIES: [&str; 4] = ["audio/", "data/", "fonts/", "img/"];
+static JUNK_FILE_NAMES: [&str; 2] = [".DS_Store", "Thumbs.db"];
+static SUFFIXES: [(&str, ModFileAction); 6] = [
+    (".xml.append", ModFileAction::Append),
+    (".append.xml", ModFileAction::Append),
+    (".xml.rawappend", ModFileAction::RawAppend),
+    (".rawappend.xml", ModFileAction::RawAppend),
+    (".xml.rawclobber", ModFileAction::Replace),
+    (".rawclobber.xml", ModFileAction::Replace),
+];
+
+/// Describes how a [`ModFile`] is applied to its target entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModFileAction {
+    /// Replace the entry, or add it if it doesn't exist.
+    Replace,
+    /// Patch the existing XML entry, processing advanced find tags, see [`Package::patch_xml`].
+    Append,
+    /// Append to the existing XML entry as-is, see [`Package::append_xml`].
+    RawAppend,
+}
+
+/// A single file of an [`FtlMod`], which is applied to a package entry.
+#[derive(Debug)]
+pub struct ModFile {
+    archive_path: String,
+    inner_path: String,
+    action: ModFileAction,
+    content: Vec<u8>,
+}
+
+impl ModFile {
+    /// Returns the path of this file within the mod archive.
+    pub fn archive_path(&self) -> &str {
+        &self.archive_path
+    }
+
+    /// Returns the `inner_path` of the entry this file is applied to.
+    pub fn inner_path(&self) -> &str {
+        &self.inner_path
+    }
+
+    /// Returns how this file is applied to its target entry.
+    pub fn action(&self) -> ModFileAction {
+        self.action
+    }
+
+    /// Returns the content of this file.
+    pub fn content(&self) -> &[u8] {
+        &self.content
+    }
+}
+
+/// A Slipstream-compatible FTL mod, read from a `.ftl` (zip) archive or an unpacked mod directory.
+#[derive(Debug, Default)]
+pub struct FtlMod {
+    files: Vec<ModFile>,
+    ignored_paths: Vec<String>,
+}
+
+/// Outcome of [`Package::apply_mod`].
+#[derive(Debug, Default)]
+pub struct ModApplyReport {
+    added: Vec<String>,
+    overwritten: Vec<String>,
+    appended: Vec<String>,
+    skipped: Vec<String>,
+    unmatched_selectors: Vec<(String, String)>,
+}
+
+impl ModApplyReport {
+    /// Returns inner paths of entries that were added to the package.
+    pub fn added(&self) -> &[String] {
+        &self.added
+    }
+
+    /// Returns inner paths of entries that were replaced.
+    pub fn overwritten(&self) -> &[String] {
+        &self.overwritten
+    }
+
+    /// Returns inner paths of entries that were appended to.
+    pub fn appended(&self) -> &[String] {
+        &self.appended
+    }
+
+    /// Returns archive paths of files that append to entries which do not exist in the package,
+    /// and were therefore not applied.
+    pub fn skipped(&self) -> &[String] {
+        &self.skipped
+    }
+
+    /// Returns find tags of `.xml.append` files that did not match any element, as pairs of the
+    /// patched entry's inner path and the find tag, see [`XmlPatchReport::unmatched_selectors`].
+    pub fn unmatched_selectors(&self) -> &[(String, String)] {
+        &self.unmatched_selectors
+    }
+}
+
+impl FtlMod {
+    /// Maximum uncompressed size of a single file in a mod archive. Larger files are rejected
+    /// instead of being read into memory, which guards against zip bombs.
+    pub const MAX_FILE_SIZE: u64 = 256 * 1024 * 1024;
+
+    /// Reads the mod archive at the specified path.
+    pub fn from_path<P: AsRef<Path>>(source_path: P) -> Result<FtlMod, ModReadError> {
+        FtlMod::from_input(BufReader::new(File::open(source_path)?))
+    }
+
+    /// Reads a mod archive from the specified input. Content of all applicable files is read
+    /// into memory.
+    ///
+    /// Returns a [ModReadError::FileTooLargeError] if an applicable file is larger than
+    /// [FtlMod::MAX_FILE_SIZE].
+    pub fn from_input<R: Read + Seek>(input: R) -> Result<FtlMod, ModReadError> {
+        let mut archive = ZipArchive::new(input)?;
+
+        let mut result = FtlMod::default();
+        for index in 0..archive.len() {
+            let mut file = archive.by_index(index)?;
+            if file.is_dir() {
+                continue;
+            }
+
+            let archive_path = file.name().replace('\\', "/");
+            result.push_file(archive_path, |archive_path| {
+                // The size recorded in the archive may be wrong, so the read itself is limited as well
+                if file.size() > FtlMod::MAX_FILE_SIZE {
+                    return Err(ModReadError::FileTooLargeError(archive_path.to_string()));
+                }
+                read_limited(&mut file, FtlMod::MAX_FILE_SIZE)?
+                    .ok_or_else(|| ModReadError::FileTooLargeError(archive_path.to_string()))
+            })?;
+        }
+
+        Ok(result)
+    }
+
+    /// Reads an unpacked mod from the specified directory, which contains the mod's top-level
+    /// directories. Files are read in alphabetical order, since directories have no inherent one.
+    pub fn from_directory<P: AsRef<Path>>(source_path: P) -> Result<FtlMod, ModReadError> {
+        let mut archive_paths = Vec::new();
+        collect_files(source_path.as_ref(), "", &mut archive_paths)?;
+        archive_paths.sort();
+
+        let mut result = FtlMod::default();
+        for archive_path in archive_paths {
+            let file_path = source_path.as_ref().join(&archive_path);
+            result.push_file(archive_path, |_| std::fs::read(file_path))?;
+        }
+
+        Ok(result)
+    }
+
+    /// Treats the entries of the specified [`Package`] as the files of a mod, using their
+    /// `inner_path`s as archive paths.
+    pub fn from_package(package: &Package) -> Result<FtlMod, std::io::Error> {
+        let mut result = FtlMod::default();
+        for entry in package.iter() {
+            result.push_file(entry.inner_path().to_string(), |_| entry.content())?;
+        }
+
+        Ok(result)
+    }
+
+    /// Adds the file at `archive_path` to this mod, reading its content only if it is applicable.
+    fn push_file<E, F>(&mut self, archive_path: String, read_content: F) -> Result<(), E>
+        where F: FnOnce(&str) -> Result<Vec<u8>, E>
+    {
+        if !is_applicable(&archive_path) {
+            self.ignored_paths.push(archive_path);
+            return Ok(());
+        }
+
+        let (inner_path, action) = target_of(&archive_path);
+        let content = read_content(&archive_path)?;
+        self.files.push(ModFile {
+            inner_path,
+            action,
+            archive_path,
+            content,
+        });
+        Ok(())
+    }
+
+    /// Returns the files of this mod that are applied to a package, in archive order.
+    pub fn files(&self) -> &[ModFile] {
+        &self.files
+    }
+
+    /// Returns archive paths of files that are not applied to a package: files outside of the
+    /// game's top-level directories, and junk files created by file managers.
+    pub fn ignored_paths(&self) -> &[String] {
+        &self.ignored_paths
+    }
+}
+
+impl Package {
+    /// Applies the specified mod to this [`Package`], file by file in archive order.
+    ///
+    /// Appends to entries that do not exist are skipped, and listed in the returned report. If any
+    /// file fails to apply, an error is returned and the package is left unchanged.
+    pub fn apply_mod(&mut self, ftl_mod: &FtlMod) -> Result<ModApplyReport, ModApplyError> {
+        let mut report = ModApplyReport::default();
+        let mut staged = StagedContents::default();
+        for file in ftl_mod.files() {
+            let inner_path = file.inner_path.as_str();
+            let exists = staged.contains(inner_path) || self.entry_exists(inner_path);
+            match file.action {
+                ModFileAction::Replace => {
+                    if exists {
+                        report.overwritten.push(file.inner_path.clone());
+                    } else {
+                        report.added.push(file.inner_path.clone());
+                    }
+
+                    staged.put(inner_path, file.content.clone());
+                }
+                ModFileAction::Append | ModFileAction::RawAppend => {
+                    if !exists {
+                        report.skipped.push(file.archive_path.clone());
+                        continue;
+                    }
+
+                    let existing_text = match staged.take(inner_path) {
+                        Some(content) => append::xml_text_of(inner_path, content)?,
+                        None => self.xml_text(inner_path)?,
+                    };
+                    let text = if file.action == ModFileAction::Append {
+                        let (patched_text, patch_report) = patch::patched_xml(inner_path, &existing_text, &file.content)?;
+                        for selector in patch_report.unmatched_selectors() {
+                            report.unmatched_selectors.push((file.inner_path.clone(), selector.clone()));
+                        }
+                        patched_text
+                    } else {
+                        append::appended_xml(inner_path, &existing_text, &file.content)?
+                    };
+                    staged.put(inner_path, text.into_bytes());
+                    report.appended.push(file.inner_path.clone());
+                }
+            }
+        }
+
+        for (inner_path, content) in staged.contents {
+            self.put_entry(PackageEntry::from_byte_array(inner_path, content));
+        }
+        Ok(report)
+    }
+
+    /// Applies the specified mods to this [`Package`] one after another, so that later mods take
+    /// precedence, and returns a report for each of them.
+    pub fn apply_mods<'a, I: IntoIterator<Item=&'a FtlMod>>(&mut self, ftl_mods: I) -> Result<Vec<ModApplyReport>, ModApplyError> {
+        ftl_mods.into_iter()
+            .map(|ftl_mod| self.apply_mod(ftl_mod))
+            .collect()
+    }
+}
+
+/// Contents of the entries changed by a mod while it is applied, in the order they were first
+/// changed in, so that they are only put into the package once the whole mod applied.
+#[derive(Default)]
+struct StagedContents<'a> {
+    contents: Vec<(&'a str, Vec<u8>)>,
+    positions: HashMap<&'a str, usize>,
+}
+
+impl<'a> StagedContents<'a> {
+    fn contains(&self, inner_path: &str) -> bool {
+        self.positions.contains_key(inner_path)
+    }
+
+    /// Takes the staged content of `inner_path` out, to be replaced by a later [`put`](Self::put).
+    fn take(&mut self, inner_path: &str) -> Option<Vec<u8>> {
+        self.positions.get(inner_path)
+            .map(|position| std::mem::take(&mut self.contents[*position].1))
+    }
+
+    fn put(&mut self, inner_path: &'a str, content: Vec<u8>) {
+        match self.positions.get(inner_path) {
+            Some(position) => self.contents[*position].1 = content,
+            None => {
+                self.positions.insert(inner_path, self.contents.len());
+                self.contents.push((inner_path, content));
+            }
+        }
+    }
+}
+
+fn is_applicable(archive_path: &str) -> bool {
+    let file_name = archive_path.rsplit('/').next().unwrap_or(archive_path);
+    TOP_LEVEL_DIRECTORIES.iter().any(|directory| archive_path.starts_with(directory))
+        && !JUNK_FILE_NAMES.contains(&file_name)
+}
+
+/// Collects paths of all files below `directory`, relative to the mod's root directory.
+fn collect_files(directory: &Path, prefix: &str, archive_paths: &mut Vec<String>) -> Result<(), std::io::Error> {
+    for dir_entry in std::fs::read_dir(directory)? {
+        let dir_entry = dir_entry?;
+        let archive_path = format!("{}{}", prefix, dir_entry.file_name().to_string_lossy());
+        if dir_entry.file_type()?.is_dir() {
+            collect_files(&dir_entry.path(), &format!("{}/", archive_path), archive_paths)?;
+        } else {
+            archive_paths.push(archive_path);
+        }
+    }
+
+    Ok(())
+}
+
+fn target_of(archive_path: &str) -> (String, ModFileAction) {
+    for (suffix, action) in SUFFIXES {
+        if let Some(stem) = archive_path.strip_suffix(suffix) {
+            return (format!("{}.xml", stem), action);
+        }
+    }
+
+    (archive_path.to_string(), ModFileAction::Replace)
+}
@@ -0,0 +1,413 @@
+use std::collections::BTreeSet;
+
+use crate::{Package, PackageEntry};
+use crate::slipstream::append::{split_prolog, strip_appended_prolog, ROOT_ELEMENT_NAME};
+use crate::slipstream::error::XmlAppendError;
+use crate::slipstream::xml::{self, Element, Node};
+
+// Slipstream's "advanced find" tags locate elements among the children of the current context
+// (the root of the file, or the element matched by an enclosing find tag), and apply the commands
+// nested in them to each match:
+// - `<mod:findName type="..." name="...">` := elements with the given `name` attribute
+//   (defaults: `reverse="true" start="0" limit="1"`)
+// - `<mod:findLike type="...">` := elements matching a nested `<mod:selector>`
+//   (defaults: `reverse="false" start="0" limit="-1"`)
+// - `<mod:findWithChildLike type="..." child-type="...">` := elements with a child matching a
+//   nested `<mod:selector>` (defaults: `reverse="false" start="0" limit="-1"`)
+// - `<mod:findComposite>` := elements matched by a nested `<mod:par op="AND|OR">`, which
+//   intersects or unites the matches of the find tags and `<mod:par>` tags nested in it, in
+//   document order (defaults: `reverse="false" start="0" limit="-1"`). Commands nested in those
+//   find tags are ignored.
+//
+// `type` restricts matches to elements with the given name. A selector matches elements having
+// all of its attributes, and its text, if it has any. With `panic="true"`, a find tag that matches
+// nothing aborts the patch.
+//
+// Commands:
+// - `<mod:setAttributes a="..."/>`, `<mod:removeAttributes a=""/>`, `<mod:setValue>...</mod:setValue>`
+// - `<mod:removeTag/>`
+// - `<mod-append:name ...>` := adds a `<name ...>` child
+// - `<mod-overwrite:name ...>` := replaces the first `<name>` child, or adds one if there is none
+// - nested find tags, which search among the children of the match
+//
+// Anything else at the top level of the patch is appended, as with plain `.xml.append` files.
+
+static MOD_PREFIX: &str = "mod:";
+static MOD_APPEND_PREFIX: &str = "mod-append:";
+static MOD_OVERWRITE_PREFIX: &str = "mod-overwrite:";
+
+/// Names of commands, which are only valid nested in a find tag.
+static COMMAND_NAMES: &[&str] = &[
+    "mod:selector", "mod:par", "mod:setAttributes", "mod:removeAttributes", "mod:setValue", "mod:removeTag",
+];
+
+/// Outcome of [`Package::patch_xml`].
+#[derive(Debug, Default)]
+pub struct XmlPatchReport {
+    unmatched_selectors: Vec<String>,
+}
+
+impl XmlPatchReport {
+    /// Returns the start tags of find tags that did not match any element, in patch order.
+    pub fn unmatched_selectors(&self) -> &[String] {
+        &self.unmatched_selectors
+    }
+}
+
+impl Package {
+    /// Applies an FTL mod's `.xml.append` file to the XML entry at the specified `inner_path`,
+    /// processing Slipstream's advanced find tags (`mod:findName`, `mod:findLike`,
+    /// `mod:findWithChildLike`, `mod:findComposite`) and the commands nested in them. Content
+    /// outside of find tags is appended as with [`Package::append_xml`]. The entry is replaced
+    /// with the patched result held in memory.
+    ///
+    /// Like Slipstream, the existing entry is parsed leniently: mismatched and stray end tags,
+    /// which occur in some vanilla files, are recovered from instead of failing the patch.
+    ///
+    /// Find tags that do not match any element are listed in the returned report, unless they are
+    /// marked with `panic="true"`, in which case the entry is left unchanged and an error is returned.
+    pub fn patch_xml<S: AsRef<str>>(&mut self, inner_path: S, patch_content: &[u8]) -> Result<XmlPatchReport, XmlAppendError> {
+        let inner_path = inner_path.as_ref();
+        let existing_text = self.xml_text(inner_path)?;
+        let (patched_text, report) = patched_xml(inner_path, &existing_text, patch_content)?;
+        self.put_entry(PackageEntry::from_string(inner_path, patched_text));
+        Ok(report)
+    }
+}
+
+/// Returns the `existing_text` of the entry at `inner_path` with the `patch_content` applied to
+/// it, see [`Package::patch_xml`].
+pub(super) fn patched_xml(inner_path: &str, existing_text: &str, patch_content: &[u8]) -> Result<(String, XmlPatchReport), XmlAppendError> {
+    let (prolog, body) = split_prolog(existing_text);
+    let mut nodes = xml::parse_recovering(body).map_err(|error| XmlAppendError::InvalidXmlError {
+        inner_path: inner_path.to_string(),
+        line: error.line + prolog.matches('\n').count(),
+        message: error.message,
+    })?;
+
+    let patch_text = std::str::from_utf8(patch_content)
+        .map_err(|_| XmlAppendError::InvalidEncodingError(inner_path.to_string()))?;
+    let patch_body = strip_appended_prolog(patch_text);
+    let directives = xml::parse(patch_body).map_err(|error| XmlAppendError::InvalidPatchError {
+        line: error.line + line_offset(patch_text, patch_body),
+        message: error.message,
+    })?;
+
+    let mut report = XmlPatchReport::default();
+    apply_directives(root_context(&mut nodes), directives, &mut report)?;
+
+    Ok((format!("{}{}", prolog, xml::write(&nodes)), report))
+}
+
+/// Returns the nodes that patches are applied to: the children of the `<FTL>` root element if the
+/// fragment is wrapped in one, or else the top-level nodes of the fragment.
+fn root_context(nodes: &mut Vec<Node>) -> &mut Vec<Node> {
+    let mut elements = nodes.iter().enumerate().filter(|(_, node)| node.as_element().is_some());
+    let root_position = match (elements.next(), elements.next()) {
+        (Some((position, Node::Element(element))), None) if element.name == ROOT_ELEMENT_NAME => Some(position),
+        _ => None,
+    };
+
+    match root_position {
+        Some(position) => match &mut nodes[position] {
+            Node::Element(root) => &mut root.children,
+            _ => unreachable!(),
+        },
+        None => nodes,
+    }
+}
+
+/// Returns the number of lines preceding the `slice` within the `text` it was taken from.
+fn line_offset(text: &str, slice: &str) -> usize {
+    let start = slice.as_ptr() as usize - text.as_ptr() as usize;
+    text[..start].matches('\n').count()
+}
+
+fn apply_directives(context: &mut Vec<Node>, directives: Vec<Node>, report: &mut XmlPatchReport) -> Result<(), XmlAppendError> {
+    let mut appended_nodes = Vec::new();
+    for node in directives {
+        match node {
+            Node::Element(element) if FindKind::of(&element).is_some() => apply_find(context, &element, report)?,
+            Node::Element(element) if is_command(&element) => {
+                return Err(XmlAppendError::InvalidDirectiveError(format!("{} must be nested in a find tag", element.start_tag())));
+            }
+            Node::Element(element) if element.name.starts_with(MOD_PREFIX) => {
+                return Err(XmlAppendError::InvalidDirectiveError(format!("{} is not supported", element.start_tag())));
+            }
+            node => appended_nodes.push(node),
+        }
+    }
+
+    while appended_nodes.first().map(Node::is_whitespace).unwrap_or(false) {
+        appended_nodes.remove(0);
+    }
+    while appended_nodes.last().map(Node::is_whitespace).unwrap_or(false) {
+        appended_nodes.pop();
+    }
+
+    if !appended_nodes.is_empty() {
+        while context.last().map(Node::is_whitespace).unwrap_or(false) {
+            context.pop();
+        }
+
+        context.push(Node::Text("\n".to_string()));
+        context.extend(appended_nodes);
+        context.push(Node::Text("\n".to_string()));
+    }
+
+    Ok(())
+}
+
+fn is_command(element: &Element) -> bool {
+    COMMAND_NAMES.contains(&element.name.as_str())
+        || element.name.starts_with(MOD_APPEND_PREFIX)
+        || element.name.starts_with(MOD_OVERWRITE_PREFIX)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FindKind {
+    Name,
+    Like,
+    WithChildLike,
+    Composite,
+}
+
+impl FindKind {
+    fn of(element: &Element) -> Option<FindKind> {
+        match element.name.as_str() {
+            "mod:findName" => Some(FindKind::Name),
+            "mod:findLike" => Some(FindKind::Like),
+            "mod:findWithChildLike" => Some(FindKind::WithChildLike),
+            "mod:findComposite" => Some(FindKind::Composite),
+            _ => None,
+        }
+    }
+}
+
+/// Criteria of a find tag.
+struct Find<'a> {
+    kind: FindKind,
+    type_name: Option<&'a str>,
+    name: Option<&'a str>,
+    child_type: Option<&'a str>,
+    selector: Option<&'a Element>,
+    par: Option<&'a Element>,
+    reverse: bool,
+    start: usize,
+    limit: Option<usize>,
+    panic: bool,
+}
+
+impl<'a> Find<'a> {
+    fn parse(element: &'a Element, kind: FindKind) -> Result<Find<'a>, XmlAppendError> {
+        let invalid = |message: &str| XmlAppendError::InvalidDirectiveError(format!("{}: {}", element.start_tag(), message));
+        let parse_bool = |name: &str, default: bool| match element.attribute(name) {
+            None => Ok(default),
+            Some("true") => Ok(true),
+            Some("false") => Ok(false),
+            Some(_) => Err(invalid(&format!("'{}' must be 'true' or 'false'", name))),
+        };
+
+        let start = match element.attribute("start") {
+            None => 0,
+            Some(value) => value.parse().map_err(|_| invalid("'start' must be a non-negative number"))?,
+        };
+        let limit = match element.attribute("limit") {
+            None if kind == FindKind::Name => Some(1),
+            None | Some("-1") => None,
+            Some(value) => Some(value.parse().map_err(|_| invalid("'limit' must be -1 or a non-negative number"))?),
+        };
+
+        let find = Find {
+            kind,
+            type_name: element.attribute("type"),
+            name: element.attribute("name"),
+            child_type: element.attribute("child-type"),
+            selector: element.child_elements().find(|child| child.name == "mod:selector"),
+            par: element.child_elements().find(|child| child.name == "mod:par"),
+            reverse: parse_bool("reverse", kind == FindKind::Name)?,
+            start,
+            limit,
+            panic: parse_bool("panic", false)?,
+        };
+
+        match kind {
+            FindKind::Name if find.name.is_none() => Err(invalid("missing 'name' attribute")),
+            FindKind::WithChildLike if find.child_type.is_none() => Err(invalid("missing 'child-type' attribute")),
+            FindKind::Composite if find.par.is_none() => Err(invalid("missing nested <mod:par> tag")),
+            _ => Ok(find),
+        }
+    }
+
+    fn matches(&self, element: &Element) -> bool {
+        if self.type_name.map(|type_name| element.name != type_name).unwrap_or(false) {
+            return false;
+        }
+
+        match self.kind {
+            FindKind::Name => element.attribute("name") == self.name,
+            FindKind::Like => self.selector_matches(element),
+            FindKind::WithChildLike => element.child_elements()
+                .filter(|child| Some(child.name.as_str()) == self.child_type)
+                .any(|child| self.selector_matches(child)),
+            FindKind::Composite => unreachable!("composite find tags are matched by their <mod:par>"),
+        }
+    }
+
+    /// Returns positions of the nodes in the `context` matched by this find tag, after applying
+    /// `reverse`, `start` and `limit`.
+    fn positions(&self, context: &[Node]) -> Result<Vec<usize>, XmlAppendError> {
+        let mut positions = match self.par {
+            Some(par) if self.kind == FindKind::Composite => par_positions(context, par)?,
+            _ => context.iter().enumerate()
+                .filter(|(_, node)| node.as_element().map(|element| self.matches(element)).unwrap_or(false))
+                .map(|(position, _)| position)
+                .collect::<Vec<usize>>(),
+        };
+        if self.reverse {
+            positions.reverse();
+        }
+
+        Ok(positions.into_iter()
+            .skip(self.start)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+
+    fn selector_matches(&self, element: &Element) -> bool {
+        let selector = match self.selector {
+            Some(selector) => selector,
+            None => return true,
+        };
+
+        let selector_text = selector.text();
+        selector.attributes.iter().all(|(name, value)| element.attribute(name) == Some(value))
+            && (selector_text.trim().is_empty() || selector_text.trim() == element.text().trim())
+    }
+}
+
+/// Returns positions of the nodes in the `context` matched by a `<mod:par>` tag, in document order.
+fn par_positions(context: &[Node], par: &Element) -> Result<Vec<usize>, XmlAppendError> {
+    let is_and = match par.attribute("op") {
+        Some("AND") => true,
+        Some("OR") => false,
+        _ => return Err(XmlAppendError::InvalidDirectiveError(format!("{}: 'op' must be 'AND' or 'OR'", par.start_tag()))),
+    };
+
+    let mut result: Option<BTreeSet<usize>> = None;
+    for child in par.child_elements() {
+        let positions = if child.name == "mod:par" {
+            par_positions(context, child)?
+        } else if let Some(kind) = FindKind::of(child) {
+            let find = Find::parse(child, kind)?;
+            let positions = find.positions(context)?;
+            if positions.is_empty() && find.panic {
+                return Err(XmlAppendError::UnmatchedSelectorError(child.start_tag()));
+            }
+            positions
+        } else {
+            return Err(XmlAppendError::InvalidDirectiveError(
+                format!("{} is not supported in {}", child.start_tag(), par.start_tag())
+            ));
+        };
+
+        let positions = positions.into_iter().collect::<BTreeSet<usize>>();
+        result = Some(match result {
+            None => positions,
+            Some(result) if is_and => result.intersection(&positions).copied().collect(),
+            Some(result) => result.union(&positions).copied().collect(),
+        });
+    }
+
+    Ok(result.unwrap_or_default().into_iter().collect())
+}
+
+fn apply_find(context: &mut Vec<Node>, find_element: &Element, report: &mut XmlPatchReport) -> Result<(), XmlAppendError> {
+    let find = Find::parse(find_element, FindKind::of(find_element).unwrap())?;
+    let positions = find.positions(context)?;
+
+    if positions.is_empty() {
+        if find.panic {
+            return Err(XmlAppendError::UnmatchedSelectorError(find_element.start_tag()));
+        }
+
+        report.unmatched_selectors.push(find_element.start_tag());
+        return Ok(());
+    }
+
+    let mut removed_positions = Vec::new();
+    for position in positions {
+        if let Node::Element(target) = &mut context[position] {
+            if apply_commands(target, find_element, report)? {
+                removed_positions.push(position);
+            }
+        }
+    }
+
+    // Remove from the back, so that positions of the remaining elements stay valid
+    removed_positions.sort_unstable();
+    for position in removed_positions.into_iter().rev() {
+        context.remove(position);
+        if position > 0 && context[position - 1].is_whitespace() {
+            context.remove(position - 1);
+        }
+    }
+
+    Ok(())
+}
+
+/// Applies the commands nested in the find tag to the matched `target`, and returns whether the
+/// target should be removed.
+fn apply_commands(target: &mut Element, find_element: &Element, report: &mut XmlPatchReport) -> Result<bool, XmlAppendError> {
+    let mut remove_target = false;
+    for command in find_element.child_elements() {
+        if let Some(name) = command.name.strip_prefix(MOD_APPEND_PREFIX) {
+            target.children.push(Node::Element(renamed(command, name)));
+            continue;
+        }
+
+        if let Some(name) = command.name.strip_prefix(MOD_OVERWRITE_PREFIX) {
+            let existing_child = target.children.iter_mut()
+                .find(|child| child.as_element().map(|element| element.name == name).unwrap_or(false));
+            match existing_child {
+                Some(child) => *child = Node::Element(renamed(command, name)),
+                None => target.children.push(Node::Element(renamed(command, name))),
+            }
+            continue;
+        }
+
+        match command.name.as_str() {
+            "mod:selector" | "mod:par" => {}
+            "mod:setAttributes" => {
+                for (name, value) in &command.attributes {
+                    target.set_attribute(name, value);
+                }
+            }
+            "mod:removeAttributes" => {
+                for (name, _) in &command.attributes {
+                    target.remove_attribute(name);
+                }
+            }
+            "mod:setValue" => target.children = vec![Node::Text(command.text())],
+            "mod:removeTag" => remove_target = true,
+            _ if FindKind::of(command).is_some() => apply_find(&mut target.children, command, report)?,
+            _ => {
+                return Err(XmlAppendError::InvalidDirectiveError(
+                    format!("{} is not supported in {}", command.start_tag(), find_element.start_tag())
+                ));
+            }
+        }
+    }
+
+    Ok(remove_target)
+}
+
+/// Copies the element under a different name, eg. to turn `<mod-append:name>` into `<name>`.
+fn renamed(element: &Element, name: &str) -> Element {
+    Element {
+        name: name.to_string(),
+        attributes: element.attributes.clone(),
+        children: element.children.clone(),
+        self_closing: element.self_closing,
+    }
+}
@@ -0,0 +1,355 @@
+// A small, tolerant XML model for FTL's data files, which are fragments rather than documents:
+// they may have several top-level elements, and mods use namespace prefixes (`mod:`) without
+// declaring them. Text and attribute values are kept exactly as written, entities included, so
+// that untouched parts of a file are written back unchanged.
+//
+// Vanilla files are parsed in recovery mode, which accepts the mistakes that Slipstream's sloppy
+// parser tolerates: an end tag closes the innermost open element regardless of its name, and end
+// tags without an open element are discarded. Comments containing `--` and attributes that are
+// not separated by whitespace are accepted in both modes.
+//
+// Nodes are written and dropped recursively, so elements may only be nested up to `MAX_DEPTH`
+// levels deep.
+
+const MAX_DEPTH: usize = 256;
+
+/// A node of an XML fragment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Node {
+    Element(Element),
+    Text(String),
+    /// Comments, CDATA sections, processing instructions and doctypes, kept verbatim.
+    Verbatim(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Element {
+    pub(super) name: String,
+    pub(super) attributes: Vec<(String, String)>,
+    pub(super) children: Vec<Node>,
+    /// Whether the element is written as an empty-element tag (`<name/>`) while it has no children.
+    pub(super) self_closing: bool,
+}
+
+/// A syntax error, with the 1-based line on which it was found.
+#[derive(Debug)]
+pub(super) struct XmlSyntaxError {
+    pub(super) line: usize,
+    pub(super) message: String,
+}
+
+impl Element {
+    pub(super) fn new<S: AsRef<str>>(name: S) -> Element {
+        Element {
+            name: name.as_ref().to_string(),
+            attributes: Vec::new(),
+            children: Vec::new(),
+            self_closing: true,
+        }
+    }
+
+    /// Returns the value of the attribute with the specified name.
+    pub(super) fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter()
+            .find(|(attribute_name, _)| attribute_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub(super) fn set_attribute(&mut self, name: &str, value: &str) {
+        match self.attributes.iter_mut().find(|(attribute_name, _)| attribute_name == name) {
+            Some((_, existing_value)) => *existing_value = value.to_string(),
+            None => self.attributes.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub(super) fn remove_attribute(&mut self, name: &str) {
+        self.attributes.retain(|(attribute_name, _)| attribute_name != name);
+    }
+
+    /// Returns the concatenated text directly contained in this element.
+    pub(super) fn text(&self) -> String {
+        self.children.iter()
+            .filter_map(|child| match child {
+                Node::Text(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub(super) fn child_elements(&self) -> impl Iterator<Item=&Element> {
+        self.children.iter().filter_map(Node::as_element)
+    }
+
+    /// Writes the start tag of this element, as it appears without its content.
+    pub(super) fn start_tag(&self) -> String {
+        let mut result = String::new();
+        write_start_tag(self, &mut result);
+        result.push('>');
+        result
+    }
+}
+
+impl Node {
+    pub(super) fn as_element(&self) -> Option<&Element> {
+        match self {
+            Node::Element(element) => Some(element),
+            _ => None,
+        }
+    }
+
+    pub(super) fn is_whitespace(&self) -> bool {
+        matches!(self, Node::Text(text) if text.trim().is_empty())
+    }
+}
+
+/// Parses an XML fragment into a list of nodes.
+pub(super) fn parse(text: &str) -> Result<Vec<Node>, XmlSyntaxError> {
+    Parser { text, position: 0, recover: false }.parse()
+}
+
+/// Parses an XML fragment into a list of nodes in recovery mode, see the module comment.
+pub(super) fn parse_recovering(text: &str) -> Result<Vec<Node>, XmlSyntaxError> {
+    Parser { text, position: 0, recover: true }.parse()
+}
+
+/// Writes a list of nodes back into XML text.
+pub(super) fn write(nodes: &[Node]) -> String {
+    let mut result = String::new();
+    for node in nodes {
+        write_node(node, &mut result);
+    }
+
+    result
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+    recover: bool,
+}
+
+impl<'a> Parser<'a> {
+    fn parse(mut self) -> Result<Vec<Node>, XmlSyntaxError> {
+        // Elements whose end tag has not been reached yet, with the nodes read so far at the top level
+        let mut open_elements: Vec<Element> = Vec::new();
+        let mut top_level_nodes: Vec<Node> = Vec::new();
+
+        while self.position < self.text.len() {
+            let node = if self.rest().starts_with("</") {
+                let name = self.read_end_tag()?;
+                let element = match open_elements.pop() {
+                    Some(element) if element.name == name || self.recover => element,
+                    Some(element) => return Err(self.error(format!("expected end tag of '{}', but found '{}'", element.name, name))),
+                    None if self.recover => continue,
+                    None => return Err(self.error(format!("unexpected end tag of '{}'", name))),
+                };
+                Node::Element(element)
+            } else if self.rest().starts_with("<!--") {
+                Node::Verbatim(self.read_until("-->")?.to_string())
+            } else if self.rest().starts_with("<![CDATA[") {
+                Node::Verbatim(self.read_until("]]>")?.to_string())
+            } else if self.rest().starts_with("<?") {
+                Node::Verbatim(self.read_until("?>")?.to_string())
+            } else if self.rest().starts_with("<!") {
+                Node::Verbatim(self.read_until(">")?.to_string())
+            } else if self.rest().starts_with('<') {
+                let element = self.read_start_tag()?;
+                if !element.self_closing {
+                    if open_elements.len() == MAX_DEPTH {
+                        return Err(self.error("elements are nested too deeply".to_string()));
+                    }
+                    open_elements.push(element);
+                    continue;
+                }
+                Node::Element(element)
+            } else {
+                let length = self.rest().find('<').unwrap_or(self.rest().len());
+                let text = &self.rest()[..length];
+                self.position += length;
+                Node::Text(text.to_string())
+            };
+
+            match open_elements.last_mut() {
+                Some(parent) => parent.children.push(node),
+                None => top_level_nodes.push(node),
+            }
+        }
+
+        match open_elements.last() {
+            Some(element) => Err(self.error(format!("missing end tag of '{}'", element.name))),
+            None => Ok(top_level_nodes),
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    /// Reads everything up to and including the specified terminator.
+    fn read_until(&mut self, terminator: &str) -> Result<&'a str, XmlSyntaxError> {
+        let length = self.rest().find(terminator)
+            .ok_or_else(|| self.error(format!("missing '{}'", terminator)))?;
+        let result = &self.rest()[..length + terminator.len()];
+        self.position += result.len();
+        Ok(result)
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn read_name(&mut self) -> Result<&'a str, XmlSyntaxError> {
+        let rest = self.rest();
+        let length = rest.find(|c: char| c.is_whitespace() || matches!(c, '/' | '>' | '=' | '<'))
+            .unwrap_or(rest.len());
+        if length == 0 {
+            return Err(self.error("expected a name".to_string()));
+        }
+
+        self.position += length;
+        Ok(&rest[..length])
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), XmlSyntaxError> {
+        if !self.rest().starts_with(expected) {
+            return Err(self.error(format!("expected '{}'", expected)));
+        }
+
+        self.position += expected.len();
+        Ok(())
+    }
+
+    /// Reads a start tag, and returns the element, which is marked as self-closing if the tag is an
+    /// empty-element tag.
+    fn read_start_tag(&mut self) -> Result<Element, XmlSyntaxError> {
+        self.expect("<")?;
+        let mut element = Element::new(self.read_name()?);
+
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.position += 2;
+                return Ok(element);
+            }
+            if self.rest().starts_with('>') {
+                self.position += 1;
+                element.self_closing = false;
+                return Ok(element);
+            }
+
+            let name = self.read_name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+
+            let quote = match self.rest().chars().next() {
+                Some(quote @ ('"' | '\'')) => quote,
+                _ => return Err(self.error(format!("expected quoted value of attribute '{}'", name))),
+            };
+            self.position += 1;
+            let value = self.read_until(&quote.to_string())?;
+            element.attributes.push((name.to_string(), value[..value.len() - 1].to_string()));
+        }
+    }
+
+    fn read_end_tag(&mut self) -> Result<&'a str, XmlSyntaxError> {
+        self.expect("</")?;
+        let name = self.read_name()?;
+        self.skip_whitespace();
+        self.expect(">")?;
+        Ok(name)
+    }
+
+    fn error(&self, message: String) -> XmlSyntaxError {
+        XmlSyntaxError {
+            line: self.text[..self.position].matches('\n').count() + 1,
+            message,
+        }
+    }
+}
+
+fn write_start_tag(element: &Element, output: &mut String) {
+    output.push('<');
+    output.push_str(&element.name);
+    for (name, value) in &element.attributes {
+        output.push(' ');
+        output.push_str(name);
+        output.push('=');
+        match (value.contains('"'), value.contains('\'')) {
+            (true, false) => {
+                output.push('\'');
+                output.push_str(value);
+                output.push('\'');
+            }
+            // Values are kept with their entities, so only the quotes have to be escaped
+            (true, true) => {
+                output.push('"');
+                output.push_str(&value.replace('"', "&quot;"));
+                output.push('"');
+            }
+            _ => {
+                output.push('"');
+                output.push_str(value);
+                output.push('"');
+            }
+        }
+    }
+}
+
+fn write_node(node: &Node, output: &mut String) {
+    match node {
+        Node::Element(element) => {
+            write_start_tag(element, output);
+            if element.children.is_empty() && element.self_closing {
+                output.push_str("/>");
+                return;
+            }
+
+            output.push('>');
+            for child in &element.children {
+                write_node(child, output);
+            }
+            output.push_str("</");
+            output.push_str(&element.name);
+            output.push('>');
+        }
+        Node::Text(text) | Node::Verbatim(text) => output.push_str(text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::slipstream::xml::{parse, write, Element, Node, MAX_DEPTH};
+
+    #[test]
+    fn write_keeps_form_of_empty_elements() {
+        let text = "<a></a><b/><c>\n</c>";
+
+        let nodes = parse(text).unwrap();
+
+        assert_eq!(text, write(&nodes));
+    }
+
+    #[test]
+    fn write_escapes_quotes_of_values_containing_both() {
+        let mut element = Element::new("a");
+        element.set_attribute("double", "say \"hi\"");
+        element.set_attribute("both", "it's \"hi\"");
+
+        let text = write(&[Node::Element(element)]);
+
+        assert_eq!(r#"<a double='say "hi"' both="it's &quot;hi&quot;"/>"#, text);
+        assert!(parse(&text).is_ok());
+    }
+
+    #[test]
+    fn parse_fails_when_elements_are_nested_too_deeply() {
+        let text = "<a>".repeat(MAX_DEPTH + 1) + &"</a>".repeat(MAX_DEPTH + 1);
+
+        let result = parse(&text);
+
+        assert_eq!("elements are nested too deeply", result.unwrap_err().message);
+        assert!(parse(&text[3..text.len() - 4]).is_ok());
+    }
+}
@@ -0,0 +1,116 @@
+use std::fmt::{Display, Formatter};
+
+use crate::{Manifest, ManifestVerification, Package};
+
+/// Games whose packages this crate can read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Game {
+    FasterThanLight,
+    IntoTheBreach,
+}
+
+impl Display for Game {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Game::FasterThanLight => write!(f, "Faster than Light"),
+            Game::IntoTheBreach => write!(f, "Into the Breach"),
+        }
+    }
+}
+
+/// A vanilla release of a game's package registered in a [`ReleaseDatabase`], identified by its
+/// [`Manifest`].
+#[derive(Debug)]
+pub struct KnownRelease {
+    game: Game,
+    version: String,
+    manifest: Manifest,
+}
+
+impl KnownRelease {
+    /// Returns the game this release belongs to.
+    pub fn game(&self) -> Game {
+        self.game
+    }
+
+    /// Returns the version of the game.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Returns the manifest of the release's package.
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+}
+
+/// Outcome of [`ReleaseDatabase::identify`].
+#[derive(Debug)]
+pub enum Identification<'a> {
+    /// The package is identical to a release in the database.
+    Vanilla(&'a KnownRelease),
+    /// The package doesn't match any release in the database. Lists entries deviating from the
+    /// `closest` release, ie. the one with the fewest deviations, if the database isn't empty.
+    Unknown {
+        closest: Option<(&'a KnownRelease, ManifestVerification)>,
+    },
+}
+
+/// A collection of [releases](KnownRelease), used to tell whether a package is vanilla, and
+/// which game version it comes from.
+///
+/// This crate ships no release data, so the database only knows the releases added to it with
+/// [`add_release`](ReleaseDatabase::add_release).
+#[derive(Debug, Default)]
+pub struct ReleaseDatabase {
+    releases: Vec<KnownRelease>,
+}
+
+impl ReleaseDatabase {
+    /// Creates a new empty database.
+    pub fn new() -> ReleaseDatabase {
+        ReleaseDatabase::default()
+    }
+
+    /// Adds a release to this database.
+    ///
+    /// Manifests of releases can be generated from pristine installs with
+    /// `cargo run --example manifest -- <package file>`. Since manifests can only be compared using
+    /// the same hash algorithm, they have to be generated by a build with the same features.
+    pub fn add_release<S: AsRef<str>>(&mut self, game: Game, version: S, manifest: Manifest) {
+        self.releases.push(KnownRelease {
+            game,
+            version: version.as_ref().to_string(),
+            manifest,
+        });
+    }
+
+    /// Returns all releases in this database.
+    pub fn releases(&self) -> &[KnownRelease] {
+        &self.releases
+    }
+
+    /// Compares the specified [`Package`] against all releases in this database.
+    ///
+    /// The package's content is hashed only once, regardless of the number of releases.
+    pub fn identify(&self, package: &Package) -> Result<Identification<'_>, std::io::Error> {
+        let manifest = Manifest::from_package(package)?;
+
+        let mut closest: Option<(&KnownRelease, ManifestVerification)> = None;
+        for release in self.releases.iter() {
+            let verification = release.manifest.compare(&manifest);
+            if verification.is_ok() {
+                return Ok(Identification::Vanilla(release));
+            }
+
+            let is_closer = closest.as_ref()
+                .map(|(_, closest)| verification.deviation_count() < closest.deviation_count())
+                .unwrap_or(true);
+            if is_closer {
+                closest = Some((release, verification));
+            }
+        }
+
+        Ok(Identification::Unknown { closest })
+    }
+}
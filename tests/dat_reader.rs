@@ -1,6 +1,9 @@
 #[cfg(test)]
 mod test_dat_reader {
-    use ftldat::Package;
+    use std::fs::File;
+    use std::io::{BufReader, Cursor};
+
+    use ftldat::{DatReader, FileReadStrategy, Package, PackageReader, ReadStrategy};
 
     const SOURCE_PATH: &str = "./tests-resources/test.dat";
 
@@ -31,4 +34,195 @@ mod test_dat_reader {
         assert_eq!("test002", contents[1]);
         assert_eq!("test003", contents[2]);
     }
+
+    fn assert_is_test_package(package: &Package) {
+        assert_eq!(vec!["test1.txt", "test2.txt", "test3.txt"], package.inner_paths());
+        assert_eq!("test001".as_bytes(), package.content_by_path("test1.txt").unwrap());
+        assert_eq!("test002".as_bytes(), package.content_by_path("test2.txt").unwrap());
+        assert_eq!("test003".as_bytes(), package.content_by_path("test3.txt").unwrap());
+    }
+
+    #[test]
+    fn reader_should_read_package_from_bytes() {
+        let bytes = std::fs::read(SOURCE_PATH).unwrap();
+
+        let result = Package::from_bytes_dat(bytes);
+
+        assert_is_test_package(&result.unwrap());
+    }
+
+    #[test]
+    fn reader_should_read_package_from_input_lazily() {
+        let input = Cursor::new(std::fs::read(SOURCE_PATH).unwrap());
+
+        let result = Package::from_input_dat(input, ReadStrategy::Lazy);
+
+        assert_is_test_package(&result.unwrap());
+    }
+
+    #[test]
+    fn reader_should_read_package_from_input_eagerly() {
+        let input = BufReader::new(File::open(SOURCE_PATH).unwrap());
+
+        let result = Package::from_input_dat(input, ReadStrategy::Eager);
+
+        assert_is_test_package(&result.unwrap());
+    }
+
+    #[test]
+    fn reader_should_fail_when_entry_lies_outside_of_truncated_package() {
+        let mut bytes = std::fs::read(SOURCE_PATH).unwrap();
+        bytes.truncate(bytes.len() - 1);
+
+        let result = Package::from_bytes_dat(bytes);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn reader_should_read_package_with_every_file_strategy() {
+        for strategy in [FileReadStrategy::MemoryMap, FileReadStrategy::PositionedRead, FileReadStrategy::Load] {
+            let result = Package::from_path_with_strategy(SOURCE_PATH, DatReader(), strategy);
+
+            assert_is_test_package(&result.unwrap());
+        }
+    }
+
+    #[test]
+    fn positioned_read_should_fail_with_io_error_when_file_is_truncated() {
+        // Prepare
+        let tmp_file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::copy(SOURCE_PATH, tmp_file.path()).unwrap();
+        let package = Package::from_path_with_strategy(
+            tmp_file.path(), DatReader(), FileReadStrategy::PositionedRead,
+        ).unwrap();
+
+        // Execute
+        tmp_file.as_file().set_len(20).unwrap();
+        let result = package.entry_by_path("test3.txt").unwrap().content();
+
+        // Check
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn loaded_package_should_not_be_affected_when_file_is_truncated() {
+        // Prepare
+        let tmp_file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::copy(SOURCE_PATH, tmp_file.path()).unwrap();
+        let package = Package::from_path_with_strategy(
+            tmp_file.path(), DatReader(), FileReadStrategy::Load,
+        ).unwrap();
+
+        // Execute
+        tmp_file.as_file().set_len(0).unwrap();
+
+        // Check
+        assert_is_test_package(&package);
+    }
+
+    #[test]
+    fn salvage_should_recover_intact_package_from_index() {
+        // Execute
+        let (package, report) = Package::salvage_path_dat(SOURCE_PATH).unwrap();
+
+        // Check
+        assert_is_test_package(&package);
+        assert_eq!(3, report.recovered_from_index());
+        assert_eq!(0, report.recovered_by_scan());
+        assert!(report.unaccounted_ranges().is_empty());
+    }
+
+    #[test]
+    fn salvage_should_recover_complete_entries_of_truncated_package() {
+        // Prepare
+        let mut bytes = std::fs::read(SOURCE_PATH).unwrap();
+        bytes.truncate(85);
+
+        // Execute
+        let (package, report) = Package::salvage_bytes_dat(bytes);
+
+        // Check
+        assert_eq!(vec!["test1.txt", "test2.txt"], package.inner_paths());
+        assert_eq!("test002".as_bytes(), package.content_by_path("test2.txt").unwrap());
+        assert_eq!(Some(&(64..85)), report.unaccounted_ranges().first());
+        assert_eq!(1, report.unaccounted_ranges().len());
+        assert_eq!(21, report.unaccounted_size());
+    }
+
+    #[test]
+    fn salvage_should_scan_for_entries_when_offsets_are_damaged() {
+        // Prepare
+        let mut bytes = std::fs::read(SOURCE_PATH).unwrap();
+        bytes[4..16].fill(0);
+
+        // Execute
+        let (package, report) = Package::salvage_bytes_dat(bytes);
+
+        // Check
+        assert_is_test_package(&package);
+        assert_eq!(0, report.recovered_from_index());
+        assert_eq!(3, report.recovered_by_scan());
+        assert!(report.unaccounted_ranges().is_empty());
+    }
+
+    #[test]
+    fn salvage_should_scan_whole_package_when_entry_count_is_damaged() {
+        // Prepare
+        let mut bytes = std::fs::read(SOURCE_PATH).unwrap();
+        bytes[0..4].fill(0xFF);
+
+        // Execute
+        let (package, report) = Package::salvage_bytes_dat(bytes);
+
+        // Check
+        assert_is_test_package(&package);
+        assert_eq!(3, report.recovered_by_scan());
+        assert_eq!(Some(&(0..16)), report.unaccounted_ranges().first());
+        assert_eq!(1, report.unaccounted_ranges().len());
+    }
+
+    #[test]
+    fn salvage_should_report_damaged_entry() {
+        // Prepare
+        let mut bytes = std::fs::read(SOURCE_PATH).unwrap();
+        bytes[40..44].fill(0xFF);
+
+        // Execute
+        let (package, report) = Package::salvage_bytes_dat(bytes);
+
+        // Check
+        assert_eq!(vec!["test1.txt", "test3.txt"], package.inner_paths());
+        assert_eq!(2, report.recovered_from_index());
+        assert_eq!(Some(&(40..64)), report.unaccounted_ranges().first());
+        assert_eq!(1, report.unaccounted_ranges().len());
+    }
+
+    #[test]
+    fn reader_should_fail_without_allocating_for_damaged_counts() {
+        // Prepare
+        let bytes = std::fs::read(SOURCE_PATH).unwrap();
+        let mut damaged_entry_count = bytes.clone();
+        damaged_entry_count[0..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        let first_entry_offset = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+        let mut damaged_path_length = bytes.clone();
+        damaged_path_length[first_entry_offset + 4..first_entry_offset + 8].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        // Execute
+        let entry_count_result = Package::from_bytes_dat(damaged_entry_count);
+        let path_length_result = Package::from_bytes_dat(damaged_path_length);
+
+        // Check
+        assert!(entry_count_result.is_err());
+        assert!(path_length_result.is_err());
+    }
+
+    #[test]
+    fn reader_should_be_usable_as_trait_object() {
+        let reader: &dyn PackageReader = &DatReader();
+
+        let result = reader.read_package_from_bytes(std::fs::read(SOURCE_PATH).unwrap());
+
+        assert_is_test_package(&result.unwrap());
+    }
 }
@@ -0,0 +1,279 @@
+#[cfg(all(test, feature = "ffi"))]
+mod test_ffi {
+    use std::ffi::{c_char, CStr, CString};
+    use std::ptr::{null, null_mut};
+
+    // Link the library, whose functions are declared below as they appear in `include/ftldat.h`
+    use ftldat as _;
+
+    const SOURCE_PATH: &str = "./tests-resources/test.dat";
+
+    const FTLDAT_RESULT_OK: i32 = 0;
+    const FTLDAT_RESULT_INVALID_ARGUMENT: i32 = 1;
+    const FTLDAT_RESULT_READ_ERROR: i32 = 2;
+    const FTLDAT_RESULT_WRITE_ERROR: i32 = 3;
+    const FTLDAT_RESULT_NOT_FOUND: i32 = 4;
+    const FTLDAT_RESULT_ALREADY_EXISTS: i32 = 5;
+    const FTLDAT_RESULT_BUFFER_TOO_SMALL: i32 = 6;
+
+    #[repr(C)]
+    struct FtldatPackage {
+        _private: [u8; 0],
+    }
+
+    extern "C" {
+        fn ftldat_last_error_message(buffer: *mut c_char, buffer_size: usize) -> usize;
+        fn ftldat_package_new() -> *mut FtldatPackage;
+        fn ftldat_package_free(package: *mut FtldatPackage);
+        fn ftldat_package_open_dat(source_path: *const c_char, out_package: *mut *mut FtldatPackage) -> i32;
+        fn ftldat_package_open_pkg(source_path: *const c_char, out_package: *mut *mut FtldatPackage) -> i32;
+        fn ftldat_package_save_dat(package: *mut FtldatPackage, destination_path: *const c_char) -> i32;
+        fn ftldat_package_save_pkg(package: *mut FtldatPackage, destination_path: *const c_char) -> i32;
+        fn ftldat_package_entry_count(package: *const FtldatPackage) -> usize;
+        fn ftldat_package_entry_path(package: *const FtldatPackage, index: usize, buffer: *mut c_char, buffer_size: usize, out_length: *mut usize) -> i32;
+        fn ftldat_package_entry_exists(package: *const FtldatPackage, inner_path: *const c_char) -> bool;
+        fn ftldat_package_content_size(package: *const FtldatPackage, inner_path: *const c_char, out_size: *mut u64) -> i32;
+        fn ftldat_package_read_content(package: *const FtldatPackage, inner_path: *const c_char, buffer: *mut u8, buffer_size: usize, out_length: *mut usize) -> i32;
+        fn ftldat_package_put_entry(package: *mut FtldatPackage, inner_path: *const c_char, content: *const u8, content_length: usize) -> i32;
+        fn ftldat_package_add_entry(package: *mut FtldatPackage, inner_path: *const c_char, content: *const u8, content_length: usize) -> i32;
+        fn ftldat_package_remove_entry(package: *mut FtldatPackage, inner_path: *const c_char) -> i32;
+    }
+
+    fn c_string(value: &str) -> CString {
+        CString::new(value).unwrap()
+    }
+
+    fn open_dat(source_path: &str) -> *mut FtldatPackage {
+        let mut package = null_mut();
+        let result = unsafe { ftldat_package_open_dat(c_string(source_path).as_ptr(), &mut package) };
+        assert_eq!(FTLDAT_RESULT_OK, result);
+        package
+    }
+
+    fn last_error_message() -> String {
+        let mut buffer = [0 as c_char; 256];
+        unsafe {
+            ftldat_last_error_message(buffer.as_mut_ptr(), buffer.len());
+            CStr::from_ptr(buffer.as_ptr()).to_str().unwrap().to_string()
+        }
+    }
+
+    fn entry_paths(package: *const FtldatPackage) -> Vec<String> {
+        let mut buffer = [0 as c_char; 64];
+        (0..unsafe { ftldat_package_entry_count(package) })
+            .map(|index| unsafe {
+                let result = ftldat_package_entry_path(package, index, buffer.as_mut_ptr(), buffer.len(), null_mut());
+                assert_eq!(FTLDAT_RESULT_OK, result);
+                CStr::from_ptr(buffer.as_ptr()).to_str().unwrap().to_string()
+            })
+            .collect()
+    }
+
+    fn read_content(package: *const FtldatPackage, inner_path: &str) -> Vec<u8> {
+        let inner_path = c_string(inner_path);
+        let mut length = 0;
+        unsafe {
+            let result = ftldat_package_read_content(package, inner_path.as_ptr(), null_mut(), 0, &mut length);
+            assert_eq!(FTLDAT_RESULT_BUFFER_TOO_SMALL, result);
+
+            let mut buffer = vec![0u8; length];
+            let result = ftldat_package_read_content(package, inner_path.as_ptr(), buffer.as_mut_ptr(), buffer.len(), &mut length);
+            assert_eq!(FTLDAT_RESULT_OK, result);
+            buffer
+        }
+    }
+
+    #[test]
+    fn open_dat_should_list_entries() {
+        // Execute
+        let package = open_dat(SOURCE_PATH);
+
+        // Check
+        assert_eq!(vec!["test1.txt", "test2.txt", "test3.txt"], entry_paths(package));
+        unsafe {
+            assert!(ftldat_package_entry_exists(package, c_string("test2.txt").as_ptr()));
+            assert!(!ftldat_package_entry_exists(package, c_string("test4.txt").as_ptr()));
+            ftldat_package_free(package);
+        }
+    }
+
+    #[test]
+    fn open_should_report_error_with_message() {
+        // Prepare
+        let mut package = null_mut();
+
+        // Execute
+        let result = unsafe { ftldat_package_open_dat(c_string("./tests-resources/missing.dat").as_ptr(), &mut package) };
+
+        // Check
+        assert_eq!(FTLDAT_RESULT_READ_ERROR, result);
+        assert!(package.is_null());
+        assert!(!last_error_message().is_empty());
+    }
+
+    #[test]
+    fn read_content_should_fill_caller_buffer() {
+        // Prepare
+        let package = open_dat(SOURCE_PATH);
+        let mut size = 0;
+
+        // Execute
+        let size_result = unsafe { ftldat_package_content_size(package, c_string("test3.txt").as_ptr(), &mut size) };
+        let content = read_content(package, "test3.txt");
+
+        // Check
+        assert_eq!(FTLDAT_RESULT_OK, size_result);
+        assert_eq!(7, size);
+        assert_eq!(b"test003".to_vec(), content);
+        unsafe { ftldat_package_free(package) };
+    }
+
+    #[test]
+    fn entry_path_should_report_required_length() {
+        // Prepare
+        let package = open_dat(SOURCE_PATH);
+        let mut buffer = [0 as c_char; 4];
+        let mut length = 0;
+
+        // Execute
+        let result = unsafe { ftldat_package_entry_path(package, 0, buffer.as_mut_ptr(), buffer.len(), &mut length) };
+
+        // Check
+        assert_eq!(FTLDAT_RESULT_BUFFER_TOO_SMALL, result);
+        assert_eq!(9, length);
+        assert_eq!(FTLDAT_RESULT_NOT_FOUND, unsafe { ftldat_package_entry_path(package, 3, buffer.as_mut_ptr(), buffer.len(), &mut length) });
+        unsafe { ftldat_package_free(package) };
+    }
+
+    #[test]
+    fn entries_should_be_editable() {
+        // Prepare
+        let package = unsafe { ftldat_package_new() };
+        let content = b"test004";
+
+        // Execute
+        unsafe {
+            assert_eq!(FTLDAT_RESULT_OK, ftldat_package_put_entry(package, c_string("test4.txt").as_ptr(), content.as_ptr(), content.len()));
+            assert_eq!(FTLDAT_RESULT_OK, ftldat_package_add_entry(package, c_string("empty.txt").as_ptr(), null(), 0));
+            assert_eq!(FTLDAT_RESULT_ALREADY_EXISTS, ftldat_package_add_entry(package, c_string("test4.txt").as_ptr(), content.as_ptr(), content.len()));
+            assert_eq!("inner path 'test4.txt' already exists within the package", last_error_message());
+
+            assert_eq!(FTLDAT_RESULT_OK, ftldat_package_remove_entry(package, c_string("empty.txt").as_ptr()));
+            assert_eq!(FTLDAT_RESULT_NOT_FOUND, ftldat_package_remove_entry(package, c_string("empty.txt").as_ptr()));
+        }
+
+        // Check
+        assert_eq!(vec!["test4.txt"], entry_paths(package));
+        assert_eq!(b"test004".to_vec(), read_content(package, "test4.txt"));
+        unsafe { ftldat_package_free(package) };
+    }
+
+    #[test]
+    fn null_arguments_should_be_rejected() {
+        // Execute
+        let result = unsafe { ftldat_package_remove_entry(null_mut(), c_string("test1.txt").as_ptr()) };
+
+        // Check
+        assert_eq!(FTLDAT_RESULT_INVALID_ARGUMENT, result);
+        assert_eq!("argument 'package' is NULL", last_error_message());
+        assert_eq!(0, unsafe { ftldat_package_entry_count(null()) });
+    }
+
+    #[test]
+    fn save_should_overwrite_source_file() {
+        // Prepare
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let package_path = tmp_dir.path().join("test.dat");
+        std::fs::copy(SOURCE_PATH, &package_path).unwrap();
+        let package = open_dat(package_path.to_str().unwrap());
+        let content = b"test004";
+
+        // Execute
+        let result = unsafe {
+            ftldat_package_put_entry(package, c_string("test4.txt").as_ptr(), content.as_ptr(), content.len());
+            ftldat_package_save_dat(package, c_string(package_path.to_str().unwrap()).as_ptr())
+        };
+
+        // Check
+        assert_eq!(FTLDAT_RESULT_OK, result);
+        assert_eq!(vec!["test1.txt", "test2.txt", "test3.txt", "test4.txt"], entry_paths(package));
+        unsafe { ftldat_package_free(package) };
+
+        let reopened = open_dat(package_path.to_str().unwrap());
+        assert_eq!(b"test004".to_vec(), read_content(reopened, "test4.txt"));
+        unsafe { ftldat_package_free(reopened) };
+    }
+
+    #[test]
+    fn save_pkg_should_survive_round_trip() {
+        // Prepare
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let destination_path = c_string(tmp_dir.path().join("test.pkg").to_str().unwrap());
+        let package = open_dat(SOURCE_PATH);
+
+        // Execute
+        let result = unsafe { ftldat_package_save_pkg(package, destination_path.as_ptr()) };
+
+        // Check
+        assert_eq!(FTLDAT_RESULT_OK, result);
+        let mut reopened = null_mut();
+        assert_eq!(FTLDAT_RESULT_OK, unsafe { ftldat_package_open_pkg(destination_path.as_ptr(), &mut reopened) });
+        assert_eq!(vec!["test1.txt", "test2.txt", "test3.txt"], entry_paths(reopened));
+        assert_eq!(b"test001".to_vec(), read_content(reopened, "test1.txt"));
+        unsafe {
+            ftldat_package_free(package);
+            ftldat_package_free(reopened);
+        }
+    }
+
+    #[test]
+    fn read_content_should_accept_null_buffer_for_empty_entry() {
+        // Prepare
+        let package = unsafe { ftldat_package_new() };
+        let inner_path = c_string("empty.txt");
+        let mut length = 1;
+
+        // Execute
+        let result = unsafe {
+            ftldat_package_put_entry(package, inner_path.as_ptr(), null(), 0);
+            ftldat_package_read_content(package, inner_path.as_ptr(), null_mut(), 0, &mut length)
+        };
+
+        // Check
+        assert_eq!(FTLDAT_RESULT_OK, result);
+        assert_eq!(0, length);
+        unsafe { ftldat_package_free(package) };
+    }
+
+    #[test]
+    fn save_should_keep_package_when_writing_fails() {
+        // Prepare
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let destination_path = c_string(tmp_dir.path().join("missing/test.dat").to_str().unwrap());
+        let package = open_dat(SOURCE_PATH);
+        let content = b"test004";
+
+        // Execute
+        let result = unsafe {
+            ftldat_package_put_entry(package, c_string("test4.txt").as_ptr(), content.as_ptr(), content.len());
+            ftldat_package_save_dat(package, destination_path.as_ptr())
+        };
+
+        // Check
+        assert_eq!(FTLDAT_RESULT_WRITE_ERROR, result);
+        assert_eq!(vec!["test1.txt", "test2.txt", "test3.txt", "test4.txt"], entry_paths(package));
+        assert_eq!(b"test004".to_vec(), read_content(package, "test4.txt"));
+        unsafe { ftldat_package_free(package) };
+    }
+
+    #[test]
+    fn header_should_match_generated_header() {
+        // Prepare
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/ftldat.h"));
+        let checked_in = include_str!("../include/ftldat.h");
+
+        // Check
+        assert!(generated.lines().eq(checked_in.lines()),
+                "include/ftldat.h is outdated, replace it with {}/ftldat.h", env!("OUT_DIR"));
+    }
+}
@@ -0,0 +1,242 @@
+#[cfg(test)]
+mod test_install_journal {
+    use ftldat::{ContentHash, InstallJournal, JournalChangeKind, Package, PackageEntry};
+    use ftldat::error::{JournalReadError, UninstallError};
+
+    const SOURCE_PATH: &str = "./tests-resources/test.dat";
+
+    fn install_first_mod(package: &mut Package, journal: &mut InstallJournal) {
+        let mut transaction = package.begin_install(journal, "first").unwrap();
+        transaction.put_entry(PackageEntry::from_string("test1.txt", "test001 was modded")).unwrap();
+        transaction.put_entry(PackageEntry::from_string("test4.txt", "test004")).unwrap();
+        transaction.remove_entry("test3.txt").unwrap();
+        transaction.commit().unwrap();
+    }
+
+    #[test]
+    fn commit_should_record_changes_with_replaced_hashes() {
+        // Prepare
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+        let mut journal = InstallJournal::new();
+
+        // Execute
+        install_first_mod(&mut package, &mut journal);
+
+        // Check
+        let changes = journal.changes("first").unwrap();
+        assert_eq!(3, changes.len());
+
+        assert_eq!("test1.txt", changes[0].inner_path());
+        assert_eq!(JournalChangeKind::Overwritten, changes[0].kind());
+        assert_eq!(Some(&ContentHash::of(b"test001")), changes[0].replaced_hash());
+        assert_eq!(Some(&ContentHash::of(b"test001 was modded")), changes[0].installed_hash());
+
+        assert_eq!(JournalChangeKind::Added, changes[1].kind());
+        assert_eq!(None, changes[1].replaced_hash());
+
+        assert_eq!(JournalChangeKind::Removed, changes[2].kind());
+        assert_eq!(Some(&ContentHash::of(b"test003")), changes[2].replaced_hash());
+        assert_eq!(None, changes[2].installed_hash());
+    }
+
+    #[test]
+    fn commit_should_skip_paths_restored_to_original_content() {
+        // Prepare
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+        let mut journal = InstallJournal::new();
+
+        // Execute
+        let mut transaction = package.begin_install(&mut journal, "noop").unwrap();
+        transaction.put_entry(PackageEntry::from_string("test1.txt", "test001")).unwrap();
+        transaction.put_entry(PackageEntry::from_string("test4.txt", "test004")).unwrap();
+        transaction.remove_entry("test4.txt").unwrap();
+        transaction.commit().unwrap();
+
+        // Check
+        assert!(journal.is_installed("noop"));
+        assert!(journal.changes("noop").unwrap().is_empty());
+    }
+
+    #[test]
+    fn dropped_transaction_should_roll_back_changes() {
+        // Prepare
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+        let mut journal = InstallJournal::new();
+
+        // Execute
+        {
+            let mut transaction = package.begin_install(&mut journal, "aborted").unwrap();
+            transaction.put_entry(PackageEntry::from_string("test1.txt", "test001 was modded")).unwrap();
+            transaction.put_entry(PackageEntry::from_string("test4.txt", "test004")).unwrap();
+            transaction.rollback();
+        }
+
+        // Check
+        assert!(!journal.is_installed("aborted"));
+        assert_eq!(vec!["test1.txt", "test2.txt", "test3.txt"], package.inner_paths());
+        assert_eq!(b"test001".to_vec(), package.content_by_path("test1.txt").unwrap());
+    }
+
+    #[test]
+    fn begin_install_should_refuse_duplicate_mod_name() {
+        // Prepare
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+        let mut journal = InstallJournal::new();
+        install_first_mod(&mut package, &mut journal);
+
+        // Execute
+        let result = package.begin_install(&mut journal, "first");
+
+        // Check
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn uninstall_should_revert_changes() {
+        // Prepare
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+        let mut journal = InstallJournal::new();
+        install_first_mod(&mut package, &mut journal);
+
+        // Execute
+        let result = package.uninstall_mod(&mut journal, "first");
+
+        // Check
+        assert!(result.is_ok());
+        assert!(!journal.is_installed("first"));
+        assert_eq!(vec!["test1.txt", "test2.txt", "test3.txt"], package.inner_paths());
+        assert_eq!(b"test001".to_vec(), package.content_by_path("test1.txt").unwrap());
+        assert_eq!(b"test003".to_vec(), package.content_by_path("test3.txt").unwrap());
+    }
+
+    #[test]
+    fn uninstall_should_refuse_when_later_mod_touched_same_paths() {
+        // Prepare
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+        let mut journal = InstallJournal::new();
+        install_first_mod(&mut package, &mut journal);
+
+        let mut transaction = package.begin_install(&mut journal, "second").unwrap();
+        transaction.put_entry(PackageEntry::from_string("test4.txt", "test004 was modded again")).unwrap();
+        transaction.put_entry(PackageEntry::from_string("test2.txt", "test002 was modded")).unwrap();
+        transaction.commit().unwrap();
+
+        // Execute
+        let result = package.uninstall_mod(&mut journal, "first");
+
+        // Check
+        match result {
+            Err(UninstallError::LaterModConflictError { conflicts, .. }) => {
+                assert_eq!(1, conflicts.len());
+                assert_eq!("test4.txt", conflicts[0].inner_path());
+                assert_eq!("second", conflicts[0].mod_name());
+            }
+            other => panic!("expected a conflict, got {:?}", other),
+        }
+        assert!(journal.is_installed("first"));
+        assert_eq!(b"test004 was modded again".to_vec(), package.content_by_path("test4.txt").unwrap());
+    }
+
+    #[test]
+    fn uninstall_should_allow_reverting_in_reverse_order() {
+        // Prepare
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+        let mut journal = InstallJournal::new();
+        install_first_mod(&mut package, &mut journal);
+
+        let mut transaction = package.begin_install(&mut journal, "second").unwrap();
+        transaction.put_entry(PackageEntry::from_string("test4.txt", "test004 was modded again")).unwrap();
+        transaction.commit().unwrap();
+
+        // Execute
+        package.uninstall_mod(&mut journal, "second").unwrap();
+        let result = package.uninstall_mod(&mut journal, "first");
+
+        // Check
+        assert!(result.is_ok());
+        assert_eq!(0, journal.installed_mods().count());
+        assert!(!package.entry_exists("test4.txt"));
+    }
+
+    #[test]
+    fn uninstall_should_refuse_entries_changed_outside_of_journal() {
+        // Prepare
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+        let mut journal = InstallJournal::new();
+        install_first_mod(&mut package, &mut journal);
+        package.put_entry(PackageEntry::from_string("test1.txt", "test001 was edited by hand"));
+
+        // Execute
+        let result = package.uninstall_mod(&mut journal, "first");
+
+        // Check
+        assert!(matches!(result, Err(UninstallError::EntryModifiedError(path)) if path == "test1.txt"));
+        assert!(journal.is_installed("first"));
+    }
+
+    #[test]
+    fn journal_should_survive_round_trip_through_sidecar_file() {
+        // Prepare
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let package_path = tmp_dir.path().join("resource.dat");
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+        let mut journal = InstallJournal::new();
+        install_first_mod(&mut package, &mut journal);
+
+        // Execute
+        journal.to_sidecar(&package_path).unwrap();
+        let mut result = InstallJournal::from_sidecar(&package_path).unwrap();
+
+        // Check
+        assert!(tmp_dir.path().join("resource.dat.journal").exists());
+        assert_eq!(vec!["first"], result.installed_mods().collect::<Vec<_>>());
+        assert!(package.uninstall_mod(&mut result, "first").is_ok());
+        assert_eq!(b"test003".to_vec(), package.content_by_path("test3.txt").unwrap());
+    }
+
+    #[test]
+    fn from_sidecar_should_return_empty_journal_if_missing() {
+        // Prepare
+        let tmp_dir = tempfile::tempdir().unwrap();
+
+        // Execute
+        let result = InstallJournal::from_sidecar(tmp_dir.path().join("resource.dat")).unwrap();
+
+        // Check
+        assert_eq!(0, result.installed_mods().count());
+    }
+
+    #[test]
+    fn from_input_should_reject_corrupt_replaced_content() {
+        // Prepare
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+        let mut journal = InstallJournal::new();
+        install_first_mod(&mut package, &mut journal);
+        let mut bytes = Vec::new();
+        journal.to_output(&mut bytes).unwrap();
+
+        let position = bytes.windows(7).position(|window| window == b"test001").unwrap();
+        bytes[position + 6] = b'X';
+
+        // Execute
+        let result = InstallJournal::from_input(bytes.as_slice());
+
+        // Check
+        assert!(matches!(result, Err(JournalReadError::CorruptContentError(path)) if path == "test1.txt"));
+    }
+
+    #[test]
+    fn from_input_should_fail_for_truncated_journal_with_huge_counts() {
+        // Prepare
+        let mut bytes = Vec::new();
+        InstallJournal::new().to_output(&mut bytes).unwrap();
+        let mod_count_position = bytes.len() - 4;
+        bytes[mod_count_position..].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        // Execute
+        let result = InstallJournal::from_input(bytes.as_slice());
+
+        // Check
+        assert!(matches!(result, Err(JournalReadError::IoError(_))));
+    }
+}
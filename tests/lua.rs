@@ -0,0 +1,209 @@
+#[cfg(all(test, feature = "lua", not(feature = "lua-module")))]
+mod test_lua {
+    use mlua::{Lua, Table};
+
+    const SOURCE_PATH: &str = "./tests-resources/test.dat";
+
+    fn create_lua() -> Lua {
+        let lua = Lua::new();
+        {
+            let preload: Table = lua.globals()
+                .get::<_, Table>("package").unwrap()
+                .get("preload").unwrap();
+            preload.set("ftldat", lua.create_function(|lua, ()| ftldat::lua::create_module(lua)).unwrap()).unwrap();
+            lua.globals().set("SOURCE_PATH", SOURCE_PATH).unwrap();
+        }
+        lua
+    }
+
+    #[test]
+    fn require_should_load_module() {
+        // Prepare
+        let lua = create_lua();
+
+        // Execute
+        let result: String = lua.load(r#"
+            local ftldat = require("ftldat")
+            return ftldat._VERSION
+        "#).eval().unwrap();
+
+        // Check
+        assert_eq!(env!("CARGO_PKG_VERSION"), result);
+    }
+
+    #[test]
+    fn package_should_be_readable_from_lua() {
+        // Prepare
+        let lua = create_lua();
+
+        // Execute
+        let result: (String, usize, String, bool) = lua.load(r#"
+            local ftldat = require("ftldat")
+            local package = assert(ftldat.from_path_dat(SOURCE_PATH))
+            return table.concat(package:inner_paths(), ","), #package,
+                package:content_by_path("test2.txt"), package:entry_exists("test4.txt")
+        "#).eval().unwrap();
+
+        // Check
+        assert_eq!(("test1.txt,test2.txt,test3.txt".to_string(), 3, "test002".to_string(), false), result);
+    }
+
+    #[test]
+    fn failures_should_return_nil_and_message() {
+        // Prepare
+        let lua = create_lua();
+
+        // Execute
+        let result: (bool, String, bool, String) = lua.load(r#"
+            local ftldat = require("ftldat")
+            local missing, read_error = ftldat.from_path_dat("./tests-resources/missing.dat")
+            local package = assert(ftldat.from_path_dat(SOURCE_PATH))
+            local content, content_error = package:content_by_path("test4.txt")
+            return missing == nil, read_error, content == nil, content_error
+        "#).eval().unwrap();
+
+        // Check
+        assert!(result.0);
+        assert!(!result.1.is_empty());
+        assert!(result.2);
+        assert_eq!("inner path 'test4.txt' does not exist within the package", result.3);
+    }
+
+    #[test]
+    fn entries_should_be_editable_from_lua() {
+        // Prepare
+        let lua = create_lua();
+
+        // Execute
+        let result: (String, bool, String, String, usize) = lua.load(r#"
+            local ftldat = require("ftldat")
+            local package = assert(ftldat.from_path_dat(SOURCE_PATH))
+            package:put_entry("test4.txt", "test004")
+            package:remove_entry("test1.txt")
+            assert(package:rename_entry("test2.txt", "renamed.txt"))
+            local added, add_error = package:add_entry("test3.txt", "test00X")
+
+            local entry = package:entry_by_path("test4.txt")
+            return table.concat(package:inner_paths(), ","), added == nil, add_error,
+                entry:content(), entry:content_size()
+        "#).eval().unwrap();
+
+        // Check
+        assert_eq!("renamed.txt,test3.txt,test4.txt", result.0);
+        assert!(result.1);
+        assert_eq!("inner path 'test3.txt' already exists within the package", result.2);
+        assert_eq!("test004", result.3);
+        assert_eq!(7, result.4);
+    }
+
+    #[test]
+    fn entries_should_list_entry_metadata() {
+        // Prepare
+        let lua = create_lua();
+
+        // Execute
+        let result: String = lua.load(r#"
+            local ftldat = require("ftldat")
+            local package = assert(ftldat.from_path_dat(SOURCE_PATH))
+            local lines = {}
+            for _, entry in ipairs(package:entries()) do
+                table.insert(lines, entry:inner_path() .. "=" .. entry:content_size())
+            end
+            return table.concat(lines, ",")
+        "#).eval().unwrap();
+
+        // Check
+        assert_eq!("test1.txt=7,test2.txt=7,test3.txt=7", result);
+    }
+
+    #[test]
+    fn package_should_overwrite_its_source_file() {
+        // Prepare
+        let lua = create_lua();
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let package_path = tmp_dir.path().join("test.dat");
+        std::fs::copy(SOURCE_PATH, &package_path).unwrap();
+        lua.globals().set("PACKAGE_PATH", package_path.to_str().unwrap()).unwrap();
+
+        // Execute
+        let result: (String, bool) = lua.load(r#"
+            local ftldat = require("ftldat")
+            local package = assert(ftldat.from_path_dat(PACKAGE_PATH))
+            package:put_entry("test4.txt", "test004")
+            assert(package:into_path_dat(PACKAGE_PATH))
+            local consumed = not pcall(function() return package:entry_count() end)
+
+            local reread = assert(ftldat.from_path_dat(PACKAGE_PATH))
+            return table.concat(reread:inner_paths(), ","), consumed
+        "#).eval().unwrap();
+
+        // Check
+        assert_eq!("test1.txt,test2.txt,test3.txt,test4.txt", result.0);
+        assert!(result.1);
+    }
+
+    #[test]
+    fn package_should_keep_reading_entries_after_writing_over_its_source_file() {
+        // Prepare
+        let lua = create_lua();
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let package_path = tmp_dir.path().join("test.dat");
+        std::fs::copy(SOURCE_PATH, &package_path).unwrap();
+        lua.globals().set("PACKAGE_PATH", package_path.to_str().unwrap()).unwrap();
+
+        // Execute
+        let result: (String, String) = lua.load(r#"
+            local ftldat = require("ftldat")
+            local package = assert(ftldat.from_path_dat(PACKAGE_PATH))
+            package:remove_entry("test1.txt")
+            assert(package:to_path_dat(PACKAGE_PATH))
+
+            local reread = assert(ftldat.from_path_dat(PACKAGE_PATH))
+            return package:content_by_path("test3.txt"), table.concat(reread:inner_paths(), ",")
+        "#).eval().unwrap();
+
+        // Check
+        assert_eq!("test003", result.0);
+        assert_eq!("test2.txt,test3.txt", result.1);
+    }
+
+    #[test]
+    fn package_should_survive_round_trip_through_pkg_file() {
+        // Prepare
+        let lua = create_lua();
+        let tmp_dir = tempfile::tempdir().unwrap();
+        lua.globals().set("DESTINATION_PATH", tmp_dir.path().join("test.pkg").to_str().unwrap()).unwrap();
+
+        // Execute
+        let result: String = lua.load(r#"
+            local ftldat = require("ftldat")
+            local package = assert(ftldat.from_path_dat(SOURCE_PATH))
+            assert(package:to_path_pkg(DESTINATION_PATH))
+
+            local reread = assert(ftldat.from_path_pkg(DESTINATION_PATH))
+            return reread:entry_by_path("test3.txt"):content()
+        "#).eval().unwrap();
+
+        // Check
+        assert_eq!("test003", result);
+    }
+
+    #[test]
+    fn package_should_extract_from_lua() {
+        // Prepare
+        let lua = create_lua();
+        let tmp_dir = tempfile::tempdir().unwrap();
+        lua.globals().set("DESTINATION_PATH", tmp_dir.path().to_str().unwrap()).unwrap();
+
+        // Execute
+        let result: bool = lua.load(r#"
+            local ftldat = require("ftldat")
+            local package = assert(ftldat.from_path_dat(SOURCE_PATH))
+            return package:extract(DESTINATION_PATH)
+        "#).eval().unwrap();
+
+        // Check
+        assert!(result);
+        assert_eq!("test001", std::fs::read_to_string(tmp_dir.path().join("test1.txt")).unwrap());
+    }
+}
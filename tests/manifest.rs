@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod test_manifest {
+    use ftldat::{ContentHash, Manifest, Package, PackageEntry};
+    use ftldat::error::ManifestReadError;
+
+    const SOURCE_PATH: &str = "./tests-resources/test.dat";
+
+    #[test]
+    #[cfg(not(feature = "sha256"))]
+    fn content_hash_should_be_crc32_by_default() {
+        let entry = PackageEntry::from_string("test1.txt", "test001");
+
+        let result = entry.content_hash().unwrap();
+
+        assert_eq!("crc32", ContentHash::ALGORITHM);
+        assert_eq!("2daecb22", result.to_string());
+    }
+
+    #[test]
+    #[cfg(feature = "sha256")]
+    fn content_hash_should_be_sha256_with_feature() {
+        let entry = PackageEntry::from_string("test1.txt", "test001");
+
+        let result = entry.content_hash().unwrap();
+
+        assert_eq!("sha256", ContentHash::ALGORITHM);
+        assert_eq!("96b8c73aa9d2bc0c4020c2f26687c4c014d01534fb83a6460a1d8d71c9af106c", result.to_string());
+    }
+
+    #[test]
+    fn content_hash_should_parse_from_its_display_form() {
+        let hash = ContentHash::of(b"test001");
+
+        let result: ContentHash = hash.to_string().parse().unwrap();
+
+        assert_eq!(hash, result);
+        assert!("xyz".parse::<ContentHash>().is_err());
+    }
+
+    #[test]
+    fn manifest_should_list_entries_in_package_order() {
+        // Prepare
+        let package = Package::from_path_dat(SOURCE_PATH).unwrap();
+
+        // Execute
+        let manifest = Manifest::from_package(&package).unwrap();
+
+        // Check
+        let paths: Vec<&str> = manifest.entries().iter()
+            .map(|entry| entry.inner_path())
+            .collect();
+        assert_eq!(vec!["test1.txt", "test2.txt", "test3.txt"], paths);
+        assert_eq!(7, manifest.entries()[0].size());
+        assert_eq!(&ContentHash::of(b"test001"), manifest.entries()[0].hash());
+    }
+
+    #[test]
+    fn manifest_should_survive_round_trip_through_file() {
+        // Prepare
+        let tmp_file = tempfile::NamedTempFile::new().unwrap();
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+        package.put_entry(PackageEntry::from_string("dir with spaces/file.txt", "test"));
+        let manifest = Manifest::from_package(&package).unwrap();
+
+        // Execute
+        manifest.to_path(tmp_file.path()).unwrap();
+        let result = Manifest::from_path(tmp_file.path()).unwrap();
+
+        // Check
+        assert_eq!(manifest.entries(), result.entries());
+        assert!(result.verify(&package).unwrap().is_ok());
+    }
+
+    #[test]
+    fn verify_should_report_missing_mismatched_and_unexpected_entries() {
+        // Prepare
+        let manifest = Manifest::from_package(&Package::from_path_dat(SOURCE_PATH).unwrap()).unwrap();
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+        package.remove_entry("test1.txt");
+        package.put_entry(PackageEntry::from_string("test2.txt", "test00X"));
+        package.put_entry(PackageEntry::from_string("test4.txt", "test004"));
+
+        // Execute
+        let result = manifest.verify(&package).unwrap();
+
+        // Check
+        assert!(!result.is_ok());
+        assert_eq!(vec!["test1.txt"], result.missing());
+        assert_eq!(vec!["test2.txt"], result.mismatched());
+        assert_eq!(vec!["test4.txt"], result.unexpected());
+    }
+
+    #[test]
+    fn from_input_should_reject_other_hash_algorithm() {
+        let input = "#ftldat-manifest hash=md5\nd41d8cd98f00b204e9800998ecf8427e\t0\ttest.txt\n";
+
+        let result = Manifest::from_input(input.as_bytes());
+
+        assert!(matches!(result, Err(ManifestReadError::HashAlgorithmMismatchError(_))));
+    }
+
+    #[test]
+    fn from_input_should_reject_malformed_line() {
+        let input = format!("#ftldat-manifest hash={}\nnot a manifest line\n", ContentHash::ALGORITHM);
+
+        let result = Manifest::from_input(input.as_bytes());
+
+        assert!(matches!(result, Err(ManifestReadError::InvalidLineError(2))));
+    }
+}
@@ -0,0 +1,151 @@
+#[cfg(all(test, feature = "slipstream"))]
+mod test_mod_conflicts {
+    use ftldat::{FtlMod, ModConflictAnalysis, ModFileAction, Package, PackageEntry};
+
+    fn create_mod(files: &[(&str, &str)]) -> FtlMod {
+        let mut package = Package::new();
+        for (path, content) in files {
+            package.add_entry(PackageEntry::from_string(path, *content)).unwrap();
+        }
+
+        FtlMod::from_package(&package).unwrap()
+    }
+
+    #[test]
+    fn analysis_should_list_mods_touching_each_path() {
+        // Prepare
+        let first = create_mod(&[
+            ("data/blueprints.xml.append", "<blueprint name=\"FIRST\"/>"),
+            ("img/ship/kestral_base.png", "first png"),
+            ("readme.txt", "ignored"),
+        ]);
+        let second = create_mod(&[
+            ("data/blueprints.xml.append", "<blueprint name=\"SECOND\"/>"),
+            ("data/events.xml", "<events/>"),
+        ]);
+
+        // Execute
+        let analysis = ModConflictAnalysis::new([("first", &first), ("second", &second)]);
+
+        // Check
+        let paths = analysis.paths().iter().map(|path| path.inner_path()).collect::<Vec<&str>>();
+        assert_eq!(vec!["data/blueprints.xml", "data/events.xml", "img/ship/kestral_base.png"], paths);
+
+        let conflicts = analysis.conflicts().collect::<Vec<_>>();
+        assert_eq!(1, conflicts.len());
+        let mod_names = conflicts[0].touches().iter().map(|touch| touch.mod_name()).collect::<Vec<&str>>();
+        assert_eq!(vec!["first", "second"], mod_names);
+        assert_eq!("data/blueprints.xml.append", conflicts[0].touches()[0].archive_path());
+    }
+
+    #[test]
+    fn appends_should_be_mergeable_without_winner() {
+        // Prepare
+        let first = create_mod(&[("data/blueprints.xml.append", "<blueprint name=\"FIRST\"/>")]);
+        let second = create_mod(&[("data/blueprints.append.xml", "<blueprint name=\"SECOND\"/>")]);
+
+        // Execute
+        let analysis = ModConflictAnalysis::new([("first", &first), ("second", &second)]);
+
+        // Check
+        let path = analysis.path("data/blueprints.xml").unwrap();
+        assert!(path.touches().iter().all(|touch| touch.action() == ModFileAction::Append));
+        assert!(path.winner().is_none());
+        assert!(path.is_mergeable());
+        assert!(!path.is_identical());
+    }
+
+    #[test]
+    fn overwrite_should_win_over_earlier_appends() {
+        // Prepare
+        let first = create_mod(&[("data/blueprints.xml.append", "<blueprint name=\"FIRST\"/>")]);
+        let second = create_mod(&[("data/blueprints.xml", "<blueprints/>")]);
+        let third = create_mod(&[("data/blueprints.xml.rawappend", "<blueprint name=\"THIRD\"/>")]);
+
+        // Execute
+        let analysis = ModConflictAnalysis::new([("first", &first), ("second", &second), ("third", &third)]);
+
+        // Check
+        let path = analysis.path("data/blueprints.xml").unwrap();
+        assert_eq!("second", path.winner().unwrap().mod_name());
+        assert!(!path.is_mergeable());
+        assert!(path.touches()[2].is_append());
+    }
+
+    #[test]
+    fn identical_overwrites_should_be_reported() {
+        // Prepare
+        let first = create_mod(&[("img/ship/kestral_base.png", "png"), ("img/ship/kestral_gib1.png", "gib")]);
+        let second = create_mod(&[("img/ship/kestral_base.png", "png"), ("img/ship/kestral_gib1.png", "other gib")]);
+
+        // Execute
+        let analysis = ModConflictAnalysis::new([("first", &first), ("second", &second)]);
+
+        // Check
+        let identical = analysis.path("img/ship/kestral_base.png").unwrap();
+        assert!(identical.is_identical());
+        assert!(identical.is_mergeable());
+        assert_eq!("second", identical.winner().unwrap().mod_name());
+
+        let different = analysis.path("img/ship/kestral_gib1.png").unwrap();
+        assert!(!different.is_identical());
+        assert!(!different.is_mergeable());
+        assert!(analysis.path("img/ship/kestral_gib2.png").is_none());
+    }
+
+    #[test]
+    fn mod_should_read_from_directory() {
+        // Prepare
+        let tmp_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tmp_dir.path().join("data")).unwrap();
+        std::fs::create_dir_all(tmp_dir.path().join("img/ship")).unwrap();
+        std::fs::write(tmp_dir.path().join("data/events.xml.append"), "<event/>").unwrap();
+        std::fs::write(tmp_dir.path().join("img/ship/kestral_base.png"), "png").unwrap();
+        std::fs::write(tmp_dir.path().join("readme.txt"), "readme").unwrap();
+
+        // Execute
+        let ftl_mod = FtlMod::from_directory(tmp_dir.path()).unwrap();
+
+        // Check
+        let archive_paths = ftl_mod.files().iter().map(|file| file.archive_path()).collect::<Vec<&str>>();
+        assert_eq!(vec!["data/events.xml.append", "img/ship/kestral_base.png"], archive_paths);
+        assert_eq!("data/events.xml", ftl_mod.files()[0].inner_path());
+        assert_eq!(b"png", ftl_mod.files()[1].content());
+        assert_eq!(vec!["readme.txt"], ftl_mod.ignored_paths());
+    }
+
+    #[test]
+    fn replaces_should_only_be_mergeable_if_identical_and_before_appends() {
+        // Prepare
+        let replace_a = create_mod(&[("data/events.xml", "<events a=\"1\"/>")]);
+        let replace_b = create_mod(&[("data/events.xml", "<events b=\"1\"/>")]);
+        let append = create_mod(&[("data/events.xml.append", "<event/>")]);
+
+        // Execute
+        let different = ModConflictAnalysis::new([("a", &replace_a), ("b", &replace_b)]);
+        let identical_then_append = ModConflictAnalysis::new([("a1", &replace_a), ("a2", &replace_a), ("append", &append)]);
+        let append_then_replace = ModConflictAnalysis::new([("append", &append), ("a", &replace_a)]);
+
+        // Check
+        assert!(!different.path("data/events.xml").unwrap().is_mergeable());
+        assert!(identical_then_append.path("data/events.xml").unwrap().is_mergeable());
+        assert!(!append_then_replace.path("data/events.xml").unwrap().is_mergeable());
+    }
+
+
+    #[test]
+    fn analysis_should_not_treat_crc32_collision_as_identical() {
+        // Prepare
+        // Both contents have the CRC32 0x4ddb0c25
+        let first = create_mod(&[("img/ship/kestral_base.png", "plumless")]);
+        let second = create_mod(&[("img/ship/kestral_base.png", "buckeroo")]);
+
+        // Execute
+        let analysis = ModConflictAnalysis::new([("first", &first), ("second", &second)]);
+
+        // Check
+        let path = analysis.path("img/ship/kestral_base.png").unwrap();
+        assert!(!path.is_identical());
+        assert!(!path.is_mergeable());
+    }
+}
@@ -4,7 +4,8 @@ mod test_package {
 
     use tempfile::tempdir;
 
-    use ftldat::{Package, PackageEntry};
+    use ftldat::{calculate_path_hash, Package, PackageEntry};
+    use ftldat::error::RenameError;
 
     const SOURCE_PATH: &str = "./tests-resources/test.dat";
 
@@ -233,4 +234,520 @@ mod test_package {
         assert!(PathBuf::from(tmp_path).join("test2.txt").exists());
         assert!(PathBuf::from(tmp_path).join("test3.txt").exists());
     }
+
+    #[test]
+    fn into_path_should_create_file_when_destination_does_not_exist() {
+        // Prepare
+        let mut package = Package::new();
+        package.put_entry(PackageEntry::from_string("test", "test"));
+        let tmp_dir = tempdir().unwrap();
+        let destination_path = tmp_dir.path().join("new.dat");
+
+        // Execute
+        let result = package.into_path_dat(&destination_path);
+
+        // Check
+        assert!(result.is_ok());
+        let written = Package::from_path_dat(&destination_path).unwrap();
+        assert_eq!(b"test".to_vec(), written.content_by_path("test").unwrap());
+    }
+
+    #[test]
+    fn into_path_should_keep_files_sharing_the_destination_stem() {
+        // Prepare
+        let mut package = Package::new();
+        package.put_entry(PackageEntry::from_string("test", "test"));
+        let tmp_dir = tempdir().unwrap();
+        let unrelated_path = tmp_dir.path().join("resource.tmp");
+        std::fs::write(&unrelated_path, "unrelated").unwrap();
+
+        // Execute
+        package.into_path_dat(tmp_dir.path().join("resource.dat")).unwrap();
+
+        // Check
+        assert_eq!("unrelated", std::fs::read_to_string(&unrelated_path).unwrap());
+        assert!(!tmp_dir.path().join("resource.dat.tmp").exists());
+    }
+
+    #[test]
+    fn into_path_should_remove_temporary_file_on_error() {
+        // Prepare
+        let mut package = Package::new();
+        package.put_entry(PackageEntry::from_string("test", "test"));
+        let tmp_dir = tempdir().unwrap();
+        let destination_path = tmp_dir.path().join("resource.dat");
+        std::fs::create_dir(&destination_path).unwrap();
+        std::fs::write(destination_path.join("file"), "file").unwrap();
+
+        // Execute
+        let result = package.into_path_dat(&destination_path);
+
+        // Check
+        assert!(result.is_err());
+        assert!(!tmp_dir.path().join("resource.dat.tmp").exists());
+    }
+
+    fn create_nested_package() -> Package {
+        let mut package = Package::new();
+        package.put_entry(PackageEntry::from_string("img/ship/kestrel_base.png", "test"));
+        package.put_entry(PackageEntry::from_string("data/blueprints.xml", "test"));
+        package.put_entry(PackageEntry::from_string("img/ship/interior/room.png", "test"));
+        package.put_entry(PackageEntry::from_string("img/shipyard.png", "test"));
+        package.put_entry(PackageEntry::from_string("data/events.xml", "test"));
+        package.put_entry(PackageEntry::from_string("readme.txt", "test"));
+        package
+    }
+
+    #[test]
+    fn entries_with_prefix_should_return_matching_entries_in_sorted_order() {
+        // Prepare
+        let package = create_nested_package();
+
+        // Execute
+        let result: Vec<&str> = package.entries_with_prefix("img/ship")
+            .map(|entry| entry.inner_path())
+            .collect();
+
+        // Check
+        assert_eq!(
+            vec!["img/ship/interior/room.png", "img/ship/kestrel_base.png", "img/shipyard.png"],
+            result
+        );
+    }
+
+    #[test]
+    fn entries_with_prefix_should_return_nothing_when_no_entry_matches() {
+        let package = create_nested_package();
+
+        let result = package.entries_with_prefix("audio/");
+
+        assert_eq!(0, result.count());
+    }
+
+    #[test]
+    fn entries_in_directory_should_not_descend_into_subdirectories() {
+        // Prepare
+        let package = create_nested_package();
+
+        // Execute
+        let with_slash: Vec<&str> = package.entries_in_directory("img/ship/")
+            .map(|entry| entry.inner_path())
+            .collect();
+        let without_slash: Vec<&str> = package.entries_in_directory("img/ship")
+            .map(|entry| entry.inner_path())
+            .collect();
+
+        // Check
+        assert_eq!(vec!["img/ship/kestrel_base.png"], with_slash);
+        assert_eq!(with_slash, without_slash);
+    }
+
+    #[test]
+    fn entries_in_directory_should_list_root_entries_for_empty_directory() {
+        // Prepare
+        let package = create_nested_package();
+
+        // Execute
+        let result: Vec<&str> = package.entries_in_directory("")
+            .map(|entry| entry.inner_path())
+            .collect();
+
+        // Check
+        assert_eq!(vec!["readme.txt"], result);
+    }
+
+    #[test]
+    fn entries_matching_glob_should_match_within_single_segment() {
+        // Prepare
+        let package = create_nested_package();
+
+        // Execute
+        let result: Vec<&str> = package.entries_matching_glob("img/ship/*.png")
+            .unwrap()
+            .map(|entry| entry.inner_path())
+            .collect();
+
+        // Check
+        assert_eq!(vec!["img/ship/kestrel_base.png"], result);
+    }
+
+    #[test]
+    fn entries_matching_glob_should_match_across_segments_with_double_star() {
+        // Prepare
+        let package = create_nested_package();
+
+        // Execute
+        let pngs: Vec<&str> = package.entries_matching_glob("**/*.png")
+            .unwrap()
+            .map(|entry| entry.inner_path())
+            .collect();
+        let xmls: Vec<&str> = package.entries_matching_glob("data/*.xml")
+            .unwrap()
+            .map(|entry| entry.inner_path())
+            .collect();
+
+        // Check
+        assert_eq!(
+            vec!["img/ship/interior/room.png", "img/ship/kestrel_base.png", "img/shipyard.png"],
+            pngs
+        );
+        assert_eq!(vec!["data/blueprints.xml", "data/events.xml"], xmls);
+    }
+
+    #[test]
+    fn entries_matching_glob_should_fail_for_invalid_pattern() {
+        let package = create_nested_package();
+
+        let result = package.entries_matching_glob("img/[ship");
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn remove_entry_should_keep_remaining_entries_accessible() {
+        // Prepare
+        let mut package = Package::new();
+        package.put_entry(PackageEntry::from_string("test1", "test001"));
+        package.put_entry(PackageEntry::from_string("test2", "test002"));
+        package.put_entry(PackageEntry::from_string("test3", "test003"));
+
+        // Execute
+        let result = package.remove_entry("test1");
+
+        // Check
+        assert!(result);
+        assert!(!package.entry_exists("test1"));
+        assert_eq!(vec!["test2", "test3"], package.inner_paths());
+        assert_eq!("test003".as_bytes(), package.content_by_path("test3").unwrap());
+    }
+
+    #[test]
+    fn rename_entry_should_keep_content_and_position() {
+        // Prepare
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+
+        // Execute
+        let result = package.rename_entry("test2.txt", "renamed/test2.txt");
+
+        // Check
+        assert!(result.is_ok());
+        assert!(!package.entry_exists("test2.txt"));
+        assert_eq!(vec!["test1.txt", "renamed/test2.txt", "test3.txt"], package.inner_paths());
+        assert_eq!("test002".as_bytes(), package.content_by_path("renamed/test2.txt").unwrap());
+    }
+
+    #[test]
+    fn rename_entry_should_fail_when_old_innerpath_is_free() {
+        let mut package = Package::new();
+
+        let result = package.rename_entry("test", "test2");
+
+        assert!(matches!(result, Err(RenameError::NotFound(_))));
+    }
+
+    #[test]
+    fn rename_entry_should_fail_when_new_innerpath_is_taken() {
+        // Prepare
+        let mut package = Package::new();
+        package.put_entry(PackageEntry::from_string("test1", "test001"));
+        package.put_entry(PackageEntry::from_string("test2", "test002"));
+
+        // Execute
+        let result = package.rename_entry("test1", "test2");
+
+        // Check
+        assert!(matches!(result, Err(RenameError::AlreadyExists(_))));
+        assert_eq!("test001".as_bytes(), package.content_by_path("test1").unwrap());
+        assert_eq!("test002".as_bytes(), package.content_by_path("test2").unwrap());
+    }
+
+    #[test]
+    fn rename_directory_should_move_all_nested_entries() {
+        // Prepare
+        let mut package = create_nested_package();
+
+        // Execute
+        let result = package.rename_directory("img/ship", "img/vessel/");
+
+        // Check
+        assert_eq!(2, result.unwrap());
+        assert_eq!(
+            vec![
+                "img/vessel/kestrel_base.png",
+                "data/blueprints.xml",
+                "img/vessel/interior/room.png",
+                "img/shipyard.png",
+                "data/events.xml",
+                "readme.txt",
+            ],
+            package.inner_paths()
+        );
+        assert_eq!(0, package.entries_with_prefix("img/ship/").count());
+    }
+
+    #[test]
+    fn rename_directory_should_allow_moving_into_own_subdirectory() {
+        // Prepare
+        let mut package = Package::new();
+        package.put_entry(PackageEntry::from_string("a/x", "test"));
+        package.put_entry(PackageEntry::from_string("a/b/x", "test"));
+
+        // Execute
+        let result = package.rename_directory("a", "a/b");
+
+        // Check
+        assert_eq!(2, result.unwrap());
+        assert_eq!(vec!["a/b/x", "a/b/b/x"], package.inner_paths());
+    }
+
+    #[test]
+    fn rename_directory_should_fail_without_changes_on_conflict() {
+        // Prepare
+        let mut package = create_nested_package();
+        package.put_entry(PackageEntry::from_string("img/vessel/interior/room.png", "test"));
+        let paths_before = package.inner_paths();
+
+        // Execute
+        let result = package.rename_directory("img/ship", "img/vessel");
+
+        // Check
+        assert!(matches!(result, Err(RenameError::AlreadyExists(_))));
+        assert_eq!(paths_before, package.inner_paths());
+    }
+
+    #[test]
+    fn rename_directory_should_fail_when_directory_is_empty() {
+        let mut package = create_nested_package();
+
+        let result = package.rename_directory("audio", "sound");
+
+        assert!(matches!(result, Err(RenameError::NotFound(_))));
+    }
+
+    #[test]
+    fn insert_entry_should_place_entry_at_index() {
+        // Prepare
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+
+        // Execute
+        let result = package.insert_entry(1, PackageEntry::from_string("test0.txt", "test000"));
+
+        // Check
+        assert!(result.is_ok());
+        assert_eq!(vec!["test1.txt", "test0.txt", "test2.txt", "test3.txt"], package.inner_paths());
+        assert_eq!(Some(1), package.index_of("test0.txt"));
+        assert_eq!(Some(3), package.index_of("test3.txt"));
+        assert_eq!("test003".as_bytes(), package.content_by_path("test3.txt").unwrap());
+    }
+
+    #[test]
+    fn insert_entry_should_fail_when_innerpath_is_taken() {
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+
+        let result = package.insert_entry(0, PackageEntry::from_string("test2.txt", "test"));
+
+        assert!(result.is_err());
+        assert_eq!(vec!["test1.txt", "test2.txt", "test3.txt"], package.inner_paths());
+    }
+
+    #[test]
+    fn move_entry_should_shift_entries_in_between() {
+        // Prepare
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+
+        // Execute
+        package.move_entry("test1.txt", 2).unwrap();
+        let after_forward_move = package.inner_paths();
+        package.move_entry("test1.txt", 0).unwrap();
+        let after_backward_move = package.inner_paths();
+
+        // Check
+        assert_eq!(vec!["test2.txt", "test3.txt", "test1.txt"], after_forward_move);
+        assert_eq!(vec!["test1.txt", "test2.txt", "test3.txt"], after_backward_move);
+        assert_eq!(Some(2), package.index_of("test3.txt"));
+    }
+
+    #[test]
+    fn move_entry_should_fail_when_innerpath_is_free() {
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+
+        let result = package.move_entry("test", 0);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn sort_by_inner_path_should_order_entries_lexicographically() {
+        // Prepare
+        let mut package = create_nested_package();
+
+        // Execute
+        package.sort_by_inner_path();
+
+        // Check
+        assert_eq!(
+            vec![
+                "data/blueprints.xml",
+                "data/events.xml",
+                "img/ship/interior/room.png",
+                "img/ship/kestrel_base.png",
+                "img/shipyard.png",
+                "readme.txt",
+            ],
+            package.inner_paths()
+        );
+        assert_eq!(Some(5), package.index_of("readme.txt"));
+    }
+
+    #[test]
+    fn sort_by_extension_should_group_entries_by_extension() {
+        // Prepare
+        let mut package = create_nested_package();
+        package.put_entry(PackageEntry::from_string("LICENSE", "test"));
+
+        // Execute
+        package.sort_by_extension();
+
+        // Check
+        assert_eq!(
+            vec![
+                "LICENSE",
+                "img/ship/interior/room.png",
+                "img/ship/kestrel_base.png",
+                "img/shipyard.png",
+                "readme.txt",
+                "data/blueprints.xml",
+                "data/events.xml",
+            ],
+            package.inner_paths()
+        );
+    }
+
+    #[test]
+    fn sort_by_should_use_custom_comparator_and_update_index() {
+        // Prepare
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+
+        // Execute
+        package.sort_by(|a, b| b.inner_path().cmp(a.inner_path()));
+
+        // Check
+        assert_eq!(vec!["test3.txt", "test2.txt", "test1.txt"], package.inner_paths());
+        assert_eq!(Some(0), package.index_of("test3.txt"));
+        assert_eq!("test001".as_bytes(), package.content_by_path("test1.txt").unwrap());
+    }
+
+    #[test]
+    fn retain_should_keep_only_matching_entries_in_order() {
+        // Prepare
+        let mut package = create_nested_package();
+
+        // Execute
+        package.retain(|entry| !entry.inner_path().ends_with(".png"));
+
+        // Check
+        assert_eq!(vec!["data/blueprints.xml", "data/events.xml", "readme.txt"], package.inner_paths());
+        assert!(!package.entry_exists("img/shipyard.png"));
+        assert_eq!(Some(2), package.index_of("readme.txt"));
+    }
+
+    #[test]
+    fn drain_filter_should_move_matching_entries_into_new_package() {
+        // Prepare
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+
+        // Execute
+        let drained = package.drain_filter(|entry| entry.inner_path() != "test2.txt");
+
+        // Check
+        assert_eq!(vec!["test2.txt"], package.inner_paths());
+        assert_eq!(vec!["test1.txt", "test3.txt"], drained.inner_paths());
+        assert_eq!("test003".as_bytes(), drained.content_by_path("test3.txt").unwrap());
+        assert_eq!("test002".as_bytes(), package.content_by_path("test2.txt").unwrap());
+    }
+
+    #[test]
+    fn remove_entries_should_remove_all_listed_paths() {
+        // Prepare
+        let mut package = create_nested_package();
+
+        // Execute
+        let result = package.remove_entries(["img/shipyard.png", "readme.txt", "missing.txt"]);
+
+        // Check
+        assert_eq!(2, result);
+        assert_eq!(4, package.entry_count());
+        assert!(!package.entry_exists("readme.txt"));
+        assert_eq!(Some(3), package.index_of("data/events.xml"));
+    }
+
+    #[test]
+    fn calculate_path_hash_should_ignore_case() {
+        // Execute
+        let result = calculate_path_hash("TEST1.txt");
+
+        // Check
+        assert_eq!(157232055, result);
+        assert_eq!(calculate_path_hash("test1.txt"), result);
+    }
+
+    #[test]
+    fn entries_by_path_hash_should_return_matching_entries() {
+        // Prepare
+        let mut package = Package::new();
+        package.add_entry(PackageEntry::from_string("data/a.xml", "lower")).unwrap();
+        package.add_entry(PackageEntry::from_string("data/b.xml", "other")).unwrap();
+        package.add_entry(PackageEntry::from_string("data/A.xml", "upper")).unwrap();
+
+        // Execute
+        let result = package.entries_by_path_hash(calculate_path_hash("data/a.xml"))
+            .map(|entry| entry.inner_path())
+            .collect::<Vec<&str>>();
+
+        // Check
+        assert_eq!(vec!["data/a.xml", "data/A.xml"], result);
+    }
+
+    #[test]
+    fn path_hash_collisions_should_report_colliding_paths() {
+        // Prepare
+        let mut package = Package::new();
+        package.add_entry(PackageEntry::from_string("data/a.xml", "lower")).unwrap();
+        package.add_entry(PackageEntry::from_string("data/b.xml", "other")).unwrap();
+        package.add_entry(PackageEntry::from_string("data/A.xml", "upper")).unwrap();
+
+        // Execute
+        let result = package.path_hash_collisions();
+
+        // Check
+        assert_eq!(1, result.len());
+        assert_eq!(calculate_path_hash("data/a.xml"), result[0].path_hash());
+        assert_eq!(["data/a.xml", "data/A.xml"], result[0].inner_paths());
+    }
+
+    #[test]
+    fn path_hash_collisions_should_be_empty_for_distinct_hashes() {
+        // Prepare
+        let package = Package::from_path_dat(SOURCE_PATH).unwrap();
+
+        // Execute
+        let result = package.path_hash_collisions();
+
+        // Check
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn entry_at_should_return_entries_in_package_order() {
+        // Prepare
+        let package = Package::from_path_dat(SOURCE_PATH).unwrap();
+
+        // Execute
+        let entry = package.entry_at(1);
+        let missing_entry = package.entry_at(3);
+
+        // Check
+        assert_eq!("test2.txt", entry.unwrap().inner_path());
+        assert!(missing_entry.is_none());
+    }
 }
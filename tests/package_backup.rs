@@ -0,0 +1,161 @@
+#[cfg(test)]
+mod test_package_backup {
+    use std::path::{Path, PathBuf};
+
+    use tempfile::TempDir;
+
+    use ftldat::{BackupStatus, Package, PackageBackup, PackageEntry};
+    use ftldat::error::BackupError;
+
+    const SOURCE_PATH: &str = "./tests-resources/test.dat";
+
+    fn copy_source(tmp_dir: &TempDir) -> PathBuf {
+        let package_path = tmp_dir.path().join("resource.dat");
+        std::fs::copy(SOURCE_PATH, &package_path).unwrap();
+        package_path
+    }
+
+    fn modify(package_path: &Path) -> PackageBackup {
+        let mut package = Package::from_path_dat(package_path).unwrap();
+        package.put_entry(PackageEntry::from_string("test4.txt", "test004"));
+        package.into_path_dat_with_backup(package_path).unwrap()
+    }
+
+    #[test]
+    fn status_should_be_missing_before_first_modification() {
+        // Prepare
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let package_path = copy_source(&tmp_dir);
+
+        // Execute
+        let result = PackageBackup::new(&package_path).status().unwrap();
+
+        // Check
+        assert_eq!(BackupStatus::Missing, result);
+    }
+
+    #[test]
+    fn into_path_with_backup_should_snapshot_original() {
+        // Prepare
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let package_path = copy_source(&tmp_dir);
+
+        // Execute
+        let backup = modify(&package_path);
+
+        // Check
+        assert_eq!(tmp_dir.path().join("resource.dat.bak"), backup.backup_path());
+        assert_eq!(std::fs::read(SOURCE_PATH).unwrap(), std::fs::read(backup.backup_path()).unwrap());
+        assert!(backup.verify().is_ok());
+        assert_eq!(BackupStatus::Modified, backup.status().unwrap());
+    }
+
+    #[test]
+    fn into_path_with_backup_should_keep_first_snapshot() {
+        // Prepare
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let package_path = copy_source(&tmp_dir);
+        modify(&package_path);
+
+        // Execute
+        let mut package = Package::from_path_dat(&package_path).unwrap();
+        package.put_entry(PackageEntry::from_string("test5.txt", "test005"));
+        let backup = package.into_path_dat_with_backup(&package_path).unwrap();
+
+        // Check
+        assert_eq!(std::fs::read(SOURCE_PATH).unwrap(), std::fs::read(backup.backup_path()).unwrap());
+        assert_eq!(BackupStatus::Modified, backup.status().unwrap());
+    }
+
+    #[test]
+    fn restore_should_replace_package_with_original() {
+        // Prepare
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let package_path = copy_source(&tmp_dir);
+        let backup = modify(&package_path);
+
+        // Execute
+        let result = backup.restore();
+
+        // Check
+        assert!(result.is_ok());
+        assert_eq!(std::fs::read(SOURCE_PATH).unwrap(), std::fs::read(&package_path).unwrap());
+        assert_eq!(BackupStatus::Original, backup.status().unwrap());
+    }
+
+    #[test]
+    fn restore_should_refuse_corrupt_backup() {
+        // Prepare
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let package_path = copy_source(&tmp_dir);
+        let backup = modify(&package_path);
+        std::fs::write(backup.backup_path(), b"garbage").unwrap();
+
+        // Execute
+        let result = backup.restore();
+
+        // Check
+        assert!(matches!(result, Err(BackupError::CorruptBackupError(_))));
+        assert!(Package::from_path_dat(&package_path).unwrap().entry_exists("test4.txt"));
+    }
+
+    #[test]
+    fn status_should_detect_replaced_package_as_stale() {
+        // Prepare
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let package_path = copy_source(&tmp_dir);
+        let backup = modify(&package_path);
+
+        let mut updated = Package::from_path_dat(SOURCE_PATH).unwrap();
+        updated.put_entry(PackageEntry::from_string("test1.txt", "test001 was updated"));
+        updated.to_path_dat(&package_path).unwrap();
+
+        // Execute
+        let result = backup.status().unwrap();
+
+        // Check
+        assert_eq!(BackupStatus::Stale, result);
+        assert!(matches!(backup.restore(), Err(BackupError::StaleBackupError(_))));
+    }
+
+    #[test]
+    fn into_path_with_backup_should_refuse_stale_backup_until_discarded() {
+        // Prepare
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let package_path = copy_source(&tmp_dir);
+        let backup = modify(&package_path);
+
+        let mut updated = Package::from_path_dat(SOURCE_PATH).unwrap();
+        updated.put_entry(PackageEntry::from_string("test1.txt", "test001 was updated"));
+        updated.to_path_dat(&package_path).unwrap();
+        let updated_content = std::fs::read(&package_path).unwrap();
+
+        // Execute
+        let result = Package::from_path_dat(&package_path).unwrap()
+            .into_path_dat_with_backup(&package_path);
+        backup.discard().unwrap();
+        let backup = modify(&package_path);
+
+        // Check
+        assert!(matches!(result, Err(BackupError::StaleBackupError(_))));
+        assert_eq!(updated_content, std::fs::read(backup.backup_path()).unwrap());
+    }
+
+    #[test]
+    fn restore_should_not_touch_files_sharing_package_stem() {
+        // Prepare
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let package_path = copy_source(&tmp_dir);
+        let backup = modify(&package_path);
+        let sibling_path = tmp_dir.path().join("resource.tmp");
+        std::fs::write(&sibling_path, "unrelated").unwrap();
+
+        // Execute
+        backup.restore().unwrap();
+
+        // Check
+        assert_eq!(std::fs::read(SOURCE_PATH).unwrap(), std::fs::read(&package_path).unwrap());
+        assert_eq!("unrelated", std::fs::read_to_string(&sibling_path).unwrap());
+        assert!(!tmp_dir.path().join("resource.dat.tmp").exists());
+    }
+}
@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod test_package_diff {
+    use ftldat::{Package, PackageEntry};
+
+    const SOURCE_PATH: &str = "./tests-resources/test.dat";
+
+    #[test]
+    fn diff_should_report_no_changes_between_identical_packages() {
+        // Prepare
+        let old = Package::from_path_dat(SOURCE_PATH).unwrap();
+        let new = Package::from_path_pkg("./tests-resources/test.pkg").unwrap();
+
+        // Execute
+        let diff = old.diff(&new).unwrap();
+
+        // Check
+        assert!(diff.is_empty());
+        assert_eq!(vec!["test1.txt", "test2.txt", "test3.txt"], diff.unchanged());
+        assert_eq!("", diff.to_string());
+    }
+
+    #[test]
+    fn diff_should_report_added_removed_and_modified_entries() {
+        // Prepare
+        let old = Package::from_path_dat(SOURCE_PATH).unwrap();
+        let mut new = Package::from_path_dat(SOURCE_PATH).unwrap();
+        new.remove_entry("test1.txt");
+        // Same size, different content
+        new.put_entry(PackageEntry::from_string("test2.txt", "test999"));
+        // Different size
+        new.put_entry(PackageEntry::from_string("test3.txt", "test0003"));
+        new.put_entry(PackageEntry::from_string("test4.txt", "test004"));
+
+        // Execute
+        let diff = old.diff(&new).unwrap();
+
+        // Check
+        assert!(!diff.is_empty());
+        assert_eq!(vec!["test4.txt"], diff.added());
+        assert_eq!(vec!["test1.txt"], diff.removed());
+        assert_eq!(vec!["test2.txt", "test3.txt"], diff.modified());
+        assert!(diff.unchanged().is_empty());
+        assert_eq!("A\ttest4.txt\nD\ttest1.txt\nM\ttest2.txt\nM\ttest3.txt\n", diff.to_string());
+    }
+
+    #[test]
+    fn diff_should_report_modified_entries_with_colliding_crc32() {
+        // Prepare
+        // Both contents have the CRC32 hash 4ddb0c25
+        let mut old = Package::new();
+        old.put_entry(PackageEntry::from_string("test.txt", "plumless"));
+        let mut new = Package::new();
+        new.put_entry(PackageEntry::from_string("test.txt", "buckeroo"));
+
+        // Execute
+        let diff = old.diff(&new).unwrap();
+
+        // Check
+        assert_eq!(vec!["test.txt"], diff.modified());
+        assert!(diff.unchanged().is_empty());
+    }
+}
@@ -0,0 +1,218 @@
+#[cfg(test)]
+mod test_package_index {
+    use std::io::Cursor;
+
+    use ftldat::{calculate_path_hash, Package, PackageEntry, PackageIndex};
+
+    const DAT_SOURCE_PATH: &str = "./tests-resources/test.dat";
+    const PKG_SOURCE_PATH: &str = "./tests-resources/test.pkg";
+
+    fn create_large_package() -> Package {
+        let mut package = Package::new();
+        for i in 0..1000 {
+            package.add_entry(PackageEntry::from_string(format!("data/file{}.txt", i), format!("content{}", i))).unwrap();
+        }
+
+        package
+    }
+
+    fn assert_is_test_index(index: &PackageIndex) {
+        assert_eq!(3, index.entry_count());
+        assert!(index.entry_exists("test2.txt").unwrap());
+        assert!(!index.entry_exists("test4.txt").unwrap());
+        assert_eq!("test003".as_bytes(), index.content_by_path("test3.txt").unwrap().unwrap());
+
+        let paths = index.iter()
+            .map(|entry| entry.unwrap().inner_path().to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(vec!["test1.txt", "test2.txt", "test3.txt"], paths);
+    }
+
+    #[test]
+    fn index_should_read_dat_package() {
+        // Execute
+        let result = PackageIndex::from_path_dat(DAT_SOURCE_PATH);
+
+        // Check
+        assert_is_test_index(&result.unwrap());
+    }
+
+    #[test]
+    fn index_should_read_pkg_package() {
+        // Execute
+        let result = PackageIndex::from_path_pkg(PKG_SOURCE_PATH);
+
+        // Check
+        assert_is_test_index(&result.unwrap());
+    }
+
+    #[test]
+    fn index_should_read_package_from_bytes() {
+        // Execute
+        let dat_result = PackageIndex::from_bytes_dat(std::fs::read(DAT_SOURCE_PATH).unwrap());
+        let pkg_result = PackageIndex::from_bytes_pkg(std::fs::read(PKG_SOURCE_PATH).unwrap());
+
+        // Check
+        assert_is_test_index(&dat_result.unwrap());
+        assert_is_test_index(&pkg_result.unwrap());
+    }
+
+    #[test]
+    fn find_should_return_entry_location() {
+        // Prepare
+        let index = PackageIndex::from_path_dat(DAT_SOURCE_PATH).unwrap();
+
+        // Execute
+        let result = index.find("test2.txt").unwrap().unwrap();
+
+        // Check
+        assert_eq!("test2.txt", result.inner_path());
+        assert_eq!(7, result.data_size());
+        assert_eq!(Some(1), index.position_of("test2.txt").unwrap());
+    }
+
+    #[test]
+    fn pkg_lookup_should_be_case_sensitive() {
+        // Prepare
+        // PKG path hashes are case-insensitive, so this path shares a hash with `test1.txt`.
+        let index = PackageIndex::from_path_pkg(PKG_SOURCE_PATH).unwrap();
+
+        // Execute
+        let result = index.entry_exists("TEST1.txt");
+
+        // Check
+        assert!(!result.unwrap());
+    }
+
+    #[test]
+    fn index_should_find_entries_in_large_package() {
+        // Prepare
+        let package = create_large_package();
+        let mut dat_bytes = Cursor::new(Vec::new());
+        let mut pkg_bytes = Cursor::new(Vec::new());
+        package.to_output_dat(&mut dat_bytes).unwrap();
+        package.to_output_pkg(&mut pkg_bytes).unwrap();
+
+        // Execute
+        let dat_index = PackageIndex::from_bytes_dat(dat_bytes.into_inner()).unwrap();
+        let pkg_index = PackageIndex::from_bytes_pkg(pkg_bytes.into_inner()).unwrap();
+
+        // Check
+        for index in [dat_index, pkg_index] {
+            assert_eq!(1000, index.entry_count());
+            assert_eq!(Some(567), index.position_of("data/file567.txt").unwrap());
+            assert_eq!("content999".as_bytes(), index.content_by_path("data/file999.txt").unwrap().unwrap());
+            assert!(!index.entry_exists("data/file1000.txt").unwrap());
+        }
+    }
+
+    #[test]
+    fn entry_by_path_should_outlive_index() {
+        // Prepare
+        let index = PackageIndex::from_path_pkg(PKG_SOURCE_PATH).unwrap();
+
+        // Execute
+        let entry = index.entry_by_path("test1.txt").unwrap().unwrap();
+        drop(index);
+
+        // Check
+        assert_eq!("test1.txt", entry.inner_path());
+        assert_eq!("test001".as_bytes(), entry.content().unwrap());
+    }
+
+    #[test]
+    fn into_package_should_create_editable_package() {
+        // Prepare
+        let index = PackageIndex::from_path_dat(DAT_SOURCE_PATH).unwrap();
+
+        // Execute
+        let mut package = index.into_package().unwrap();
+        package.remove_entry("test2.txt");
+
+        // Check
+        assert_eq!(vec!["test1.txt", "test3.txt"], package.inner_paths());
+        assert_eq!("test003".as_bytes(), package.content_by_path("test3.txt").unwrap());
+    }
+
+    #[test]
+    fn index_should_fail_on_truncated_package() {
+        // Prepare
+        let mut dat_bytes = std::fs::read(DAT_SOURCE_PATH).unwrap();
+        let mut pkg_bytes = std::fs::read(PKG_SOURCE_PATH).unwrap();
+        dat_bytes.truncate(8);
+        pkg_bytes.truncate(40);
+
+        // Execute
+        let dat_result = PackageIndex::from_bytes_dat(dat_bytes);
+        let pkg_result = PackageIndex::from_bytes_pkg(pkg_bytes);
+
+        // Check
+        assert!(dat_result.is_err());
+        assert!(pkg_result.is_err());
+    }
+
+    #[test]
+    fn entry_at_should_fail_on_entry_out_of_bounds() {
+        // Prepare
+        let mut bytes = std::fs::read(DAT_SOURCE_PATH).unwrap();
+        bytes.truncate(bytes.len() - 1);
+        let index = PackageIndex::from_bytes_dat(bytes).unwrap();
+
+        // Execute
+        let result = index.entry_at(2);
+
+        // Check
+        assert!(result.is_err());
+        assert!(index.entry_at(0).is_ok());
+    }
+
+    fn create_colliding_package() -> Package {
+        let mut package = Package::new();
+        package.add_entry(PackageEntry::from_string("data/a.xml", "lower")).unwrap();
+        package.add_entry(PackageEntry::from_string("data/b.xml", "other")).unwrap();
+        package.add_entry(PackageEntry::from_string("data/A.xml", "upper")).unwrap();
+        package
+    }
+
+    #[test]
+    fn entries_by_path_hash_should_return_matching_entries() {
+        // Prepare
+        let package = create_colliding_package();
+        let mut dat_bytes = Cursor::new(Vec::new());
+        let mut pkg_bytes = Cursor::new(Vec::new());
+        package.to_output_dat(&mut dat_bytes).unwrap();
+        package.to_output_pkg(&mut pkg_bytes).unwrap();
+        let dat_index = PackageIndex::from_bytes_dat(dat_bytes.into_inner()).unwrap();
+        let pkg_index = PackageIndex::from_bytes_pkg(pkg_bytes.into_inner()).unwrap();
+
+        for index in [dat_index, pkg_index] {
+            // Execute
+            let result = index.entries_by_path_hash(calculate_path_hash("data/a.xml")).unwrap();
+
+            // Check
+            let paths = result.iter().map(|entry| entry.inner_path()).collect::<Vec<&str>>();
+            assert_eq!(vec!["data/a.xml", "data/A.xml"], paths);
+            assert!(index.entries_by_path_hash(0).unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn path_hash_collisions_should_report_colliding_paths() {
+        // Prepare
+        let package = create_colliding_package();
+        let mut dat_bytes = Cursor::new(Vec::new());
+        let mut pkg_bytes = Cursor::new(Vec::new());
+        package.to_output_dat(&mut dat_bytes).unwrap();
+        package.to_output_pkg(&mut pkg_bytes).unwrap();
+        let dat_index = PackageIndex::from_bytes_dat(dat_bytes.into_inner()).unwrap();
+        let pkg_index = PackageIndex::from_bytes_pkg(pkg_bytes.into_inner()).unwrap();
+
+        for index in [dat_index, pkg_index] {
+            // Execute
+            let result = index.path_hash_collisions().unwrap();
+
+            // Check
+            assert_eq!(package.path_hash_collisions(), result);
+        }
+    }
+}
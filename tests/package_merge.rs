@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod test_package_merge {
+    use ftldat::{MergePolicy, Package, PackageEntry};
+
+    const SOURCE_PATH: &str = "./tests-resources/test.dat";
+
+    fn create_mod_package() -> Package {
+        let mut package = Package::new();
+        package.put_entry(PackageEntry::from_string("test4.txt", "mod004"));
+        package.put_entry(PackageEntry::from_string("test2.txt", "mod002"));
+        package
+    }
+
+    #[test]
+    fn merge_with_overwrite_should_replace_existing_entries_in_place() {
+        // Prepare
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+
+        // Execute
+        let report = package.merge(create_mod_package(), MergePolicy::Overwrite).unwrap();
+
+        // Check
+        assert_eq!(vec!["test4.txt"], report.added());
+        assert_eq!(vec!["test2.txt"], report.overwritten());
+        assert!(report.skipped().is_empty());
+        assert_eq!(vec!["test1.txt", "test2.txt", "test3.txt", "test4.txt"], package.inner_paths());
+        assert_eq!("mod002".as_bytes(), package.content_by_path("test2.txt").unwrap());
+        assert_eq!("test003".as_bytes(), package.content_by_path("test3.txt").unwrap());
+    }
+
+    #[test]
+    fn merge_with_keep_existing_should_skip_conflicting_entries() {
+        // Prepare
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+
+        // Execute
+        let report = package.merge(create_mod_package(), MergePolicy::KeepExisting).unwrap();
+
+        // Check
+        assert_eq!(vec!["test4.txt"], report.added());
+        assert!(report.overwritten().is_empty());
+        assert_eq!(vec!["test2.txt"], report.skipped());
+        assert_eq!("test002".as_bytes(), package.content_by_path("test2.txt").unwrap());
+        assert_eq!("mod004".as_bytes(), package.content_by_path("test4.txt").unwrap());
+    }
+
+    #[test]
+    fn merge_with_error_on_conflict_should_fail_without_changes() {
+        // Prepare
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+
+        // Execute
+        let result = package.merge(create_mod_package(), MergePolicy::ErrorOnConflict);
+
+        // Check
+        let error = result.unwrap_err();
+        assert_eq!(vec!["test2.txt"], error.inner_paths());
+        assert_eq!(3, package.entry_count());
+        assert!(!package.entry_exists("test4.txt"));
+    }
+
+    #[test]
+    fn merge_with_error_on_conflict_should_succeed_without_conflicts() {
+        // Prepare
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+        let mut other = Package::new();
+        other.put_entry(PackageEntry::from_string("test4.txt", "mod004"));
+
+        // Execute
+        let report = package.merge(other, MergePolicy::ErrorOnConflict).unwrap();
+
+        // Check
+        assert_eq!(vec!["test4.txt"], report.added());
+        assert_eq!(4, package.entry_count());
+    }
+
+    #[test]
+    fn merge_should_keep_lazy_content_of_merged_package() {
+        // Prepare
+        let mut package = Package::new();
+        let other = Package::from_path_pkg("./tests-resources/test.pkg").unwrap();
+
+        // Execute
+        let report = package.merge(other, MergePolicy::Overwrite).unwrap();
+
+        // Check
+        assert_eq!(3, report.added().len());
+        assert_eq!("test001".as_bytes(), package.content_by_path("test1.txt").unwrap());
+    }
+}
@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod test_package_patch {
+    use ftldat::{Package, PackageEntry, PackagePatch};
+    use ftldat::error::{PatchApplyError, PatchReadError};
+
+    const SOURCE_PATH: &str = "./tests-resources/test.dat";
+
+    fn create_target_package() -> Package {
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+        package.remove_entry("test1.txt");
+        package.put_entry(PackageEntry::from_string("test2.txt", "test002 was modified by a patch"));
+        package.put_entry(PackageEntry::from_string("test4.txt", "test004"));
+        package
+    }
+
+    fn assert_same_entries(expected: &Package, actual: &Package) {
+        let diff = expected.diff(actual).unwrap();
+        assert!(diff.is_empty(), "packages differ:\n{}", diff);
+    }
+
+    #[test]
+    fn create_should_list_changed_entries() {
+        // Prepare
+        let base = Package::from_path_dat(SOURCE_PATH).unwrap();
+        let target = create_target_package();
+
+        // Execute
+        let patch = PackagePatch::create(&base, &target).unwrap();
+
+        // Check
+        assert!(!patch.is_empty());
+        assert_eq!(vec!["test4.txt"], patch.added_paths());
+        assert_eq!(vec!["test1.txt"], patch.removed_paths());
+        assert_eq!(vec!["test2.txt"], patch.modified_paths());
+    }
+
+    #[test]
+    fn patch_should_survive_round_trip_through_file_and_apply_to_base() {
+        // Prepare
+        let tmp_file = tempfile::NamedTempFile::new().unwrap();
+        let base = Package::from_path_dat(SOURCE_PATH).unwrap();
+        let target = create_target_package();
+        PackagePatch::create(&base, &target).unwrap()
+            .to_path(tmp_file.path())
+            .unwrap();
+
+        // Execute
+        let patch = PackagePatch::from_path(tmp_file.path()).unwrap();
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+        let result = patch.apply(&mut package);
+
+        // Check
+        assert!(result.is_ok());
+        assert_same_entries(&target, &package);
+        assert_eq!(vec!["test2.txt", "test3.txt", "test4.txt"], package.inner_paths());
+    }
+
+    #[test]
+    fn apply_should_refuse_mismatching_base_without_changes() {
+        // Prepare
+        let base = Package::from_path_dat(SOURCE_PATH).unwrap();
+        let patch = PackagePatch::create(&base, &create_target_package()).unwrap();
+
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+        package.put_entry(PackageEntry::from_string("test2.txt", "test00X"));
+
+        // Execute
+        let result = patch.apply(&mut package);
+
+        // Check
+        assert!(matches!(result, Err(PatchApplyError::BaseMismatchError { .. })));
+        assert_eq!(vec!["test1.txt", "test2.txt", "test3.txt"], package.inner_paths());
+        assert_eq!("test00X".as_bytes(), package.content_by_path("test2.txt").unwrap());
+    }
+
+    #[test]
+    fn apply_should_refuse_base_missing_removed_entry() {
+        // Prepare
+        let base = Package::from_path_dat(SOURCE_PATH).unwrap();
+        let patch = PackagePatch::create(&base, &create_target_package()).unwrap();
+
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+        package.remove_entry("test1.txt");
+
+        // Execute
+        let result = patch.apply(&mut package);
+
+        // Check
+        assert!(matches!(result, Err(PatchApplyError::MissingBaseEntryError(_))));
+    }
+
+    #[test]
+    fn from_input_should_fail_for_non_patch_file() {
+        let file = std::fs::File::open(SOURCE_PATH).unwrap();
+
+        let result = PackagePatch::from_input(file);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn from_input_should_fail_for_truncated_patch_with_huge_counts() {
+        // Prepare
+        let mut bytes = b"FTLPATCH".to_vec();
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+
+        // Execute
+        let result = PackagePatch::from_input(bytes.as_slice());
+
+        // Check
+        assert!(matches!(result, Err(PatchReadError::IoError(_))));
+    }
+}
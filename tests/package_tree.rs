@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod test_package_tree {
+    use ftldat::{Package, PackageEntry};
+
+    fn create_nested_package() -> Package {
+        let mut package = Package::new();
+        package.put_entry(PackageEntry::from_string("img/ship/kestrel_base.png", "12345"));
+        package.put_entry(PackageEntry::from_string("data/blueprints.xml", "123"));
+        package.put_entry(PackageEntry::from_string("img/ship/interior/room.png", "12"));
+        package.put_entry(PackageEntry::from_string("img/shipyard.png", "1"));
+        package.put_entry(PackageEntry::from_string("readme.txt", "1234"));
+        package
+    }
+
+    #[test]
+    fn directory_tree_should_list_immediate_children() {
+        // Prepare
+        let package = create_nested_package();
+
+        // Execute
+        let root = package.directory_tree();
+
+        // Check
+        assert_eq!("", root.path());
+        let directories: Vec<&str> = root.directories()
+            .map(|directory| directory.name())
+            .collect();
+        assert_eq!(vec!["data", "img"], directories);
+        let files: Vec<&str> = root.files().iter()
+            .map(|entry| entry.inner_path())
+            .collect();
+        assert_eq!(vec!["readme.txt"], files);
+    }
+
+    #[test]
+    fn directory_tree_should_be_empty_for_empty_package() {
+        let package = Package::new();
+
+        let root = package.directory_tree();
+
+        assert_eq!(0, root.entry_count());
+        assert_eq!(0, root.directories().count());
+    }
+
+    #[test]
+    fn directory_should_return_subtree_with_full_paths() {
+        // Prepare
+        let package = create_nested_package();
+
+        // Execute
+        let result = package.directory("img/ship/");
+
+        // Check
+        let node = result.unwrap();
+        assert_eq!("img/ship", node.path());
+        assert_eq!("ship", node.name());
+        assert_eq!(1, node.files().len());
+        assert_eq!("img/ship/kestrel_base.png", node.files()[0].inner_path());
+        assert_eq!("img/ship/interior", node.directory("interior").unwrap().path());
+    }
+
+    #[test]
+    fn directory_should_return_none_when_no_entries_under_directory() {
+        let package = create_nested_package();
+
+        assert!(package.directory("audio").is_none());
+        // `img/ship` must not match `img/shipyard.png`
+        assert!(package.directory("img/shipy").is_none());
+    }
+
+    #[test]
+    fn directory_should_aggregate_count_and_size_recursively() {
+        // Prepare
+        let package = create_nested_package();
+
+        // Execute
+        let img = package.directory("img").unwrap();
+        let root = package.directory_tree();
+
+        // Check
+        assert_eq!(3, img.entry_count());
+        assert_eq!(8, img.total_size().unwrap());
+        assert_eq!(5, root.entry_count());
+        assert_eq!(15, root.total_size().unwrap());
+    }
+
+    #[test]
+    fn walk_should_visit_directories_depth_first() {
+        // Prepare
+        let package = create_nested_package();
+        let root = package.directory_tree();
+
+        // Execute
+        let result: Vec<&str> = root.walk()
+            .map(|directory| directory.path())
+            .collect();
+
+        // Check
+        assert_eq!(vec!["", "data", "img", "img/ship", "img/ship/interior"], result);
+    }
+}
@@ -1,6 +1,9 @@
 #[cfg(test)]
 mod test_pkg_reader {
-    use ftldat::Package;
+    use std::fs::File;
+    use std::io::{BufReader, Cursor};
+
+    use ftldat::{Package, PkgReadWarning, PkgReader, ReadStrategy};
 
     const SOURCE_PATH: &str = "./tests-resources/test.pkg";
 
@@ -31,4 +34,190 @@ mod test_pkg_reader {
         assert_eq!("test002", contents[1]);
         assert_eq!("test003", contents[2]);
     }
+
+    fn assert_is_test_package(package: &Package) {
+        assert_eq!(vec!["test1.txt", "test2.txt", "test3.txt"], package.inner_paths());
+        assert_eq!("test001".as_bytes(), package.content_by_path("test1.txt").unwrap());
+        assert_eq!("test002".as_bytes(), package.content_by_path("test2.txt").unwrap());
+        assert_eq!("test003".as_bytes(), package.content_by_path("test3.txt").unwrap());
+    }
+
+    #[test]
+    fn reader_should_read_package_from_bytes() {
+        let bytes = std::fs::read(SOURCE_PATH).unwrap();
+
+        let result = Package::from_bytes_pkg(bytes);
+
+        assert_is_test_package(&result.unwrap());
+    }
+
+    #[test]
+    fn reader_should_read_package_from_input_lazily() {
+        let input = Cursor::new(std::fs::read(SOURCE_PATH).unwrap());
+
+        let result = Package::from_input_pkg(input, ReadStrategy::Lazy);
+
+        assert_is_test_package(&result.unwrap());
+    }
+
+    #[test]
+    fn reader_should_read_package_from_input_eagerly() {
+        let input = BufReader::new(File::open(SOURCE_PATH).unwrap());
+
+        let result = Package::from_input_pkg(input, ReadStrategy::Eager);
+
+        assert_is_test_package(&result.unwrap());
+    }
+
+    #[test]
+    fn reader_should_fail_when_entry_lies_outside_of_truncated_package() {
+        let mut bytes = std::fs::read(SOURCE_PATH).unwrap();
+        bytes.truncate(bytes.len() - 1);
+
+        let result = Package::from_bytes_pkg(bytes);
+
+        assert!(result.is_err());
+    }
+
+    const FIRST_ENTRY_HEADER_OFFSET: usize = 16;
+
+    /// Rewrites the test package with `extra_index_size` unknown bytes appended to its header, and
+    /// `extra_entry_size` unknown bytes appended to each entry header.
+    fn extend_headers(bytes: &[u8], extra_index_size: u16, extra_entry_size: u16) -> Vec<u8> {
+        let index_size = u16::from_be_bytes([bytes[4], bytes[5]]) + extra_index_size;
+        let entry_size = u16::from_be_bytes([bytes[6], bytes[7]]);
+        let entry_count = u32::from_be_bytes(bytes[8..12].try_into().unwrap()) as usize;
+        let shift = extra_index_size as u32 + extra_entry_size as u32 * entry_count as u32;
+
+        let mut result = bytes[0..4].to_vec();
+        result.extend_from_slice(&index_size.to_be_bytes());
+        result.extend_from_slice(&(entry_size + extra_entry_size).to_be_bytes());
+        result.extend_from_slice(&bytes[8..16]);
+        result.extend(vec![0xFFu8; extra_index_size as usize]);
+        for i in 0..entry_count {
+            let start = FIRST_ENTRY_HEADER_OFFSET + entry_size as usize * i;
+            let mut header = bytes[start..start + entry_size as usize].to_vec();
+            let data_offset = u32::from_be_bytes(header[8..12].try_into().unwrap()) + shift;
+            header[8..12].copy_from_slice(&data_offset.to_be_bytes());
+            result.extend(header);
+            result.extend(vec![0xFFu8; extra_entry_size as usize]);
+        }
+        result.extend_from_slice(&bytes[FIRST_ENTRY_HEADER_OFFSET + entry_size as usize * entry_count..]);
+
+        result
+    }
+
+    #[test]
+    fn strict_reader_should_fail_on_path_hash_mismatch() {
+        let mut bytes = std::fs::read(SOURCE_PATH).unwrap();
+        bytes[FIRST_ENTRY_HEADER_OFFSET] ^= 0xFF;
+
+        let result = Package::from_bytes_pkg(bytes);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn lenient_reader_should_report_path_hash_mismatch() {
+        // Prepare
+        let mut bytes = std::fs::read(SOURCE_PATH).unwrap();
+        bytes[FIRST_ENTRY_HEADER_OFFSET] ^= 0xFF;
+
+        // Execute
+        let (package, warnings) = Package::from_bytes_pkg_lenient(bytes).unwrap();
+
+        // Check
+        assert_is_test_package(&package);
+        assert_eq!(1, warnings.len());
+        assert!(matches!(&warnings[0], PkgReadWarning::PathHashMismatch { inner_path, .. } if inner_path == "test1.txt"));
+    }
+
+    #[test]
+    fn reader_should_report_unknown_entry_options() {
+        // Prepare
+        let mut bytes = std::fs::read(SOURCE_PATH).unwrap();
+        bytes[FIRST_ENTRY_HEADER_OFFSET + 20 + 4] = 0x80;
+
+        // Execute
+        let (index, warnings) = PkgReader::new().read_index_with_warnings(&mut Cursor::new(&bytes)).unwrap();
+
+        // Check
+        assert_eq!(3, index.len());
+        assert_eq!(
+            vec![PkgReadWarning::UnknownEntryOptions { inner_path: "test2.txt".to_string(), entry_options: 0x80 }],
+            warnings
+        );
+    }
+
+    #[test]
+    fn strict_reader_should_fail_on_extended_headers() {
+        let bytes = extend_headers(&std::fs::read(SOURCE_PATH).unwrap(), 4, 8);
+
+        let result = Package::from_bytes_pkg(bytes);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn lenient_reader_should_skip_unknown_header_bytes() {
+        // Prepare
+        let bytes = extend_headers(&std::fs::read(SOURCE_PATH).unwrap(), 4, 8);
+
+        // Execute
+        let (package, warnings) = Package::from_bytes_pkg_lenient(bytes).unwrap();
+
+        // Check
+        assert_is_test_package(&package);
+        assert_eq!(vec![
+            PkgReadWarning::ExtendedIndexHeader { index_size: 20 },
+            PkgReadWarning::ExtendedEntryHeader { entry_size: 28 },
+        ], warnings);
+    }
+
+    #[test]
+    fn lenient_reader_should_fail_on_truncated_headers() {
+        let mut bytes = std::fs::read(SOURCE_PATH).unwrap();
+        bytes[7] = 12;
+
+        let result = Package::from_bytes_pkg_lenient(bytes);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn lenient_package_from_path_should_report_no_warnings_for_valid_package() {
+        // Execute
+        let (package, warnings) = Package::from_path_pkg_lenient(SOURCE_PATH).unwrap();
+
+        // Check
+        assert_is_test_package(&package);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn reader_should_fail_without_allocating_for_damaged_counts() {
+        // Prepare
+        let bytes = std::fs::read(SOURCE_PATH).unwrap();
+        let mut damaged_entry_count = bytes.clone();
+        damaged_entry_count[8..12].copy_from_slice(&u32::MAX.to_be_bytes());
+        let mut damaged_path_region_size = bytes.clone();
+        damaged_path_region_size[12..16].copy_from_slice(&u32::MAX.to_be_bytes());
+
+        // Execute
+        let entry_count_result = Package::from_bytes_pkg(damaged_entry_count);
+        let path_region_size_result = Package::from_bytes_pkg(damaged_path_region_size);
+
+        // Check
+        assert!(entry_count_result.is_err());
+        assert!(path_region_size_result.is_err());
+    }
+
+    #[test]
+    fn warnings_should_display_headers_smaller_than_expected() {
+        let index_warning = PkgReadWarning::ExtendedIndexHeader { index_size: 0 };
+        let entry_warning = PkgReadWarning::ExtendedEntryHeader { entry_size: 0 };
+
+        assert_eq!("header: skipped 0 unknown bytes of header", index_warning.to_string());
+        assert_eq!("header: skipped 0 unknown bytes of each entry header", entry_warning.to_string());
+    }
 }
@@ -1,8 +1,9 @@
 #[cfg(test)]
 mod test_pkg_writer {
+    use std::io::Cursor;
     use std::path::Path;
 
-    use ftldat::{Package, PackageEntry};
+    use ftldat::{Package, PackageEntry, PkgWriter};
 
     const SOURCE_PATH: &str = "./tests-resources/test.pkg";
 
@@ -67,4 +68,67 @@ mod test_pkg_writer {
         assert_eq!(order_before_write[1], order_after_write[1]);
         assert_eq!(order_before_write[2], order_after_write[2]);
     }
-}
\ No newline at end of file
+
+    fn create_package_with_duplicates() -> Package {
+        let mut package = Package::new();
+        package.put_entry(PackageEntry::from_string("img/a.png", "duplicated content"));
+        package.put_entry(PackageEntry::from_string("img/b.png", "unique content"));
+        package.put_entry(PackageEntry::from_string("img/c.png", "duplicated content"));
+        package.put_entry(PackageEntry::from_string("img/d.png", "duplicated content"));
+        package
+    }
+
+    #[test]
+    fn writer_without_deduplication_should_report_nothing_saved() {
+        // Prepare
+        let package = create_package_with_duplicates();
+
+        // Execute
+        let report = PkgWriter::new()
+            .write_package(&package, Cursor::new(Vec::new()))
+            .unwrap();
+
+        // Check
+        assert_eq!(0, report.deduplicated_entry_count());
+        assert_eq!(0, report.bytes_saved());
+    }
+
+    #[test]
+    fn writer_with_deduplication_should_write_identical_content_once() {
+        // Prepare
+        let package = create_package_with_duplicates();
+        let mut plain_output = Cursor::new(Vec::new());
+        let mut deduplicated_output = Cursor::new(Vec::new());
+        PkgWriter::new().write_package(&package, &mut plain_output).unwrap();
+
+        // Execute
+        let report = PkgWriter::with_deduplication()
+            .write_package(&package, &mut deduplicated_output)
+            .unwrap();
+
+        // Check
+        assert_eq!(2, report.deduplicated_entry_count());
+        assert_eq!(36, report.bytes_saved());
+        assert_eq!(
+            plain_output.get_ref().len() as u64 - report.bytes_saved(),
+            deduplicated_output.get_ref().len() as u64
+        );
+    }
+
+    #[test]
+    fn reader_should_read_entries_sharing_data_regions() {
+        // Prepare
+        let tmp_file = tempfile::NamedTempFile::new().unwrap();
+        let package = create_package_with_duplicates();
+        package.to_path(tmp_file.path(), PkgWriter::with_deduplication()).unwrap();
+
+        // Execute
+        let result = Package::from_path_pkg(tmp_file.path()).unwrap();
+
+        // Check
+        assert_eq!(package.inner_paths(), result.inner_paths());
+        for inner_path in package.inner_paths() {
+            assert_eq!(package.content_by_path(&inner_path), result.content_by_path(&inner_path));
+        }
+    }
+}
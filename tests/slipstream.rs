@@ -0,0 +1,319 @@
+#[cfg(all(test, feature = "slipstream"))]
+mod test_slipstream {
+    use std::io::{Cursor, Write};
+
+    use zip::write::FileOptions;
+    use zip::ZipWriter;
+
+    use ftldat::{FtlMod, InstallJournal, ModFileAction, Package, PackageEntry};
+    use ftldat::error::ModReadError;
+
+    fn create_mod(files: &[(&str, &str)]) -> FtlMod {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (path, content) in files {
+            writer.start_file(*path, FileOptions::default()).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+
+        let archive = writer.finish().unwrap();
+        FtlMod::from_input(Cursor::new(archive.into_inner())).unwrap()
+    }
+
+    fn create_package() -> Package {
+        let mut package = Package::new();
+        package.add_entry(PackageEntry::from_string("data/blueprints.xml", "<blueprints/>")).unwrap();
+        package.add_entry(PackageEntry::from_string("img/ship/kestral_base.png", "png")).unwrap();
+        package
+    }
+
+    fn content_string(package: &Package, inner_path: &str) -> String {
+        String::from_utf8(package.content_by_path(inner_path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn mod_should_map_files_onto_inner_paths() {
+        // Execute
+        let ftl_mod = create_mod(&[
+            ("data/events.xml", "<events/>"),
+            ("audio/music/track.ogg", "ogg"),
+            ("img/ship/kestral_base.png", "modded png"),
+            ("fonts/font.ttf", "ttf"),
+            ("mod-appendix/metadata.xml", "<metadata/>"),
+            ("readme.txt", "readme"),
+            ("img/Thumbs.db", "junk"),
+        ]);
+
+        // Check
+        let paths = ftl_mod.files().iter().map(|file| file.inner_path()).collect::<Vec<&str>>();
+        assert_eq!(vec!["data/events.xml", "audio/music/track.ogg", "img/ship/kestral_base.png", "fonts/font.ttf"], paths);
+        assert_eq!(vec!["mod-appendix/metadata.xml", "readme.txt", "img/Thumbs.db"], ftl_mod.ignored_paths());
+    }
+
+    #[test]
+    fn mod_should_recognize_append_suffixes() {
+        // Execute
+        let ftl_mod = create_mod(&[
+            ("data/blueprints.xml.append", ""),
+            ("data/events.append.xml", ""),
+            ("data/sector_data.xml.rawappend", ""),
+            ("data/animations.xml.rawclobber", ""),
+        ]);
+
+        // Check
+        let targets = ftl_mod.files().iter()
+            .map(|file| (file.inner_path(), file.action()))
+            .collect::<Vec<(&str, ModFileAction)>>();
+        assert_eq!(vec![
+            ("data/blueprints.xml", ModFileAction::Append),
+            ("data/events.xml", ModFileAction::Append),
+            ("data/sector_data.xml", ModFileAction::RawAppend),
+            ("data/animations.xml", ModFileAction::Replace),
+        ], targets);
+    }
+
+    #[test]
+    fn apply_mod_should_add_and_overwrite_entries() {
+        // Prepare
+        let mut package = create_package();
+        let ftl_mod = create_mod(&[
+            ("img/ship/kestral_base.png", "modded png"),
+            ("data/events.xml", "<events/>"),
+        ]);
+
+        // Execute
+        let report = package.apply_mod(&ftl_mod).unwrap();
+
+        // Check
+        assert_eq!(vec!["data/events.xml"], report.added());
+        assert_eq!(vec!["img/ship/kestral_base.png"], report.overwritten());
+        assert_eq!("modded png", content_string(&package, "img/ship/kestral_base.png"));
+        assert_eq!("<events/>", content_string(&package, "data/events.xml"));
+    }
+
+    #[test]
+    fn apply_mod_should_append_raw_text() {
+        // Prepare
+        let mut package = create_package();
+        let ftl_mod = create_mod(&[("data/blueprints.xml.rawappend", "<shipBlueprint/>")]);
+
+        // Execute
+        let report = package.apply_mod(&ftl_mod).unwrap();
+
+        // Check
+        assert_eq!(vec!["data/blueprints.xml"], report.appended());
+        assert_eq!("<blueprints/>\n<shipBlueprint/>\n", content_string(&package, "data/blueprints.xml"));
+    }
+
+    #[test]
+    fn apply_mod_should_skip_append_to_missing_entry() {
+        // Prepare
+        let mut package = create_package();
+        let ftl_mod = create_mod(&[("data/missing.xml.rawappend", "<event/>")]);
+
+        // Execute
+        let report = package.apply_mod(&ftl_mod).unwrap();
+
+        // Check
+        assert_eq!(vec!["data/missing.xml.rawappend"], report.skipped());
+        assert!(!package.entry_exists("data/missing.xml"));
+    }
+
+    #[test]
+    fn apply_mods_should_let_later_mods_win() {
+        // Prepare
+        let mut package = create_package();
+        let first_mod = create_mod(&[("data/events.xml", "<first/>")]);
+        let second_mod = create_mod(&[("data/events.xml", "<second/>")]);
+
+        // Execute
+        let reports = package.apply_mods([&first_mod, &second_mod]).unwrap();
+
+        // Check
+        assert_eq!(vec!["data/events.xml"], reports[0].added());
+        assert_eq!(vec!["data/events.xml"], reports[1].overwritten());
+        assert_eq!("<second/>", content_string(&package, "data/events.xml"));
+    }
+
+    #[test]
+    fn mod_should_fail_on_invalid_archive() {
+        let result = FtlMod::from_input(Cursor::new(b"not a zip".to_vec()));
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn append_xml_should_insert_into_root_element() {
+        // Prepare
+        let mut package = Package::new();
+        package.add_entry(PackageEntry::from_string(
+            "data/blueprints.xml",
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\r\n<FTL>\r\n<blueprint name=\"A\"/>\r\n</FTL>\r\n",
+        )).unwrap();
+
+        // Execute
+        let result = package.append_xml("data/blueprints.xml", b"<blueprint name=\"B\"/>");
+
+        // Check
+        assert!(result.is_ok());
+        assert_eq!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\r\n<FTL>\r\n<blueprint name=\"A\"/>\n<blueprint name=\"B\"/>\n</FTL>\n",
+            content_string(&package, "data/blueprints.xml")
+        );
+    }
+
+    #[test]
+    fn append_xml_should_strip_declaration_and_root_element_of_appended_content() {
+        // Prepare
+        let mut package = Package::new();
+        package.add_entry(PackageEntry::from_string("data/events.xml", "<event name=\"A\"/>")).unwrap();
+        let appended_content = "\u{feff}<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<FTL>\n<event name=\"B\"/>\n</FTL>\n";
+
+        // Execute
+        let result = package.append_xml("data/events.xml", appended_content.as_bytes());
+
+        // Check
+        assert!(result.is_ok());
+        assert_eq!("<event name=\"A\"/>\n<event name=\"B\"/>\n", content_string(&package, "data/events.xml"));
+    }
+
+    #[test]
+    fn append_xml_should_keep_other_root_elements_of_appended_content() {
+        // Prepare
+        let mut package = Package::new();
+        package.add_entry(PackageEntry::from_string("data/events.xml", "<FTL>\n</FTL>")).unwrap();
+
+        // Execute
+        let result = package.append_xml("data/events.xml", b"<FTLX/>\n<eventList/>");
+
+        // Check
+        assert!(result.is_ok());
+        assert_eq!("<FTL>\n<FTLX/>\n<eventList/>\n</FTL>\n", content_string(&package, "data/events.xml"));
+    }
+
+    #[test]
+    fn append_xml_should_fail_for_missing_entry() {
+        let mut package = Package::new();
+
+        let result = package.append_xml("data/missing.xml", b"<event/>");
+
+        assert!(result.is_err());
+        assert!(!package.entry_exists("data/missing.xml"));
+    }
+
+    #[test]
+    fn apply_mod_should_append_xml_inside_root_element() {
+        // Prepare
+        let mut package = Package::new();
+        package.add_entry(PackageEntry::from_string("data/blueprints.xml", "<FTL>\n<blueprint/>\n</FTL>\n")).unwrap();
+        let ftl_mod = create_mod(&[("data/blueprints.xml.append", "<?xml version=\"1.0\"?>\n<shipBlueprint/>")]);
+
+        // Execute
+        let report = package.apply_mod(&ftl_mod).unwrap();
+
+        // Check
+        assert_eq!(vec!["data/blueprints.xml"], report.appended());
+        assert_eq!("<FTL>\n<blueprint/>\n<shipBlueprint/>\n</FTL>\n", content_string(&package, "data/blueprints.xml"));
+    }
+
+    #[test]
+    fn apply_mod_should_report_unmatched_selectors() {
+        // Prepare
+        let mut package = create_package();
+        let ftl_mod = create_mod(&[
+            ("data/blueprints.xml.append", "<mod:findName type=\"shipBlueprint\" name=\"MISSING\"><mod:removeTag/></mod:findName>"),
+        ]);
+
+        // Execute
+        let report = package.apply_mod(&ftl_mod).unwrap();
+
+        // Check
+        assert_eq!(
+            [("data/blueprints.xml".to_string(), "<mod:findName type=\"shipBlueprint\" name=\"MISSING\">".to_string())],
+            report.unmatched_selectors()
+        );
+    }
+
+
+    #[test]
+    fn journaled_mod_should_uninstall_cleanly() {
+        // Prepare
+        let mut package = create_package();
+        let mut journal = InstallJournal::new();
+        let ftl_mod = create_mod(&[
+            ("data/blueprints.xml.append", "<blueprint name=\"MODDED\"/>"),
+            ("img/ship/kestral_base.png", "modded png"),
+            ("data/events.xml", "<events/>"),
+        ]);
+        let mut transaction = package.begin_install(&mut journal, "mod.ftl").unwrap();
+        transaction.apply_mod(&ftl_mod).unwrap();
+        transaction.commit().unwrap();
+
+        // Execute
+        let result = package.uninstall_mod(&mut journal, "mod.ftl");
+
+        // Check
+        assert!(result.is_ok());
+        assert_eq!(vec!["data/blueprints.xml", "img/ship/kestral_base.png"], package.inner_paths());
+        assert_eq!("<blueprints/>", content_string(&package, "data/blueprints.xml"));
+        assert_eq!("png", content_string(&package, "img/ship/kestral_base.png"));
+    }
+
+
+    #[test]
+    fn mod_should_fail_on_file_larger_than_limit() {
+        // Prepare
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer.start_file("data/events.xml", FileOptions::default()).unwrap();
+        writer.write_all(b"<event/>").unwrap();
+        let mut archive = writer.finish().unwrap().into_inner();
+
+        // Claim a huge uncompressed size in the central directory
+        let header = archive.windows(4).position(|window| window == b"PK\x01\x02").unwrap();
+        archive[header + 24..header + 28].copy_from_slice(&0x7FFF_FFFFu32.to_le_bytes());
+
+        // Execute
+        let result = FtlMod::from_input(Cursor::new(archive));
+
+        // Check
+        assert!(matches!(result, Err(ModReadError::FileTooLargeError(path)) if path == "data/events.xml"));
+    }
+
+
+    #[test]
+    fn apply_mod_should_append_to_entry_added_by_same_mod() {
+        // Prepare
+        let mut package = create_package();
+        let ftl_mod = create_mod(&[
+            ("data/events.xml", "<events/>"),
+            ("data/events.xml.rawappend", "<event/>"),
+        ]);
+
+        // Execute
+        let report = package.apply_mod(&ftl_mod).unwrap();
+
+        // Check
+        assert_eq!(vec!["data/events.xml"], report.added());
+        assert_eq!(vec!["data/events.xml"], report.appended());
+        assert_eq!("<events/>\n<event/>\n", content_string(&package, "data/events.xml"));
+    }
+
+    #[test]
+    fn apply_mod_should_leave_package_unchanged_when_file_fails() {
+        // Prepare
+        let mut package = create_package();
+        let ftl_mod = create_mod(&[
+            ("data/events.xml", "<events/>"),
+            ("img/ship/kestral_base.png", "modded png"),
+            ("data/blueprints.xml.append", "<unclosed>"),
+        ]);
+
+        // Execute
+        let result = package.apply_mod(&ftl_mod);
+
+        // Check
+        assert!(result.is_err());
+        assert!(!package.entry_exists("data/events.xml"));
+        assert_eq!("png", content_string(&package, "img/ship/kestral_base.png"));
+        assert_eq!("<blueprints/>", content_string(&package, "data/blueprints.xml"));
+    }
+}
@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod test_vanilla {
+    use ftldat::{Game, Identification, Manifest, Package, PackageEntry, ReleaseDatabase};
+
+    const SOURCE_PATH: &str = "./tests-resources/test.dat";
+
+    fn create_database() -> ReleaseDatabase {
+        let mut old_release = Package::from_path_dat(SOURCE_PATH).unwrap();
+        old_release.remove_entry("test3.txt");
+
+        let mut database = ReleaseDatabase::new();
+        database.add_release(Game::IntoTheBreach, "1.0", Manifest::from_package(&old_release).unwrap());
+        database.add_release(
+            Game::IntoTheBreach, "1.1",
+            Manifest::from_package(&Package::from_path_dat(SOURCE_PATH).unwrap()).unwrap(),
+        );
+        database
+    }
+
+    #[test]
+    fn identify_should_match_vanilla_release() {
+        // Prepare
+        let database = create_database();
+        let package = Package::from_path_pkg("./tests-resources/test.pkg").unwrap();
+
+        // Execute
+        let result = database.identify(&package).unwrap();
+
+        // Check
+        match result {
+            Identification::Vanilla(release) => {
+                assert_eq!(Game::IntoTheBreach, release.game());
+                assert_eq!("1.1", release.version());
+            }
+            other => panic!("expected vanilla release, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn identify_should_list_deviations_from_closest_release() {
+        // Prepare
+        let database = create_database();
+        let mut package = Package::from_path_dat(SOURCE_PATH).unwrap();
+        package.put_entry(PackageEntry::from_string("test2.txt", "modded"));
+
+        // Execute
+        let result = database.identify(&package).unwrap();
+
+        // Check
+        match result {
+            Identification::Unknown { closest: Some((release, deviations)) } => {
+                assert_eq!("1.1", release.version());
+                assert_eq!(vec!["test2.txt"], deviations.mismatched());
+                assert_eq!(1, deviations.deviation_count());
+            }
+            other => panic!("expected unknown release, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn identify_should_report_no_closest_release_for_empty_database() {
+        let package = Package::from_path_dat(SOURCE_PATH).unwrap();
+        let database = ReleaseDatabase::new();
+
+        let result = database.identify(&package).unwrap();
+
+        assert!(matches!(result, Identification::Unknown { closest: None }));
+    }
+}
@@ -0,0 +1,359 @@
+#[cfg(all(test, feature = "slipstream"))]
+mod test_xml_patch {
+    use ftldat::{Package, PackageEntry};
+
+    const INNER_PATH: &str = "data/blueprints.xml";
+    const BLUEPRINTS: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<FTL>
+<weaponBlueprint name="LASER_BURST_1">
+	<type>LASER</type>
+	<damage>1</damage>
+	<shots>2</shots>
+</weaponBlueprint>
+<weaponBlueprint name="LASER_BURST_2">
+	<type>LASER</type>
+	<damage>1</damage>
+	<shots>3</shots>
+</weaponBlueprint>
+<weaponBlueprint name="MISSILES_1">
+	<type>MISSILES</type>
+	<damage>2</damage>
+</weaponBlueprint>
+<blueprintList name="WEAPONS_LASER">
+	<name>LASER_BURST_1</name>
+	<name>LASER_BURST_2</name>
+</blueprintList>
+</FTL>
+"#;
+
+    fn create_package() -> Package {
+        let mut package = Package::new();
+        package.add_entry(PackageEntry::from_string(INNER_PATH, BLUEPRINTS)).unwrap();
+        package
+    }
+
+    fn content_string(package: &Package) -> String {
+        String::from_utf8(package.content_by_path(INNER_PATH).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn find_name_should_set_attributes() {
+        // Prepare
+        let mut package = create_package();
+        let patch = r#"<mod:findName type="weaponBlueprint" name="MISSILES_1">
+	<mod:setAttributes name="MISSILES_RENAMED" rarity="2"/>
+</mod:findName>"#;
+
+        // Execute
+        let report = package.patch_xml(INNER_PATH, patch.as_bytes()).unwrap();
+
+        // Check
+        assert!(report.unmatched_selectors().is_empty());
+        let content = content_string(&package);
+        assert!(content.contains(r#"<weaponBlueprint name="MISSILES_RENAMED" rarity="2">"#));
+        assert!(!content.contains(r#"name="MISSILES_1""#));
+    }
+
+    #[test]
+    fn patch_should_keep_untouched_content_unchanged() {
+        // Prepare
+        let mut package = create_package();
+        let patch = r#"<mod:findName type="weaponBlueprint" name="MISSING"><mod:removeTag/></mod:findName>"#;
+
+        // Execute
+        package.patch_xml(INNER_PATH, patch.as_bytes()).unwrap();
+
+        // Check
+        assert_eq!(BLUEPRINTS, content_string(&package));
+    }
+
+    #[test]
+    fn patch_should_keep_form_of_empty_elements() {
+        // Prepare
+        let mut package = Package::new();
+        package.add_entry(PackageEntry::from_string(INNER_PATH, "<FTL><text name=\"EMPTY\"></text><img/></FTL>")).unwrap();
+        let patch = r#"<mod:findName type="text" name="EMPTY"><mod:setAttributes patched="true"/></mod:findName>"#;
+
+        // Execute
+        package.patch_xml(INNER_PATH, patch.as_bytes()).unwrap();
+
+        // Check
+        assert_eq!("<FTL><text name=\"EMPTY\" patched=\"true\"></text><img/></FTL>", content_string(&package));
+    }
+
+    #[test]
+    fn find_like_should_match_selector_and_remove_tags() {
+        // Prepare
+        let mut package = create_package();
+        let patch = r#"<mod:findLike type="weaponBlueprint">
+	<mod:selector name="LASER_BURST_2"/>
+	<mod:removeTag/>
+</mod:findLike>"#;
+
+        // Execute
+        package.patch_xml(INNER_PATH, patch.as_bytes()).unwrap();
+
+        // Check
+        let content = content_string(&package);
+        assert!(!content.contains(r#"<weaponBlueprint name="LASER_BURST_2">"#));
+        assert!(content.contains(r#"<weaponBlueprint name="LASER_BURST_1">"#));
+        assert!(content.contains("</weaponBlueprint>\n<weaponBlueprint name=\"MISSILES_1\">"));
+    }
+
+    #[test]
+    fn find_like_should_respect_start_and_limit() {
+        // Prepare
+        let mut package = create_package();
+        let patch = r#"<mod:findLike type="weaponBlueprint" start="1" limit="1">
+	<mod:setAttributes patched="true"/>
+</mod:findLike>"#;
+
+        // Execute
+        package.patch_xml(INNER_PATH, patch.as_bytes()).unwrap();
+
+        // Check
+        let content = content_string(&package);
+        assert_eq!(1, content.matches(r#"patched="true""#).count());
+        assert!(content.contains(r#"<weaponBlueprint name="LASER_BURST_2" patched="true">"#));
+    }
+
+    #[test]
+    fn find_with_child_like_should_match_child_text() {
+        // Prepare
+        let mut package = create_package();
+        let patch = r#"<mod:findWithChildLike type="weaponBlueprint" child-type="type">
+	<mod:selector>MISSILES</mod:selector>
+	<mod-append:missiles>1</mod-append:missiles>
+</mod:findWithChildLike>"#;
+
+        // Execute
+        package.patch_xml(INNER_PATH, patch.as_bytes()).unwrap();
+
+        // Check
+        let content = content_string(&package);
+        assert_eq!(1, content.matches("<missiles>1</missiles>").count());
+        assert!(content.contains("<damage>2</damage>\n<missiles>1</missiles></weaponBlueprint>"));
+    }
+
+    #[test]
+    fn mod_overwrite_should_replace_first_matching_child_or_append() {
+        // Prepare
+        let mut package = create_package();
+        let patch = r#"<mod:findName type="weaponBlueprint" name="LASER_BURST_1">
+	<mod-overwrite:damage>5</mod-overwrite:damage>
+	<mod-overwrite:cooldown>10</mod-overwrite:cooldown>
+</mod:findName>"#;
+
+        // Execute
+        package.patch_xml(INNER_PATH, patch.as_bytes()).unwrap();
+
+        // Check
+        let content = content_string(&package);
+        assert!(content.contains("<damage>5</damage>\n\t<shots>2</shots>\n<cooldown>10</cooldown></weaponBlueprint>"));
+        assert_eq!(1, content.matches("<damage>1</damage>").count());
+    }
+
+    #[test]
+    fn nested_find_should_search_children_of_match() {
+        // Prepare
+        let mut package = create_package();
+        let patch = r#"<mod:findName type="blueprintList" name="WEAPONS_LASER">
+	<mod:findLike type="name">
+		<mod:selector>LASER_BURST_2</mod:selector>
+		<mod:setValue>LASER_HEAVY_1</mod:setValue>
+	</mod:findLike>
+	<mod:removeAttributes name=""/>
+</mod:findName>"#;
+
+        // Execute
+        package.patch_xml(INNER_PATH, patch.as_bytes()).unwrap();
+
+        // Check
+        let content = content_string(&package);
+        assert!(content.contains("<blueprintList>\n\t<name>LASER_BURST_1</name>\n\t<name>LASER_HEAVY_1</name>\n</blueprintList>"));
+    }
+
+    #[test]
+    fn find_name_should_default_to_last_match() {
+        // Prepare
+        let mut package = Package::new();
+        package.add_entry(PackageEntry::from_string(INNER_PATH, r#"<event name="A"/><event name="A"/>"#)).unwrap();
+        let patch = r#"<mod:findName name="A"><mod:setAttributes last="true"/></mod:findName>"#;
+
+        // Execute
+        package.patch_xml(INNER_PATH, patch.as_bytes()).unwrap();
+
+        // Check
+        assert_eq!(r#"<event name="A"/><event name="A" last="true"/>"#, content_string(&package));
+    }
+
+    #[test]
+    fn patch_should_append_content_outside_of_find_tags() {
+        // Prepare
+        let mut package = create_package();
+        let patch = r#"<?xml version="1.0" encoding="utf-8"?>
+<mod:findName type="weaponBlueprint" name="MISSILES_1"><mod:removeTag/></mod:findName>
+<weaponBlueprint name="BEAM_1"/>
+"#;
+
+        // Execute
+        package.patch_xml(INNER_PATH, patch.as_bytes()).unwrap();
+
+        // Check
+        let content = content_string(&package);
+        assert!(content.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<FTL>\n"));
+        assert!(content.ends_with("</blueprintList>\n<weaponBlueprint name=\"BEAM_1\"/>\n</FTL>\n"));
+        assert!(!content.contains("MISSILES_1"));
+    }
+
+    #[test]
+    fn patch_should_report_unmatched_selectors() {
+        // Prepare
+        let mut package = create_package();
+        let patch = r#"<mod:findName type="weaponBlueprint" name="MISSING"><mod:removeTag/></mod:findName>
+<mod:findLike type="shipBlueprint"><mod:removeTag/></mod:findLike>"#;
+
+        // Execute
+        let report = package.patch_xml(INNER_PATH, patch.as_bytes()).unwrap();
+
+        // Check
+        assert_eq!(vec![
+            r#"<mod:findName type="weaponBlueprint" name="MISSING">"#,
+            r#"<mod:findLike type="shipBlueprint">"#,
+        ], report.unmatched_selectors());
+    }
+
+    #[test]
+    fn patch_should_fail_on_unmatched_selector_with_panic() {
+        // Prepare
+        let mut package = create_package();
+        let patch = r#"<mod:findName name="LASER_BURST_1"><mod:removeTag/></mod:findName>
+<mod:findName name="MISSING" panic="true"><mod:removeTag/></mod:findName>"#;
+
+        // Execute
+        let result = package.patch_xml(INNER_PATH, patch.as_bytes());
+
+        // Check
+        assert!(result.is_err());
+        assert_eq!(BLUEPRINTS, content_string(&package));
+    }
+
+    #[test]
+    fn patch_should_fail_on_command_outside_of_find_tag() {
+        let mut package = create_package();
+
+        let result = package.patch_xml(INNER_PATH, br#"<mod:setAttributes name="X"/>"#);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn patch_should_fail_on_malformed_xml() {
+        // Prepare
+        let mut package = create_package();
+        package.put_entry(PackageEntry::from_string(INNER_PATH, "<FTL>\n<event>\n</FTL>"));
+
+        // Execute
+        let result = package.patch_xml(INNER_PATH, b"<event/>");
+
+        // Check
+        let error = result.unwrap_err();
+        assert!(error.to_string().contains("line 3"), "{}", error);
+    }
+
+    #[test]
+    fn find_composite_should_intersect_matches_of_and_par() {
+        // Prepare
+        let mut package = create_package();
+        let patch = r#"<mod:findComposite>
+	<mod:par op="AND">
+		<mod:findLike type="weaponBlueprint"/>
+		<mod:findWithChildLike child-type="shots">
+			<mod:selector>3</mod:selector>
+		</mod:findWithChildLike>
+	</mod:par>
+	<mod:setAttributes patched="true"/>
+</mod:findComposite>"#;
+
+        // Execute
+        let report = package.patch_xml(INNER_PATH, patch.as_bytes()).unwrap();
+
+        // Check
+        assert!(report.unmatched_selectors().is_empty());
+        let content = content_string(&package);
+        assert_eq!(1, content.matches(r#"patched="true""#).count());
+        assert!(content.contains(r#"<weaponBlueprint name="LASER_BURST_2" patched="true">"#));
+    }
+
+    #[test]
+    fn find_composite_should_unite_matches_of_or_par_in_document_order() {
+        // Prepare
+        let mut package = create_package();
+        let patch = r#"<mod:findComposite limit="2">
+	<mod:par op="OR">
+		<mod:findName type="blueprintList" name="WEAPONS_LASER"/>
+		<mod:par op="AND">
+			<mod:findName type="weaponBlueprint" name="MISSILES_1"/>
+			<mod:findLike type="weaponBlueprint"/>
+		</mod:par>
+		<mod:findName type="weaponBlueprint" name="LASER_BURST_1"/>
+	</mod:par>
+	<mod:setAttributes patched="true"/>
+</mod:findComposite>"#;
+
+        // Execute
+        package.patch_xml(INNER_PATH, patch.as_bytes()).unwrap();
+
+        // Check
+        let content = content_string(&package);
+        assert_eq!(2, content.matches(r#"patched="true""#).count());
+        assert!(content.contains(r#"<weaponBlueprint name="LASER_BURST_1" patched="true">"#));
+        assert!(content.contains(r#"<weaponBlueprint name="MISSILES_1" patched="true">"#));
+        assert!(content.contains(r#"<blueprintList name="WEAPONS_LASER">"#));
+    }
+
+    #[test]
+    fn find_composite_should_fail_without_valid_par() {
+        let mut package = create_package();
+
+        let missing_par = package.patch_xml(INNER_PATH, br#"<mod:findComposite><mod:removeTag/></mod:findComposite>"#);
+        let invalid_op = package.patch_xml(INNER_PATH, br#"<mod:findComposite><mod:par op="XOR"/></mod:findComposite>"#);
+
+        assert!(missing_par.unwrap_err().to_string().contains("missing nested <mod:par> tag"));
+        assert!(invalid_op.unwrap_err().to_string().contains("'op' must be 'AND' or 'OR'"));
+    }
+
+    #[test]
+    fn patch_should_fail_on_unsupported_directive() {
+        let mut package = create_package();
+
+        let result = package.patch_xml(INNER_PATH, br#"<mod:findEverything/>"#);
+
+        assert_eq!("invalid patch directive <mod:findEverything> is not supported", result.unwrap_err().to_string());
+    }
+
+    #[test]
+    fn patch_should_recover_from_malformed_vanilla_xml() {
+        // Prepare
+        let mut package = Package::new();
+        package.add_entry(PackageEntry::from_string(INNER_PATH, r#"<!-- Weapons -- lasers first -->
+<weaponBlueprint name="LASER_BURST_1">
+	<type>LASER</type>
+</weaponBlueprnt>
+</blueprintList>
+<weaponBlueprint name="MISSILES_1"><type>MISSILES</type></weaponBlueprint>
+"#)).unwrap();
+        let patch = r#"<mod:findName type="weaponBlueprint" name="LASER_BURST_1">
+	<mod:setAttributes patched="true"/>
+</mod:findName>"#;
+
+        // Execute
+        package.patch_xml(INNER_PATH, patch.as_bytes()).unwrap();
+
+        // Check
+        let content = content_string(&package);
+        assert!(content.starts_with("<!-- Weapons -- lasers first -->"));
+        assert!(content.contains("<weaponBlueprint name=\"LASER_BURST_1\" patched=\"true\">\n\t<type>LASER</type>\n</weaponBlueprint>"));
+        assert!(content.contains(r#"<weaponBlueprint name="MISSILES_1"><type>MISSILES</type></weaponBlueprint>"#));
+    }
+}